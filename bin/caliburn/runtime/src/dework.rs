@@ -0,0 +1,320 @@
+//! The DeWork call surface.
+//!
+//! Every action users take on identities, tasks, judgements and reputation is dispatched
+//! through this module. Its call indices are frozen for each `CALL_INDEX_VERSION`: calls are
+//! only ever appended, and a call that changes its arguments gets a new name and index.
+//! The calls take no results of earlier calls other than task hashes, so they can be chained
+//! in a `utility::batch`.
+//!
+//! The same actions used to be exposed by the `Caliburn` proxy and directly by the DeWork
+//! pallets. `CheckLegacyCall` keeps accepting those calls until `LegacyCallsEnd`, which the
+//! migration to this module's storage v1 sets one `LegacyCallWindow` after the upgrade.
+
+use codec::{Decode, Encode};
+use frame_support::{decl_event, decl_module, decl_storage, traits::Get, StorageValue};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError},
+};
+use sp_std::prelude::*;
+
+use crate::{identity, judge_pool, reputation, task_board};
+use crate::migration::VersionNumber;
+use crate::reputation::BootstrapProof;
+use crate::task_board::TemplateOverrides;
+
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 1;
+
+/// The current version of this module's call indices.
+pub const CALL_INDEX_VERSION: u32 = 1;
+
+pub trait Trait: task_board::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// How long legacy calls keep being accepted after the upgrade introducing this module.
+	type LegacyCallWindow: Get<Self::BlockNumber>;
+}
+
+decl_event! {
+	pub enum Event<T>
+	where
+		BlockNumber = <T as system::Trait>::BlockNumber,
+	{
+		/// params: last block legacy calls are accepted at
+		LegacyCallsClosing(BlockNumber),
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as DeWork {
+		/// First block at which legacy calls are rejected.
+		LegacyCallsEnd get(legacy_calls_end) config(): T::BlockNumber;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// The version of the call indices of this module.
+		const CallIndexVersion: u32 = CALL_INDEX_VERSION;
+
+		fn deposit_event() = default;
+
+		fn on_initialize(n: T::BlockNumber) {
+			Self::ensure_storage_upgraded(n);
+		}
+
+		// Identity.
+
+		/// Register the sender's profile
+		pub fn register_user(origin, name: Vec<u8>, email: Vec<u8>, description: Vec<u8>, additional: Vec<u8>, kyc_hash: Vec<u8>) {
+			<identity::Module<T>>::do_register_user(origin, name, email, description, additional, kyc_hash)?;
+		}
+
+		/// Create a new subject issued by the sender
+		pub fn create_subject(origin) {
+			<identity::Module<T>>::do_create_subject(origin)?;
+		}
+
+		/// Issue a credential on a subject of the sender
+		pub fn issue(origin, to: T::AccountId, subject: u32) {
+			<identity::Module<T>>::do_issue(origin, to, subject)?;
+		}
+
+		/// Revoke a credential on a subject of the sender
+		pub fn revoke_credential(origin, to: T::AccountId, subject: u32) {
+			<identity::Module<T>>::do_revoke_credential(origin, to, subject)?;
+		}
+
+		/// Verify a credential
+		pub fn verify_credential(origin, holder: T::AccountId, subject: u32) {
+			<identity::Module<T>>::do_verify_credential(origin, holder, subject)?;
+		}
+
+		/// Attest the KYC of an identity; only the credential manager can
+		pub fn attest_kyc(origin, who: T::AccountId, verified: bool) {
+			<identity::Module<T>>::do_attest_kyc(origin, who, verified)?;
+		}
+
+		// Tasks.
+
+		/// Publish a task
+		pub fn publish_task(origin, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) {
+			<task_board::Module<T>>::publish_task(origin, desc, min_rep, pay, judge_pay, req_subjects)?;
+		}
+
+		/// Publish a sub-task funded out of the pay of a parent task
+		pub fn publish_sub_task(origin, parent: T::Hash, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>, blocked_by: Vec<T::Hash>) {
+			<task_board::Module<T>>::publish_sub_task(origin, parent, desc, min_rep, pay, judge_pay, req_subjects, blocked_by)?;
+		}
+
+		/// Withdraw a task nobody has claimed yet
+		pub fn cancel_task(origin, hash: T::Hash) {
+			<task_board::Module<T>>::cancel_task(origin, hash)?;
+		}
+
+		/// Claim a task for the sender and `players`
+		pub fn claim_task(origin, hash: T::Hash, players: Vec<T::AccountId>) {
+			<task_board::Module<T>>::claim_task(origin, hash, players)?;
+		}
+
+		/// Deliver a claimed task
+		pub fn deliver_task(origin, hash: T::Hash, delivery_certificate: T::Hash) {
+			<task_board::Module<T>>::claim_deliver_task(origin, hash, delivery_certificate)?;
+		}
+
+		/// Dispute a delivery
+		pub fn request_for_judge(origin, hash: T::Hash) {
+			<task_board::Module<T>>::request_for_judge(origin, hash)?;
+		}
+
+		/// Push a task to its final state
+		pub fn task_to_final(origin, hash: T::Hash) {
+			<task_board::Module<T>>::task_to_final(origin, hash)?;
+		}
+
+		/// Rate the other side of a finished task
+		pub fn rate_task(origin, hash: T::Hash, rating: u8, review: T::Hash) {
+			<task_board::Module<T>>::rate_task(origin, hash, rating, review)?;
+		}
+
+		/// Only claim tasks whose issuer has at least `score`
+		pub fn set_min_issuer_score(origin, score: u32) {
+			<task_board::Module<T>>::set_min_issuer_score(origin, score)?;
+		}
+
+		/// Store a reusable task template
+		pub fn create_template(origin, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) {
+			<task_board::Module<T>>::create_template(origin, desc, min_rep, pay, judge_pay, req_subjects)?;
+		}
+
+		/// Remove a template together with its schedule
+		pub fn remove_template(origin, id: u32) {
+			<task_board::Module<T>>::remove_template(origin, id)?;
+		}
+
+		/// Publish a task from a template, replacing the given fields
+		pub fn publish_from_template(origin, id: u32, overrides: TemplateOverrides<T::Balance>) {
+			<task_board::Module<T>>::publish_from_template(origin, id, overrides)?;
+		}
+
+		/// Publish a template every `period` blocks
+		pub fn schedule_template(origin, id: u32, period: T::BlockNumber, funds: T::Balance) {
+			<task_board::Module<T>>::schedule_template(origin, id, period, funds)?;
+		}
+
+		/// Reserve more funds for a recurring schedule
+		pub fn fund_schedule(origin, id: u32, amount: T::Balance) {
+			<task_board::Module<T>>::fund_schedule(origin, id, amount)?;
+		}
+
+		/// Stop a recurring schedule
+		pub fn cancel_schedule(origin, id: u32) {
+			<task_board::Module<T>>::cancel_schedule(origin, id)?;
+		}
+
+		// Judgements.
+
+		/// Attach evidence to a judgement that is still processing
+		pub fn submit_evidence(origin, hash: T::Hash, content: T::Hash, statement: Vec<u8>) {
+			<judge_pool::Module<T>>::submit_evidence(origin, hash, content, statement)?;
+		}
+
+		/// Vote on a judgement, attaching the hash of the rationale behind the vote
+		pub fn exec_judgement(origin, hash: T::Hash, result: u32, rationale: T::Hash) {
+			<judge_pool::Module<T>>::exec_judgement(origin, hash, result, rationale)?;
+		}
+
+		/// Verify that a judgement is done
+		pub fn verify_judgement_done(origin, hash: T::Hash) {
+			<judge_pool::Module<T>>::verify_judgement_Done(origin, hash)?;
+		}
+
+		// Reputation.
+
+		/// Receive the initial score by proving a distinct identity
+		pub fn bootstrap(origin, proof: BootstrapProof) {
+			<reputation::Module<T>>::bootstrap(origin, proof)?;
+		}
+
+		/// Give up a bonded score and its links
+		pub fn unbond(origin) {
+			<reputation::Module<T>>::unbond(origin)?;
+		}
+
+		/// Offer `who` to share the score of the sender
+		pub fn link_account(origin, who: T::AccountId) {
+			<reputation::Module<T>>::link_account(origin, who)?;
+		}
+
+		/// Accept the link offered by `primary`
+		pub fn accept_link(origin, primary: T::AccountId) {
+			<reputation::Module<T>>::accept_link(origin, primary)?;
+		}
+
+		/// Stop `who` sharing the score of its primary account
+		pub fn unlink_account(origin, who: T::AccountId) {
+			<reputation::Module<T>>::unlink_account(origin, who)?;
+		}
+
+		// Insurance.
+
+		/// Ask governance for compensation out of the insurance pool
+		pub fn appeal(origin, hash: T::Hash, statement: T::Hash) {
+			<task_board::Module<T>>::appeal(origin, hash, statement)?;
+		}
+
+		// Deadlines.
+
+		/// Stop a published task being claimed after `deadline`
+		pub fn set_claim_deadline(origin, hash: T::Hash, deadline: Option<T::BlockNumber>) {
+			<task_board::Module<T>>::set_claim_deadline(origin, hash, deadline)?;
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Whether calls superseded by this module are still accepted.
+	pub fn accepts_legacy_calls() -> bool {
+		<system::Module<T>>::block_number() < Self::legacy_calls_end()
+	}
+
+	fn ensure_storage_upgraded(now: T::BlockNumber) {
+		<StorageVersion>::mutate(|version| {
+			if *version >= STORAGE_VERSION { return }
+			Self::migrate_to_v1(version, now);
+		});
+	}
+
+	// migrate storage from v0 to v1.
+	//
+	// v0 is a chain that had no DeWork facade yet; its clients keep working until
+	// `LegacyCallWindow` blocks after the upgrade.
+	fn migrate_to_v1(version: &mut VersionNumber, now: T::BlockNumber) {
+		if *version != 0 { return }
+		*version += 1;
+
+		let end = now + T::LegacyCallWindow::get();
+		<LegacyCallsEnd<T>>::put(end);
+		Self::deposit_event(RawEvent::LegacyCallsClosing(end));
+
+		frame_support::print("Finished migrating DeWork storage to v1.");
+	}
+}
+
+/// Tells the calls superseded by the DeWork call surface.
+pub trait LegacyCall {
+	/// Whether this call, or any call it dispatches, is superseded.
+	fn is_legacy_call(&self) -> bool;
+}
+
+/// Reject legacy calls once `LegacyCallsEnd` is reached.
+///
+/// Neither encoded nor signed, so adding it keeps transactions of existing clients valid.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckLegacyCall<T: Trait + Send + Sync>(sp_std::marker::PhantomData<T>);
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for CheckLegacyCall<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckLegacyCall")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Trait + Send + Sync> CheckLegacyCall<T> {
+	pub fn new() -> Self {
+		Self(sp_std::marker::PhantomData)
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckLegacyCall<T> where
+	<T as system::Trait>::Call: LegacyCall,
+{
+	type AccountId = T::AccountId;
+	type Call = <T as system::Trait>::Call;
+	type AdditionalSigned = ();
+	type DispatchInfo = frame_support::weights::DispatchInfo;
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> { Ok(()) }
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: Self::DispatchInfo,
+		_len: usize,
+	) -> TransactionValidity {
+		if call.is_legacy_call() && !<Module<T>>::accepts_legacy_calls() {
+			return InvalidTransaction::Call.into()
+		}
+		Ok(Default::default())
+	}
+}
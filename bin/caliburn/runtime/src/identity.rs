@@ -13,6 +13,10 @@ use sp_runtime::{DispatchResult};
 use sp_std::prelude::*;
 use system::{self, ensure_signed};
 
+use crate::migration::VersionNumber;
+
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 1;
 
 pub trait Trait: system::Trait + timestamp::Trait + balances::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
@@ -62,6 +66,8 @@ decl_event! {
         SubjectCreated(AccountId, u32),
         //A new identity is created.
         IdentityCreated(AccountId),
+        // The KYC attestation of an account changed - holder, verified.
+        KycAttested(AccountId, bool),
 	}
 }
 
@@ -94,11 +100,16 @@ decl_storage! {
         // Issuer to Subject mapping.
         Subjects get(subjects) config(): map u32 => T::AccountId;
 
+        // Metadata of a subject.
+        SubjectDetails get(subject_details): map u32 => SkillSubjectDetails<T::AccountId>;
+
         // Credentials store.
         // Mapping (holder, subject) to Credential.
         Credentials get(credentials): map (T::AccountId, u32) => Credential<T::Moment, T::AccountId>;
         //credential manager
         CredManager get(cred_manager) config(): T::AccountId;
+        // Accounts whose KYC the credential manager attested.
+        KycVerified get(kyc_verified): map T::AccountId => bool;
 
         //Reputation: default is 50.
         Reputation get(rep) config(): map T::AccountId => u32;
@@ -109,6 +120,32 @@ decl_storage! {
         IdentityIndex: map T::AccountId => u64;
 
         Nonce: u64;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
+	}
+	add_extra_genesis {
+		/// Metadata of the genesis subjects: subject, name, tags, description.
+		config(subject_details): Vec<(u32, Vec<u8>, Vec<u8>, Vec<u8>)>;
+		/// Credentials held at genesis: holder, subject.
+		config(credentials): Vec<(T::AccountId, u32)>;
+		build(|config| {
+			for (subject, name, tags, description) in &config.subject_details {
+				<SubjectDetails<T>>::insert(subject, SkillSubjectDetails {
+					issuer: <Subjects<T>>::get(subject),
+					name: name.clone(),
+					tags: tags.clone(),
+					description: description.clone(),
+				});
+			}
+			for (holder, subject) in &config.credentials {
+				<Credentials<T>>::insert((holder.clone(), *subject), Credential {
+					subject: *subject,
+					when: Default::default(),
+					by: <Subjects<T>>::get(subject),
+				});
+			}
+		});
 	}
 }
 
@@ -116,6 +153,21 @@ decl_module! {
 	pub struct Module < T: Trait > for enum Call where origin: T::Origin {
 		type Error = Error < T >;
 		fn deposit_event() = default;
+
+		fn on_initialize(_n: T::BlockNumber) {
+			Self::ensure_storage_upgraded();
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Ensures storage is upgraded to the layout expected by `STORAGE_VERSION`.
+	fn ensure_storage_upgraded() {
+		<StorageVersion>::mutate(|version| {
+			if *version >= STORAGE_VERSION { return }
+			// v0 to v1 only introduces the version itself.
+			*version = STORAGE_VERSION;
+		});
 	}
 }
 
@@ -206,6 +258,20 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Attest or withdraw the KYC of `who`.
+	/// Only the credential manager can call this function.
+	pub fn do_attest_kyc(origin: T::Origin, who: T::AccountId, verified: bool) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		ensure!(sender == <CredManager<T>>::get(), "Unauthorized.");
+		if verified {
+			<KycVerified<T>>::insert(&who, true);
+		} else {
+			<KycVerified<T>>::remove(&who);
+		}
+		Self::deposit_event(RawEvent::KycAttested(who, verified));
+		Ok(())
+	}
+
 	pub fn check_credential(player: &T::AccountId, sub: &u32) -> bool {
 		<Credentials<T>>::exists((player, sub))
 	}
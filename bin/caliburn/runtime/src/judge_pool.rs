@@ -10,19 +10,26 @@ use frame_support::{
 	traits::{
 		Currency,
 		ExistenceRequirement,
+		ReservableCurrency,
 		WithdrawReason,
 		WithdrawReasons,
 	},
 };
-use sp_runtime::{DispatchError, DispatchResult, RuntimeDebug, traits::{Hash, Zero}};
+use sp_runtime::{DispatchError, DispatchResult, RuntimeDebug, traits::{EnsureOrigin, Hash, Zero}};
 use sp_std::{self, prelude::*};
 use sp_std::result::Result;
-use system::{self, ensure_root, ensure_signed};
+use system::{self, ensure_signed};
 
+use crate::migration::{self, VersionNumber};
 use crate::reputation;
 
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 1;
+
 pub trait Trait: system::Trait + balances::Trait + reputation::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Origin allowed to change the judge pool parameters.
+	type ParameterOrigin: EnsureOrigin<Self::Origin>;
 }
 
 #[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
@@ -79,6 +86,17 @@ impl<T: Trait> Judgement<T> {
 	}
 }
 
+/// A content hash with a short statement attached to a dispute by one of its parties.
+#[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
+pub struct Evidence<AccountId, Hash, Balance> {
+	pub submitter: AccountId,
+	pub content: Hash,
+	pub statement: Vec<u8>,
+	/// Reserved on the submitter until the judgement is done; the evidence itself is kept until
+	/// the dispute is pruned.
+	pub deposit: Balance,
+}
+
 #[derive(Encode, Decode, Copy, Clone, RuntimeDebug, Eq, PartialEq)]
 pub enum JudgeKind {
 	UnCreated,
@@ -97,19 +115,80 @@ decl_event! {
 	where
 	AccountId = <T as system::Trait>::AccountId,
 	Hash = <T as system::Trait>::Hash,
+	Balance = <T as balances::Trait>::Balance,
+	BlockNumber = <T as system::Trait>::BlockNumber,
 	{
 		BeginJudge(AccountId, Hash),
+		/// params: submitter, task-hash, content hash
+		EvidenceSubmitted(AccountId, Hash, Hash),
+		/// params: judge, task-hash, rationale hash
+		JudgeVoted(AccountId, Hash, Hash),
+		/// params: new judge size
+		JudgeSizeChanged(u32),
+		/// params: new threshold
+		ThresholdChanged(u32),
+		/// params: new deposit, new max evidence per party, new max statement length
+		EvidenceLimitsChanged(Balance, u32, u32),
+		/// params: new warmup, new period, new max panels per period
+		PanelLimitsChanged(BlockNumber, BlockNumber, u32),
+		/// params: new voting period
+		VotingPeriodChanged(BlockNumber),
+		/// params: task-hash, votes cast before the judgement was closed
+		JudgementClosed(Hash, u32),
+		/// params: new dispute retention
+		DisputeRetentionChanged(BlockNumber),
+		/// params: task-hash
+		DisputePruned(Hash),
 	}
 
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as JudgePoolModule {
-		Judges: map T::Hash => Judgement<T>;
+		pub Judges get(judges): map T::Hash => Judgement<T>;
 		JudgeSize get(judge_size) config(): u32;
 		Threshold get(threshold) config(): u32;
-		PledgePool: map (T::AccountId, T::Hash) => T::Balance;
+		PledgePool get(pledge_pool): map (T::AccountId, T::Hash) => T::Balance;
 		JudgeResult: map T::Hash => u8;
+
+		/// Accounts allowed to submit evidence to a judgement.
+		pub Parties get(parties): map T::Hash => Vec<T::AccountId>;
+		/// Evidence submitted to a judgement.
+		pub Evidences get(evidences): map T::Hash => Vec<Evidence<T::AccountId, T::Hash, T::Balance>>;
+		/// Rationale hash attached by a judge to their vote.
+		Rationales get(rationale): map (T::Hash, T::AccountId) => Option<T::Hash>;
+		/// Deposit reserved for every piece of evidence.
+		EvidenceDeposit get(evidence_deposit) config(): T::Balance;
+		/// Maximum number of pieces of evidence each party can submit to a judgement.
+		MaxEvidence get(max_evidence) config(): u32 = 8;
+		/// Maximum length of an evidence statement.
+		MaxStatementLen get(max_statement_len) config(): u32 = 256;
+
+		/// Blocks a newly bootstrapped identity waits before it can join a judge panel.
+		JudgeWarmup get(judge_warmup) config(): T::BlockNumber;
+		/// Length of the window in which panel joins are counted; zero disables the limit.
+		PanelPeriod get(panel_period) config(): T::BlockNumber;
+		/// Maximum number of panels an identity can join per `PanelPeriod`.
+		MaxPanelsPerPeriod get(max_panels_per_period) config(): u32 = 4;
+		/// Start of the current window and panels joined in it, by primary account.
+		PanelJoins get(panel_joins): map T::AccountId => (T::BlockNumber, u32);
+
+		/// Blocks a judgement waits for a full panel before it can be closed with the votes cast;
+		/// zero never closes judgements early.
+		pub VotingPeriod get(voting_period) config(): T::BlockNumber;
+		/// Block at which each judgement began.
+		pub JudgementStarted get(judgement_started): map T::Hash => Option<T::BlockNumber>;
+
+		/// Blocks the parties, evidence and rationales of a done judgement are kept for appeals;
+		/// zero keeps them forever.
+		pub DisputeRetention get(dispute_retention): T::BlockNumber;
+		/// Block at which each judgement was done, while its dispute is kept.
+		pub JudgementDone get(judgement_done): map T::Hash => Option<T::BlockNumber>;
+		/// Number of unresolved appeals that need the dispute of a judgement.
+		DisputeHolds get(dispute_holds): map T::Hash => u32;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
 	}
 }
 
@@ -123,6 +202,16 @@ decl_error! {
 		JudgeSizeFull,
 		JudgeRepeat,
 		JudgeProcessing,
+		EvidencePartyInvalid,
+		EvidenceLimitReached,
+		EvidenceStatementTooLong,
+		RationaleMissing,
+		JudgeNotBootstrapped,
+		JudgeWarmingUp,
+		JudgeRateLimited,
+		JudgeIdentityRepeat,
+		JudgementNotStalled,
+		DisputeRetained,
 	}
 }
 
@@ -131,39 +220,111 @@ decl_module! {
 		type Error = Error < T >;
 		fn deposit_event() = default;
 
+		fn on_initialize(_n: T::BlockNumber) {
+			Self::ensure_storage_upgraded();
+		}
+
 		/// Changing judge_size through governance
 		fn change_judge_size(origin, size: u32) {
-			let root = ensure_root(origin)?;
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
 			<JudgeSize>::put(size);
+			Self::deposit_event(RawEvent::JudgeSizeChanged(size));
 		}
 
 		/// Changing threshold through governance
 		fn change_judge_threshold(origin, threshold: u32) {
-			let root = ensure_root(origin)?;
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
 			<Threshold>::put(threshold);
+			Self::deposit_event(RawEvent::ThresholdChanged(threshold));
 		}
 
-		/// Execute judge task
-		pub fn exec_judgement(origin, hash: T::Hash, result: u32) {
-			Self::do_exec_judgement(origin, hash, result)?;
+		/// Changing the evidence deposit and limits through governance
+		fn set_evidence_limits(origin, deposit: T::Balance, max_evidence: u32, max_statement_len: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<EvidenceDeposit<T>>::put(deposit);
+			<MaxEvidence>::put(max_evidence);
+			<MaxStatementLen>::put(max_statement_len);
+			Self::deposit_event(RawEvent::EvidenceLimitsChanged(deposit, max_evidence, max_statement_len));
+		}
+
+		/// Changing how fast identities can join judge panels through governance
+		fn set_panel_limits(origin, warmup: T::BlockNumber, period: T::BlockNumber, max_panels: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<JudgeWarmup<T>>::put(warmup);
+			<PanelPeriod<T>>::put(period);
+			<MaxPanelsPerPeriod>::put(max_panels);
+			Self::deposit_event(RawEvent::PanelLimitsChanged(warmup, period, max_panels));
+		}
+
+		/// Changing how long judgements wait for votes through governance
+		fn set_voting_period(origin, period: T::BlockNumber) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<VotingPeriod<T>>::put(period);
+			Self::deposit_event(RawEvent::VotingPeriodChanged(period));
+		}
+
+		/// Execute judge task, attaching the hash of the rationale behind the vote
+		pub fn exec_judgement(origin, hash: T::Hash, result: u32, rationale: T::Hash) {
+			Self::do_exec_judgement(origin, hash, result, rationale)?;
+		}
+
+		/// Attach evidence to a judgement that is still processing
+		pub fn submit_evidence(origin, hash: T::Hash, content: T::Hash, statement: Vec<u8>) {
+			Self::do_submit_evidence(origin, hash, content, statement)?;
 		}
 
 		/// Verify that the task has been completed
 		pub fn verify_judgement_Done(origin, hash: T::Hash) {
 			Self::do_view_judgement_result(origin, hash)?;
 		}
+
+		/// Changing how long disputes are kept for appeals through governance
+		fn set_dispute_retention(origin, retention: T::BlockNumber) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<DisputeRetention<T>>::put(retention);
+			Self::deposit_event(RawEvent::DisputeRetentionChanged(retention));
+		}
+
+		/// Remove the dispute of a judgement done for longer than `DisputeRetention`
+		pub fn prune_dispute(origin, hash: T::Hash) {
+			let _ = ensure_signed(origin)?;
+			Self::do_prune_dispute(&hash)?;
+		}
 	}
 }
 
 
 impl<T: Trait> Module<T> {
-	pub fn do_exec_judgement(origin: T::Origin, hash: T::Hash, result: u32) -> DispatchResult {
+	pub fn do_exec_judgement(origin: T::Origin, hash: T::Hash, result: u32, rationale: T::Hash) -> DispatchResult {
 		let sender = ensure_signed(origin)?;
+		ensure!(Self::view_progress(hash.clone()) == JudgeKind::Processing, Error::<T>::JudgeKindInvalid);
+		ensure!(rationale != T::Hash::default(), Error::<T>::RationaleMissing);
 		ensure!(Self::verify_judge_for_hash(sender.clone(), &hash), Error::<T>::JudgeVerifyFaild);
+		Self::ensure_distinct_identity(&sender, &hash)?;
 		let judgement: Judgement<T> = <Judges<T>>::get(&hash);
 		<balances::Module<T> as Currency<_>>::withdraw(&sender, judgement.pledge_limit, WithdrawReasons::all(), ExistenceRequirement::KeepAlive)?;
 		<PledgePool<T>>::insert((sender.clone(), hash.clone()), judgement.pledge_limit.clone());
+		<Rationales<T>>::insert((hash.clone(), sender.clone()), rationale.clone());
 		Self::add_judge(sender.clone(), &hash, result)?;
+		Self::note_panel_join(&<reputation::Module<T>>::primary(&sender));
+		Self::deposit_event(RawEvent::JudgeVoted(sender, hash, rationale));
+		Ok(())
+	}
+
+	pub fn do_submit_evidence(origin: T::Origin, hash: T::Hash, content: T::Hash, statement: Vec<u8>) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		ensure!(Self::view_progress(hash.clone()) == JudgeKind::Processing, Error::<T>::JudgeKindInvalid);
+		ensure!(<Parties<T>>::get(&hash).contains(&sender), Error::<T>::EvidencePartyInvalid);
+		ensure!((statement.len() as u32) <= Self::max_statement_len(), Error::<T>::EvidenceStatementTooLong);
+		let mut evidences = <Evidences<T>>::get(&hash);
+		let submitted = evidences.iter().filter(|e| e.submitter == sender).count() as u32;
+		ensure!(submitted < Self::max_evidence(), Error::<T>::EvidenceLimitReached);
+
+		let deposit = Self::evidence_deposit();
+		<balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, deposit)?;
+		evidences.push(Evidence { submitter: sender.clone(), content: content.clone(), statement, deposit });
+		<Evidences<T>>::insert(&hash, evidences);
+		Self::deposit_event(RawEvent::EvidenceSubmitted(sender, hash, content));
 		Ok(())
 	}
 
@@ -187,13 +348,42 @@ impl<T: Trait> Module<T> {
 impl<T: Trait> Module<T> {
 	/// The application layer processes the task corresponding to the hash,
 	/// obtains the credentials of the success of the task, and makes a decision
-	pub fn begin_judgement(hash: T::Hash, sender: T::AccountId, pledge_limit: T::Balance) -> DispatchResult {
+	///
+	/// `parties` are the accounts allowed to submit evidence while the judgement is processing.
+	pub fn begin_judgement(hash: T::Hash, sender: T::AccountId, pledge_limit: T::Balance, parties: Vec<T::AccountId>) -> DispatchResult {
 		let mut judge = Judgement::default();
 		judge.threshold = Self::threshold();
 		judge.pool_size = Self::judge_size();
 		judge.pledge_limit = pledge_limit;
 		judge.sender = sender;
 		Self::save_judgement(&hash, &judge)?;
+		<Parties<T>>::insert(&hash, parties);
+		<JudgementStarted<T>>::insert(&hash, <system::Module<T>>::block_number());
+		Ok(())
+	}
+
+	/// Block after which a processing judgement can be closed with the votes cast so far
+	///
+	/// `None` when the judgement is not processing, has no recorded start or `VotingPeriod` is zero.
+	pub fn voting_deadline(hash: &T::Hash) -> Option<T::BlockNumber> {
+		let period = Self::voting_period();
+		if period.is_zero() || Self::view_progress(hash.clone()) != JudgeKind::Processing {
+			return None;
+		}
+		Self::judgement_started(hash).map(|started| started + period)
+	}
+
+	/// Close a judgement whose voting deadline passed without a full panel
+	///
+	/// The votes cast decide the result; ties, including a panel nobody joined, go against the delivery.
+	pub fn close_stalled(hash: &T::Hash) -> DispatchResult {
+		let deadline = Self::voting_deadline(hash).ok_or(Error::<T>::JudgementNotStalled)?;
+		ensure!(<system::Module<T>>::block_number() >= deadline, Error::<T>::JudgementNotStalled);
+		let mut judgement: Judgement<T> = <Judges<T>>::get(hash);
+		judgement.kind = JudgeKind::Done;
+		Self::save_judgement(hash, &judgement)?;
+		Self::close_dispute(hash);
+		Self::deposit_event(RawEvent::JudgementClosed(hash.clone(), judgement.judges.len() as u32));
 		Ok(())
 	}
 
@@ -216,6 +406,28 @@ impl<T: Trait> Module<T> {
 	}
 }
 
+impl<T: Trait> Module<T> {
+	/// Ensures storage is upgraded to the layout expected by `STORAGE_VERSION`.
+	fn ensure_storage_upgraded() {
+		<StorageVersion>::mutate(|version| {
+			if *version >= STORAGE_VERSION { return }
+			Self::migrate_to_v1(version);
+		});
+	}
+
+	// migrate storage from v0 to v1.
+	//
+	// this re-encodes every `Judges` entry, dropping judgements that no longer decode.
+	fn migrate_to_v1(version: &mut VersionNumber) {
+		if *version != 0 { return }
+		*version += 1;
+
+		migration::translate_values::<Judges<T>, Judgement<T>, _, _>(|judgement| judgement);
+
+		frame_support::print("Finished migrating JudgePoolModule storage to v1.");
+	}
+}
+
 impl<T: Trait> Module<T> {
 	/// Store judge tasks
 	pub fn save_judgement(hash: &T::Hash, judgement: &Judgement<T>) -> DispatchResult {
@@ -236,7 +448,53 @@ impl<T: Trait> Module<T> {
 		let mut judgement: Judgement<T> = <Judges<T>>::get(hash);
 		ensure!((judgement.judges.len() as u32) < (Self::judge_size() as u32), Error::<T>::JudgeSizeFull);
 		judgement.add_judge(sender, Self::handler_result(result))?;
-		Self::save_judgement(hash, &judgement);
+		Self::save_judgement(hash, &judgement)?;
+		if judgement.kind == JudgeKind::Done {
+			Self::close_dispute(hash);
+		}
+		Ok(())
+	}
+
+	/// Unreserve the evidence deposits of a finished judgement, keeping its dispute for appeals
+	fn close_dispute(hash: &T::Hash) {
+		for evidence in <Evidences<T>>::get(hash) {
+			<balances::Module<T> as ReservableCurrency<_>>::unreserve(&evidence.submitter, evidence.deposit);
+		}
+		<JudgementDone<T>>::insert(hash, <system::Module<T>>::block_number());
+	}
+
+	/// Whether the dispute of the done judgement `hash` is still kept
+	pub fn dispute_kept(hash: &T::Hash) -> bool {
+		<JudgementDone<T>>::exists(hash)
+	}
+
+	/// Keep the dispute of `hash` until a matching `release_dispute`, for an appeal to be resolved
+	pub fn hold_dispute(hash: &T::Hash) {
+		<DisputeHolds<T>>::mutate(hash, |holds| *holds += 1);
+	}
+
+	/// Release a hold taken with `hold_dispute`
+	pub fn release_dispute(hash: &T::Hash) {
+		<DisputeHolds<T>>::mutate(hash, |holds| *holds = holds.saturating_sub(1));
+	}
+
+	/// Remove the parties, evidence and rationales of a judgement once `DisputeRetention` passed and
+	/// no appeal holds them
+	pub fn do_prune_dispute(hash: &T::Hash) -> DispatchResult {
+		let retention = Self::dispute_retention();
+		let done = Self::judgement_done(hash).ok_or(Error::<T>::JudgementNotFound)?;
+		ensure!(!retention.is_zero(), Error::<T>::DisputeRetained);
+		ensure!(<system::Module<T>>::block_number() >= done + retention, Error::<T>::DisputeRetained);
+		ensure!(Self::dispute_holds(hash) == 0, Error::<T>::DisputeRetained);
+
+		<Evidences<T>>::remove(hash);
+		<Parties<T>>::remove(hash);
+		for (judge, _) in <Judges<T>>::get(hash).judges {
+			<Rationales<T>>::remove((hash.clone(), judge));
+		}
+		<JudgementDone<T>>::remove(hash);
+		<DisputeHolds<T>>::remove(hash);
+		Self::deposit_event(RawEvent::DisputePruned(hash.clone()));
 		Ok(())
 	}
 
@@ -251,6 +509,44 @@ impl<T: Trait> Module<T> {
 			return false;
 		}
 		let judgement: Judgement<T> = <Judges<T>>::get(hash);
-		rep.score >= Self::threshold() && (judgement.judges.len() as u32) < Self::judge_size() && judgement.verify_repeat(judge)
+		judgement.kind == JudgeKind::Processing && rep.score >= Self::threshold() && (judgement.judges.len() as u32) < Self::judge_size() && judgement.verify_repeat(judge)
+	}
+
+	/// Ensure `judge` is a bootstrapped identity past its warmup and panel rate limit, and that
+	/// neither it nor an account linked to it is a party or already sits on the panel of `hash`
+	pub fn ensure_distinct_identity(judge: &T::AccountId, hash: &T::Hash) -> DispatchResult {
+		let primary = <reputation::Module<T>>::primary(judge);
+		ensure!(<reputation::Module<T>>::is_bootstrapped(judge), Error::<T>::JudgeNotBootstrapped);
+
+		let now = <system::Module<T>>::block_number();
+		let bootstrapped_at = <reputation::Module<T>>::bootstrapped_at(&primary);
+		ensure!(now >= bootstrapped_at + Self::judge_warmup(), Error::<T>::JudgeWarmingUp);
+
+		let period = Self::panel_period();
+		if !period.is_zero() {
+			let (start, joined) = Self::panel_joins(&primary);
+			ensure!(now >= start + period || joined < Self::max_panels_per_period(), Error::<T>::JudgeRateLimited);
+		}
+
+		let judgement: Judgement<T> = <Judges<T>>::get(hash);
+		let mut others = judgement.judges.into_iter().map(|(j, _)| j).chain(<Parties<T>>::get(hash));
+		ensure!(!others.any(|a| <reputation::Module<T>>::primary(&a) == primary), Error::<T>::JudgeIdentityRepeat);
+		Ok(())
+	}
+
+	/// Count a panel joined by `primary` against its rate limit
+	fn note_panel_join(primary: &T::AccountId) {
+		let period = Self::panel_period();
+		if period.is_zero() {
+			return;
+		}
+		let now = <system::Module<T>>::block_number();
+		<PanelJoins<T>>::mutate(primary, |(start, joined)| {
+			if now >= *start + period {
+				*start = now;
+				*joined = 0;
+			}
+			*joined += 1;
+		});
 	}
 }
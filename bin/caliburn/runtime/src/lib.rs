@@ -15,6 +15,7 @@ use grandpa::fg_primitives;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::OpaqueMetadata;
+use sp_core::u32_trait::{_1, _2, _3, _4};
 use sp_runtime::{
 	ApplyExtrinsicResult, create_runtime_str, generic, impl_opaque_keys,
 	MultiSignature, transaction_validity::TransactionValidity,
@@ -24,7 +25,8 @@ pub use sp_runtime::{Perbill, Permill};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, NumberFor, StaticLookup, Verify,
+	BlakeTwo256, Block as BlockT, ConvertInto, EnsureOrigin, IdentifyAccount, NumberFor, StaticLookup,
+	Verify,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -67,10 +69,20 @@ pub type DigestItem = generic::DigestItem<Hash>;
 
 /// Used for the module template in `./template.rs`
 mod se;
-mod task_board;
+pub mod dework;
+pub mod task_board;
 mod identity;
-mod reputation;
-mod judge_pool;
+pub mod reputation;
+pub mod judge_pool;
+pub mod organisation;
+mod migration;
+
+pub use task_board::WEBHOOK_KEY as DEWORK_WEBHOOK_KEY;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -101,7 +113,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("caliburn"),
 	impl_name: create_runtime_str!("caliburn"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 12,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 };
@@ -241,12 +253,224 @@ impl sudo::Trait for Runtime {
 	type Proposal = Call;
 }
 
+parameter_types! {
+	pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+	pub const VotingPeriod: BlockNumber = 7 * DAYS;
+	pub const EmergencyVotingPeriod: BlockNumber = 1 * DAYS;
+	pub const MinimumDeposit: Balance = 100_000;
+	pub const EnactmentPeriod: BlockNumber = 8 * DAYS;
+	pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+	pub const PreimageByteDeposit: Balance = 10;
+}
+
+impl democracy::Trait for Runtime {
+	type Proposal = Call;
+	type Event = Event;
+	type Currency = Balances;
+	type EnactmentPeriod = EnactmentPeriod;
+	type LaunchPeriod = LaunchPeriod;
+	type VotingPeriod = VotingPeriod;
+	type EmergencyVotingPeriod = EmergencyVotingPeriod;
+	type MinimumDeposit = MinimumDeposit;
+	/// A straight majority of the council can decide what their next motion is.
+	type ExternalOrigin = collective::EnsureProportionAtLeast<_1, _2, AccountId, CouncilCollective>;
+	/// A super-majority can have the next scheduled referendum be a straight majority-carries vote.
+	type ExternalMajorityOrigin = collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	/// A unanimous council can have the next scheduled referendum be a straight default-carries
+	/// (NTB) vote.
+	type ExternalDefaultOrigin = collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+	/// Three quarters of the council can have an ExternalMajority/ExternalDefault vote be tabled
+	/// immediately and with a shorter voting/enactment period.
+	type FastTrackOrigin = collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	// To cancel a proposal which has been passed, 3/4 of the council must agree to it.
+	type CancellationOrigin = collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	// Any single council member may veto a coming council proposal, however they can only do it
+	// once and it lasts only for the cooloff period.
+	type VetoOrigin = collective::EnsureMember<AccountId, CouncilCollective>;
+	type CooloffPeriod = CooloffPeriod;
+	type PreimageByteDeposit = PreimageByteDeposit;
+	type Slash = Treasury;
+}
+
+type CouncilCollective = collective::Instance1;
+impl collective::Trait<CouncilCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+}
+
+parameter_types! {
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 10_000;
+	pub const SpendPeriod: BlockNumber = 1 * DAYS;
+	pub const Burn: Permill = Permill::from_percent(0);
+}
+
+impl treasury::Trait for Runtime {
+	type Currency = Balances;
+	type ApproveOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
+	type RejectOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
+	type Event = Event;
+	type ProposalRejection = ();
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+}
+
+/// Origin allowed to change the DeWork parameters: root, which a democracy referendum
+/// dispatches as, or more than half of the council.
+pub struct EnsureRootOrHalfCouncil;
+impl EnsureOrigin<Origin> for EnsureRootOrHalfCouncil {
+	type Success = ();
+	fn try_origin(o: Origin) -> Result<Self::Success, Origin> {
+		<system::EnsureRoot<AccountId> as EnsureOrigin<Origin>>::try_origin(o)
+			.map(|_| ())
+			.or_else(<collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective> as EnsureOrigin<Origin>>::try_origin)
+	}
+}
+
+parameter_types! {
+	// One storage item; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const MultisigDepositBase: Balance = 30_000;
+	// Additional storage item size of 32 bytes.
+	pub const MultisigDepositFactor: Balance = 5_000;
+	pub const MaxSignatories: u16 = 100;
+}
+
+impl utility::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type MultisigDepositBase = MultisigDepositBase;
+	type MultisigDepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+}
+
 impl se::Trait for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const LegacyCallWindow: BlockNumber = 28 * DAYS;
+}
+
+impl dework::Trait for Runtime {
+	type Event = Event;
+	type LegacyCallWindow = LegacyCallWindow;
+}
+
+impl dework::LegacyCall for Call {
+	fn is_legacy_call(&self) -> bool {
+		match self {
+			Call::Caliburn(call) => match call {
+				se::Call::issue(..)
+				| se::Call::publish_task(..)
+				| se::Call::claim_task(..)
+				| se::Call::revoke_credential(..)
+				| se::Call::verify_credential(..)
+				| se::Call::create_subject(..)
+				| se::Call::register_user(..) => true,
+				_ => false,
+			},
+			// Everything the DeWork calls cover, which leaves governance and the unsigned poke.
+			Call::DeWorkTasks(call) => match call {
+				task_board::Call::publish_task(..)
+				| task_board::Call::claim_task(..)
+				| task_board::Call::claim_deliver_task(..)
+				| task_board::Call::request_for_judge(..)
+				| task_board::Call::task_to_final(..)
+				| task_board::Call::publish_sub_task(..)
+				| task_board::Call::cancel_task(..)
+				| task_board::Call::set_claim_deadline(..)
+				| task_board::Call::rate_task(..)
+				| task_board::Call::appeal(..)
+				| task_board::Call::set_min_issuer_score(..)
+				| task_board::Call::create_template(..)
+				| task_board::Call::remove_template(..)
+				| task_board::Call::publish_from_template(..)
+				| task_board::Call::schedule_template(..)
+				| task_board::Call::fund_schedule(..)
+				| task_board::Call::cancel_schedule(..) => true,
+				_ => false,
+			},
+			Call::JudgePoolModule(call) => match call {
+				judge_pool::Call::exec_judgement(..)
+				| judge_pool::Call::submit_evidence(..)
+				| judge_pool::Call::verify_judgement_Done(..) => true,
+				_ => false,
+			},
+			Call::ReputationModule(call) => match call {
+				reputation::Call::bootstrap(..)
+				| reputation::Call::unbond(..)
+				| reputation::Call::link_account(..)
+				| reputation::Call::accept_link(..)
+				| reputation::Call::unlink_account(..) => true,
+				_ => false,
+			},
+			// Batched calls are dispatched without going through the signed extensions again.
+			Call::Utility(utility::Call::batch(calls)) => calls.iter().any(|call| call.is_legacy_call()),
+			Call::Utility(utility::Call::as_sub(_, call)) => call.is_legacy_call(),
+			Call::Utility(utility::Call::as_multi(_, _, _, call)) => call.is_legacy_call(),
+			Call::Organisations(organisation::Call::act_as(_, call)) => call.is_legacy_call(),
+			_ => false,
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxMembers: u32 = 64;
+}
+
+impl organisation::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type MaxMembers = MaxMembers;
+}
+
+impl organisation::OrganisationCall for Call {
+	fn required_roles(&self) -> Option<organisation::Roles> {
+		use organisation::Roles;
+		match self {
+			// Only the DeWork calls, so that members cannot move the organisation's funds.
+			Call::DeWork(call) => match call {
+				dework::Call::publish_task(..)
+				| dework::Call::publish_sub_task(..)
+				| dework::Call::cancel_task(..)
+				| dework::Call::create_template(..)
+				| dework::Call::remove_template(..)
+				| dework::Call::publish_from_template(..)
+				| dework::Call::schedule_template(..)
+				| dework::Call::fund_schedule(..)
+				| dework::Call::cancel_schedule(..) => Some(Roles::PUBLISH),
+				dework::Call::task_to_final(..)
+				| dework::Call::rate_task(..)
+				| dework::Call::set_min_issuer_score(..) => Some(Roles::ACCEPT),
+				dework::Call::request_for_judge(..)
+				| dework::Call::submit_evidence(..)
+				| dework::Call::appeal(..) => Some(Roles::DISPUTE),
+				dework::Call::claim_task(..)
+				| dework::Call::deliver_task(..) => Some(Roles::WORK),
+				_ => None,
+			},
+			Call::Utility(utility::Call::batch(calls)) => calls.iter()
+				.try_fold(Roles::default(), |roles, call| call.required_roles().map(|r| roles | r)),
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
 impl task_board::Trait for Runtime {
 	type Event = Event;
+	type ParameterOrigin = EnsureRootOrHalfCouncil;
+	type ProtocolFeeDestination = Treasury;
+	type Call = Call;
+	type SubmitTransaction = system::offchain::TransactionSubmitter<(), Runtime, UncheckedExtrinsic>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
 }
 
 impl identity::Trait for Runtime {
@@ -255,10 +479,12 @@ impl identity::Trait for Runtime {
 
 impl reputation::Trait for Runtime {
 	type Event = Event;
+	type ParameterOrigin = EnsureRootOrHalfCouncil;
 }
 
 impl judge_pool::Trait for Runtime {
 	type Event = Event;
+	type ParameterOrigin = EnsureRootOrHalfCouncil;
 }
 
 construct_runtime!(
@@ -276,11 +502,17 @@ construct_runtime!(
 		TransactionPayment: transaction_payment::{Module, Storage},
 		Sudo: sudo,
 		RandomnessCollectiveFlip: randomness_collective_flip::{Module, Call, Storage},
-		Caliburn:se::{Module, Call, Storage, Event<T>},
-		DeWorkTasks: task_board::{Module, Call, Storage, Event<T>},
+		Caliburn:se::{Module, Call, Event<T>},
+		DeWorkTasks: task_board::{Module, Call, Storage, Event<T>, Config<T>, ValidateUnsigned},
 		Identity: identity::{Module, Call, Storage, Event<T>, Config<T>},
-		ReputationModule: reputation::{Module, Call, Storage, Event<T>},
-		JudgePoolModule: judge_pool::{Module, Call, Storage, Event<T>},
+		ReputationModule: reputation::{Module, Call, Storage, Event<T>, Config<T>},
+		JudgePoolModule: judge_pool::{Module, Call, Storage, Event<T>, Config<T>},
+		Utility: utility::{Module, Call, Storage, Event<T>},
+		DeWork: dework::{Module, Call, Storage, Event<T>, Config<T>},
+		Organisations: organisation::{Module, Call, Storage, Event<T>},
+		Democracy: democracy::{Module, Call, Storage, Config, Event<T>},
+		Council: collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
+		Treasury: treasury::{Module, Call, Storage, Config, Event<T>},
 	}
 );
 
@@ -301,7 +533,8 @@ pub type SignedExtra = (
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
-	transaction_payment::ChargeTransactionPayment<Runtime>
+	transaction_payment::ChargeTransactionPayment<Runtime>,
+	dework::CheckLegacyCall<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -310,6 +543,27 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
 
+sp_api::decl_runtime_apis! {
+	/// Discovery of the DeWork tasks an account can claim.
+	pub trait DeWorkApi<AccountId, Hash, Balance, Moment, BlockNumber> where
+		AccountId: codec::Codec,
+		Hash: codec::Codec,
+		Balance: codec::Codec,
+		Moment: codec::Codec,
+		BlockNumber: codec::Codec,
+	{
+		/// The `Published` tasks `who` can claim, best match first, skipping `offset` and
+		/// returning at most `limit`.
+		fn eligible_tasks(who: AccountId, offset: u32, limit: u32) -> Vec<task_board::EligibleTask<Hash, Balance, Moment, BlockNumber>>;
+
+		/// Every `Published` task `who` can claim, best match first.
+		fn all_eligible_tasks(who: AccountId) -> Vec<task_board::EligibleTask<Hash, Balance, Moment, BlockNumber>>;
+
+		/// The insurance pool against the pay it may have to cover.
+		fn insurance_solvency() -> task_board::InsuranceSolvency<Balance>;
+	}
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -389,4 +643,18 @@ impl_runtime_apis! {
 			Grandpa::grandpa_authorities()
 		}
 	}
+
+	impl self::DeWorkApi<Block, AccountId, Hash, Balance, u64, BlockNumber> for Runtime {
+		fn eligible_tasks(who: AccountId, offset: u32, limit: u32) -> Vec<task_board::EligibleTask<Hash, Balance, u64, BlockNumber>> {
+			DeWorkTasks::eligible_tasks(&who, offset, limit)
+		}
+
+		fn all_eligible_tasks(who: AccountId) -> Vec<task_board::EligibleTask<Hash, Balance, u64, BlockNumber>> {
+			DeWorkTasks::all_eligible_tasks(&who)
+		}
+
+		fn insurance_solvency() -> task_board::InsuranceSolvency<Balance> {
+			DeWorkTasks::insurance_solvency()
+		}
+	}
 }
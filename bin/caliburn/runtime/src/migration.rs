@@ -0,0 +1,90 @@
+//! Storage migration helpers shared by the DeWork pallets.
+//!
+//! Every DeWork pallet keeps a `StorageVersion` value and brings its storage up to date from
+//! `on_initialize`. Each migration step upgrades exactly one version, so a chain can skip any
+//! number of runtime releases and still be migrated in order.
+
+use codec::{Decode, FullCodec};
+use frame_support::storage::{unhashed, StoragePrefixedMap};
+
+/// Indicator of a version of a storage layout.
+pub type VersionNumber = u32;
+
+/// Re-encode the value stored at `key` from `Old` into `New`.
+///
+/// Values that cannot be decoded as `Old` are removed. Returns the new value, if any.
+pub fn translate_value<Old, New, F>(key: &[u8], f: F) -> Option<New>
+where
+	Old: Decode,
+	New: FullCodec,
+	F: FnOnce(Old) -> New,
+{
+	match unhashed::get::<Old>(key) {
+		Some(old) => {
+			let new = f(old);
+			unhashed::put(key, &new);
+			Some(new)
+		}
+		None => {
+			if unhashed::exists(key) {
+				frame_support::print("Removing undecodable entry during DeWork storage migration.");
+				unhashed::kill(key);
+			}
+			None
+		}
+	}
+}
+
+/// Re-encode every value of the map `Map` from `Old` into its current value type.
+///
+/// Returns the number of migrated and removed entries.
+pub fn translate_values<Map, Old, New, F>(mut f: F) -> (u32, u32)
+where
+	Map: StoragePrefixedMap<New>,
+	Old: Decode,
+	New: FullCodec,
+	F: FnMut(Old) -> New,
+{
+	let prefix = Map::final_prefix();
+	let mut previous_key = prefix.to_vec();
+	let (mut migrated, mut removed) = (0, 0);
+
+	while let Some(key) = sp_io::storage::next_key(&previous_key).filter(|k| k.starts_with(&prefix)) {
+		match translate_value::<Old, New, _>(&key, &mut f) {
+			Some(_) => migrated += 1,
+			None => removed += 1,
+		}
+		previous_key = key;
+	}
+
+	(migrated, removed)
+}
+
+/// Visit every value of the map `Map` along with the hashed key it is stored under, removing
+/// the entries for which `f` returns `false`.
+///
+/// The hashed key addresses the same map key in any other map of the module using the same
+/// hasher. Returns the number of kept and removed entries.
+pub fn retain_values<Map, V, F>(mut f: F) -> (u32, u32)
+where
+	Map: StoragePrefixedMap<V>,
+	V: FullCodec,
+	F: FnMut(&[u8], &V) -> bool,
+{
+	let prefix = Map::final_prefix();
+	let mut previous_key = prefix.to_vec();
+	let (mut kept, mut removed) = (0, 0);
+
+	while let Some(key) = sp_io::storage::next_key(&previous_key).filter(|k| k.starts_with(&prefix)) {
+		match unhashed::get::<V>(&key) {
+			Some(value) if f(&key[prefix.len()..], &value) => kept += 1,
+			_ => {
+				unhashed::kill(&key);
+				removed += 1;
+			}
+		}
+		previous_key = key;
+	}
+
+	(kept, removed)
+}
@@ -0,0 +1,336 @@
+//! Test utilities for the DeWork pallets.
+
+use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup, OnInitialize}, testing::{Header, TestXt}};
+use sp_core::H256;
+use sp_io;
+use frame_support::{impl_outer_origin, parameter_types};
+use frame_support::traits::{Currency, OnUnbalanced};
+use frame_support::weights::Weight;
+
+use crate::{dework, identity, judge_pool, organisation, reputation, se, task_board};
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Test;
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl system::Trait for Test {
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Call = ();
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type ModuleToIndex = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1;
+}
+
+impl timestamp::Trait for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const TransferFee: u64 = 0;
+	pub const CreationFee: u64 = 0;
+}
+
+impl balances::Trait for Test {
+	type Balance = u64;
+	type OnFreeBalanceZero = ();
+	type OnNewAccount = ();
+	type Event = ();
+	type DustRemoval = ();
+	type TransferPayment = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type TransferFee = TransferFee;
+	type CreationFee = CreationFee;
+}
+
+impl identity::Trait for Test {
+	type Event = ();
+}
+
+/// Collects the protocol fee into the `TREASURY` account.
+pub struct FeeCollector;
+impl OnUnbalanced<balances::NegativeImbalance<Test>> for FeeCollector {
+	fn on_nonzero_unbalanced(amount: balances::NegativeImbalance<Test>) {
+		Balances::resolve_creating(&TREASURY, amount);
+	}
+}
+
+impl reputation::Trait for Test {
+	type Event = ();
+	type ParameterOrigin = system::EnsureRoot<u64>;
+}
+
+impl judge_pool::Trait for Test {
+	type Event = ();
+	type ParameterOrigin = system::EnsureRoot<u64>;
+}
+
+/// The extrinsic the offchain worker submits pokes as.
+pub type Extrinsic = TestXt<task_board::Call<Test>, ()>;
+
+parameter_types! {
+	pub const MultisigDepositBase: u64 = 0;
+	pub const MultisigDepositFactor: u64 = 0;
+	pub const MaxSignatories: u16 = 8;
+	pub const MaxMembers: u32 = 4;
+}
+
+impl utility::Trait for Test {
+	type Event = ();
+	type Call = task_board::Call<Test>;
+	type Currency = Balances;
+	type MultisigDepositBase = MultisigDepositBase;
+	type MultisigDepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+}
+
+impl organisation::Trait for Test {
+	type Event = ();
+	type Call = task_board::Call<Test>;
+	type MaxMembers = MaxMembers;
+}
+
+impl organisation::OrganisationCall for task_board::Call<Test> {
+	fn required_roles(&self) -> Option<organisation::Roles> {
+		use organisation::Roles;
+		match self {
+			task_board::Call::publish_task(..) | task_board::Call::cancel_task(..) => Some(Roles::PUBLISH),
+			task_board::Call::task_to_final(..) | task_board::Call::rate_task(..) => Some(Roles::ACCEPT),
+			task_board::Call::request_for_judge(..) | task_board::Call::appeal(..) => Some(Roles::DISPUTE),
+			task_board::Call::claim_task(..) | task_board::Call::claim_deliver_task(..) => Some(Roles::WORK),
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxScheduledPerBlock: u32 = 2;
+}
+
+impl task_board::Trait for Test {
+	type Event = ();
+	type ParameterOrigin = system::EnsureRoot<u64>;
+	type ProtocolFeeDestination = FeeCollector;
+	type Call = task_board::Call<Test>;
+	type SubmitTransaction = system::offchain::TransactionSubmitter<(), task_board::Call<Test>, Extrinsic>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+}
+
+parameter_types! {
+	pub const LegacyCallWindow: u64 = 10;
+}
+
+impl dework::Trait for Test {
+	type Event = ();
+	type LegacyCallWindow = LegacyCallWindow;
+}
+
+/// The mock has no outer call, so `()` stands for any legacy call.
+impl dework::LegacyCall for () {
+	fn is_legacy_call(&self) -> bool {
+		true
+	}
+}
+
+impl se::Trait for Test {
+	type Event = ();
+}
+
+pub type System = system::Module<Test>;
+pub type Timestamp = timestamp::Module<Test>;
+pub type Balances = balances::Module<Test>;
+pub type Identity = identity::Module<Test>;
+pub type Reputation = reputation::Module<Test>;
+pub type JudgePool = judge_pool::Module<Test>;
+pub type TaskBoard = task_board::Module<Test>;
+pub type DeWork = dework::Module<Test>;
+pub type Caliburn = se::Module<Test>;
+pub type Utility = utility::Module<Test>;
+pub type Organisations = organisation::Module<Test>;
+
+/// The issuer used by most tests.
+pub const ISSUER: u64 = 1;
+/// Team leader of the receiving team.
+pub const LEADER: u64 = 2;
+/// Second member of the receiving team.
+pub const MEMBER: u64 = 3;
+/// Judges eligible to sit on a panel.
+pub const JUDGES: [u64; 3] = [10, 11, 12];
+/// Bond reserved to bootstrap a score without a verified identity.
+pub const BOND: u64 = 100;
+/// Receiver of the protocol fee.
+pub const TREASURY: u64 = 99;
+/// Initial free balance of every endowed account.
+pub const ENDOWMENT: u64 = 1_000;
+
+/// A task published at genesis: issuer, description, min_rep, pay, judge_pay, req_subjects.
+pub type GenesisTask = (u64, Vec<u8>, u32, u64, u64, Vec<u32>);
+
+pub struct ExtBuilder {
+	judge_size: u32,
+	threshold: u32,
+	evidence_deposit: u64,
+	panel_limits: (u64, u64, u32),
+	deadlines: (u64, u64, u64),
+	endowed: Vec<u64>,
+	subjects: Vec<(u32, u64)>,
+	credentials: Vec<(u64, u32)>,
+	scores: Vec<(u64, u32)>,
+	tasks: Vec<GenesisTask>,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		Self {
+			judge_size: 3,
+			threshold: 50,
+			evidence_deposit: 0,
+			panel_limits: (0, 0, 4),
+			deadlines: (0, 0, 0),
+			endowed: vec![ISSUER, LEADER, MEMBER, 4, 5, JUDGES[0], JUDGES[1], JUDGES[2]],
+			subjects: vec![],
+			credentials: vec![],
+			// Judges need a bootstrapped score to sit on a panel.
+			scores: JUDGES.iter().map(|judge| (*judge, 50)).collect(),
+			tasks: vec![],
+		}
+	}
+}
+
+impl ExtBuilder {
+	pub fn judge_size(mut self, judge_size: u32) -> Self {
+		self.judge_size = judge_size;
+		self
+	}
+	pub fn threshold(mut self, threshold: u32) -> Self {
+		self.threshold = threshold;
+		self
+	}
+	pub fn evidence_deposit(mut self, evidence_deposit: u64) -> Self {
+		self.evidence_deposit = evidence_deposit;
+		self
+	}
+	pub fn panel_limits(mut self, warmup: u64, period: u64, max_panels: u32) -> Self {
+		self.panel_limits = (warmup, period, max_panels);
+		self
+	}
+	pub fn deadlines(mut self, acceptance_period: u64, deadline_notice: u64, voting_period: u64) -> Self {
+		self.deadlines = (acceptance_period, deadline_notice, voting_period);
+		self
+	}
+	pub fn subject(mut self, subject: u32, issuer: u64) -> Self {
+		self.subjects.push((subject, issuer));
+		self
+	}
+	pub fn credential(mut self, holder: u64, subject: u32) -> Self {
+		self.credentials.push((holder, subject));
+		self
+	}
+	pub fn score(mut self, who: u64, score: u32) -> Self {
+		self.scores.push((who, score));
+		self
+	}
+	pub fn task(mut self, task: GenesisTask) -> Self {
+		self.tasks.push(task);
+		self
+	}
+	pub fn build(self) -> sp_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: self.endowed.iter().map(|who| (*who, ENDOWMENT)).collect(),
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		judge_pool::GenesisConfig::<Test> {
+			judge_size: self.judge_size,
+			threshold: self.threshold,
+			evidence_deposit: self.evidence_deposit,
+			max_evidence: 2,
+			max_statement_len: 16,
+			judge_warmup: self.panel_limits.0,
+			panel_period: self.panel_limits.1,
+			max_panels_per_period: self.panel_limits.2,
+			voting_period: self.deadlines.2,
+		}.assimilate_storage(&mut t).unwrap();
+		identity::GenesisConfig::<Test> {
+			subject_count: self.subjects.iter().map(|(subject, _)| subject + 1).max().unwrap_or(0),
+			subject_details: self.subjects.iter().map(|(subject, _)| (*subject, b"subject".to_vec(), vec![], vec![])).collect(),
+			subjects: self.subjects,
+			credentials: self.credentials,
+			cred_manager: ISSUER,
+			rep: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		reputation::GenesisConfig::<Test> {
+			initial_score: 50,
+			reputation_step: 5,
+			rating_prior_weight: 2,
+			trusted_subjects: vec![0],
+			bootstrap_bond: BOND,
+			max_linked_accounts: 2,
+			scores: self.scores,
+		}.assimilate_storage(&mut t).unwrap();
+		task_board::GenesisConfig::<Test> {
+			max_sub_tasks: 16,
+			max_blocked_by: 16,
+			protocol_fee: Default::default(),
+			insurance_premium: Default::default(),
+			appeal_deposit: Default::default(),
+			acceptance_period: self.deadlines.0,
+			deadline_notice: self.deadlines.1,
+			tasks: self.tasks,
+		}.assimilate_storage(&mut t).unwrap();
+		dework::GenesisConfig::<Test> {
+			legacy_calls_end: 0,
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+}
+
+/// Sum of every balance currently escrowed by `task_board` and `judge_pool` for `hash`.
+pub fn escrowed(hash: H256, accounts: &[u64]) -> u64 {
+	accounts.iter().map(|who| {
+		TaskBoard::issuer_pay_pool((*who, hash))
+			+ TaskBoard::staking_pay_pool((*who, hash))
+			+ JudgePool::pledge_pool((*who, hash))
+	}).sum()
+}
+
+/// Sum of the free and reserved balances of `accounts`.
+pub fn total_balances(accounts: &[u64]) -> u64 {
+	accounts.iter().map(|who| Balances::free_balance(who) + Balances::reserved_balance(who)).sum()
+}
+
+/// Advance to block `n`, running the DeWork `on_initialize` hooks of every block on the way.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		TaskBoard::on_initialize(System::block_number());
+	}
+}
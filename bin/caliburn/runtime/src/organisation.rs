@@ -0,0 +1,268 @@
+//! Organisations and squads acting as a single DeWork account.
+//!
+//! A company is the `utility::as_sub` sub-account of its founder: the founder can act as it
+//! directly, and members act as it through `act_as` within the roles they were given. A squad
+//! is the `utility::as_multi` account of its members and only acts once `threshold` of them
+//! approved the call there: it claims and delivers tasks as one receiver, `task_board` checks
+//! it against the credentials and shared score of its members, and the ratings it earns as a
+//! worker are credited to every member.
+
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure, Parameter, StorageMap, StorageValue,
+	traits::Get,
+};
+use sp_runtime::{DispatchResult, RuntimeDebug, traits::Dispatchable};
+use sp_std::{ops::BitOr, prelude::*};
+use system::ensure_signed;
+
+use crate::{identity, reputation};
+use crate::migration::VersionNumber;
+
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 1;
+
+pub trait Trait: reputation::Trait + utility::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// The overarching call type members dispatch on behalf of an organisation.
+	type Call: Parameter + Dispatchable<Origin=<Self as system::Trait>::Origin> + OrganisationCall;
+	/// Maximum number of members of one organisation.
+	type MaxMembers: Get<u32>;
+}
+
+/// Permissions of a member, as a set of flags.
+#[derive(Encode, Decode, Clone, Copy, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct Roles(pub u8);
+
+impl Roles {
+	/// Publish, cancel and schedule tasks
+	pub const PUBLISH: Roles = Roles(1);
+	/// Finalise tasks and rate the other side
+	pub const ACCEPT: Roles = Roles(1 << 1);
+	/// Dispute deliveries, submit evidence and appeal
+	pub const DISPUTE: Roles = Roles(1 << 2);
+	/// Claim and deliver tasks
+	pub const WORK: Roles = Roles(1 << 3);
+	/// Change the roles of members
+	pub const ADMIN: Roles = Roles(1 << 4);
+	pub const ALL: Roles = Roles(0b1_1111);
+
+	pub fn contains(self, other: Roles) -> bool {
+		self.0 & other.0 == other.0
+	}
+
+	pub fn is_empty(self) -> bool {
+		self.0 == 0
+	}
+}
+
+impl BitOr for Roles {
+	type Output = Roles;
+	fn bitor(self, other: Roles) -> Roles {
+		Roles(self.0 | other.0)
+	}
+}
+
+/// Tells the role a member needs to dispatch a call on behalf of an organisation.
+pub trait OrganisationCall {
+	/// The roles required, or `None` if no member may dispatch this call.
+	fn required_roles(&self) -> Option<Roles>;
+}
+
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, Eq, PartialEq)]
+pub enum OrgKind {
+	/// Sub-account of its founder
+	Company,
+	/// Multi-account of its members, which are fixed at creation
+	Squad,
+}
+
+#[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
+pub struct Organisation<AccountId> {
+	pub kind: OrgKind,
+	pub founder: AccountId,
+	/// Approvals a squad's `utility::as_multi` needs; zero for companies
+	pub threshold: u16,
+}
+
+decl_event! {
+	pub enum Event<T>
+	where
+		AccountId = <T as system::Trait>::AccountId,
+	{
+		/// params: organisation, founder, kind
+		OrganisationCreated(AccountId, AccountId, OrgKind),
+		/// params: organisation, member, new roles
+		RolesChanged(AccountId, AccountId, Roles),
+		/// params: organisation, member
+		MemberRemoved(AccountId, AccountId),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		OrganisationDuplicated,
+		OrganisationNotFound,
+		PermissionError,
+		CallNotAllowed,
+		MemberLimitReached,
+		SquadMembersFixed,
+		SquadInvalid,
+		SquadNeedsApprovals,
+		LastAdmin,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Organisations {
+		/// Organisations by account.
+		Organisations get(organisation): map T::AccountId => Option<Organisation<T::AccountId>>;
+		/// Roles by (organisation, member); empty for non-members.
+		MemberRoles get(roles): map (T::AccountId, T::AccountId) => Roles;
+		/// Members of each organisation.
+		Members get(members): map T::AccountId => Vec<T::AccountId>;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// Maximum number of members of one organisation.
+		const MaxMembers: u32 = T::MaxMembers::get();
+
+		fn deposit_event() = default;
+
+		/// Register the sender's sub-account `index` as a company, with the sender as its admin
+		pub fn create_company(origin, index: u16) {
+			let sender = ensure_signed(origin)?;
+			let org = <utility::Module<T>>::sub_account_id(sender.clone(), index);
+			ensure!(!<Organisations<T>>::exists(&org), Error::<T>::OrganisationDuplicated);
+
+			<Organisations<T>>::insert(&org, Organisation { kind: OrgKind::Company, founder: sender.clone(), threshold: 0 });
+			<MemberRoles<T>>::insert((org.clone(), sender.clone()), Roles::ALL);
+			<Members<T>>::insert(&org, vec![sender.clone()]);
+			Self::deposit_event(RawEvent::OrganisationCreated(org, sender, OrgKind::Company));
+		}
+
+		/// Register the multi-account of the sender and `others` as a squad
+		///
+		/// The squad acts through `utility::as_multi`, once `threshold` members approved.
+		pub fn create_squad(origin, others: Vec<T::AccountId>, threshold: u16) {
+			let sender = ensure_signed(origin)?;
+			let mut members = others;
+			members.push(sender.clone());
+			members.sort();
+			members.dedup();
+			ensure!(members.len() >= 2, Error::<T>::SquadInvalid);
+			ensure!(threshold >= 1 && threshold as usize <= members.len(), Error::<T>::SquadInvalid);
+			ensure!(members.len() as u32 <= T::MaxMembers::get(), Error::<T>::MemberLimitReached);
+			let org = <utility::Module<T>>::multi_account_id(&members, threshold);
+			ensure!(!<Organisations<T>>::exists(&org), Error::<T>::OrganisationDuplicated);
+
+			<Organisations<T>>::insert(&org, Organisation { kind: OrgKind::Squad, founder: sender.clone(), threshold });
+			for member in &members {
+				<MemberRoles<T>>::insert((org.clone(), member.clone()), Roles::WORK | Roles::ACCEPT | Roles::DISPUTE);
+			}
+			<Members<T>>::insert(&org, members);
+			Self::deposit_event(RawEvent::OrganisationCreated(org, sender, OrgKind::Squad));
+		}
+
+		/// Give `member` the `roles` in `org`; no roles removes a company member
+		///
+		/// Callable by the organisation account and by its admins.
+		pub fn set_roles(origin, org: T::AccountId, member: T::AccountId, roles: Roles) {
+			let sender = ensure_signed(origin)?;
+			let organisation = Self::organisation(&org).ok_or(Error::<T>::OrganisationNotFound)?;
+			ensure!(sender == org || Self::roles((org.clone(), sender)).contains(Roles::ADMIN), Error::<T>::PermissionError);
+
+			let mut members = Self::members(&org);
+			let is_member = members.contains(&member);
+			if organisation.kind == OrgKind::Squad {
+				ensure!(is_member && !roles.is_empty(), Error::<T>::SquadMembersFixed);
+			} else if !is_member {
+				ensure!(!roles.is_empty(), Error::<T>::PermissionError);
+				ensure!((members.len() as u32) < T::MaxMembers::get(), Error::<T>::MemberLimitReached);
+				members.push(member.clone());
+			}
+			if organisation.kind == OrgKind::Company && !roles.contains(Roles::ADMIN) {
+				let admin_left = members.iter()
+					.any(|m| m != &member && Self::roles((org.clone(), m.clone())).contains(Roles::ADMIN));
+				ensure!(admin_left, Error::<T>::LastAdmin);
+			}
+
+			if roles.is_empty() {
+				members.retain(|m| m != &member);
+				<MemberRoles<T>>::remove((org.clone(), member.clone()));
+				Self::deposit_event(RawEvent::MemberRemoved(org.clone(), member));
+			} else {
+				<MemberRoles<T>>::insert((org.clone(), member.clone()), roles);
+				Self::deposit_event(RawEvent::RolesChanged(org.clone(), member, roles));
+			}
+			<Members<T>>::insert(&org, members);
+		}
+
+		/// Dispatch `call` as the company `org`, within the roles of the sender
+		///
+		/// Squads act through `utility::as_multi` instead, so no member acts for them alone.
+		pub fn act_as(origin, org: T::AccountId, call: Box<<T as Trait>::Call>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let organisation = Self::organisation(&org).ok_or(Error::<T>::OrganisationNotFound)?;
+			ensure!(organisation.kind == OrgKind::Company, Error::<T>::SquadNeedsApprovals);
+			let required = call.required_roles().ok_or(Error::<T>::CallNotAllowed)?;
+			ensure!(Self::roles((org.clone(), sender)).contains(required), Error::<T>::PermissionError);
+			call.dispatch(system::RawOrigin::Signed(org).into())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Whether `who` is a member of the organisation `org`
+	pub fn is_member(org: &T::AccountId, who: &T::AccountId) -> bool {
+		<MemberRoles<T>>::exists((org.clone(), who.clone()))
+	}
+
+	/// The members of `who` if it is a squad; empty otherwise
+	pub fn squad_members(who: &T::AccountId) -> Vec<T::AccountId> {
+		match Self::organisation(who) {
+			Some(ref organisation) if organisation.kind == OrgKind::Squad => Self::members(who),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Whether the members of the squad `squad` jointly hold `subject`
+	pub fn squad_holds(squad: &T::AccountId, subject: &u32) -> bool {
+		Self::members(squad).iter().any(|m| <identity::Module<T>>::check_credential(m, subject))
+	}
+
+	/// Score a squad claims with: the mean score of its members, which share its ratings
+	pub fn squad_score(squad: &T::AccountId) -> u32 {
+		let members = Self::members(squad);
+		if members.is_empty() {
+			return 0;
+		}
+		let total = members.iter()
+			.map(|m| <reputation::Module<T>>::get_account_reputation_level(m).score as u64)
+			.sum::<u64>();
+		(total / members.len() as u64) as u32
+	}
+
+	/// `accounts` with every squad among them replaced by its members
+	pub fn expand_squads(accounts: Vec<T::AccountId>) -> Vec<T::AccountId> {
+		let mut expanded = Vec::with_capacity(accounts.len());
+		for who in accounts {
+			let members = Self::squad_members(&who);
+			if members.is_empty() {
+				expanded.push(who);
+			} else {
+				expanded.extend(members);
+			}
+		}
+		expanded.sort();
+		expanded.dedup();
+		expanded
+	}
+}
@@ -7,13 +7,26 @@ use frame_support::{
 	decl_storage,
 	ensure, StorageMap,
 	StorageValue,
+	traits::ReservableCurrency,
 };
-use sp_runtime::{DispatchResult, RuntimeDebug, traits::{Hash, Zero}};
+use frame_support::storage::{unhashed, StoragePrefixedMap};
+use sp_runtime::{DispatchResult, RuntimeDebug, traits::{EnsureOrigin, Hash, Zero}};
 use sp_std::prelude::*;
 use system::{self, ensure_signed};
 
-pub trait Trait: system::Trait {
+use crate::identity;
+use crate::migration::{self, VersionNumber};
+
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 2;
+
+/// Highest rating one side of a task can give the other.
+pub const MAX_RATING: u8 = 5;
+
+pub trait Trait: system::Trait + balances::Trait + identity::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Origin allowed to change the reputation parameters.
+	type ParameterOrigin: EnsureOrigin<Self::Origin>;
 }
 
 #[derive(Encode, Decode, Clone, RuntimeDebug, Default, Eq, PartialEq)]
@@ -22,16 +35,60 @@ pub struct ReputationLevel {
 }
 
 impl ReputationLevel {
-	const FIXED_REP_REWARD: u32 = 5;
 	/// Will add reputation strategies and algorithms
-	pub fn reduce(&mut self) -> u32 {
-		self.score -= Self::FIXED_REP_REWARD;
-		Self::FIXED_REP_REWARD
+	pub fn reduce(&mut self, step: u32) -> u32 {
+		let before = self.score;
+		self.score = self.score.saturating_sub(step);
+		before - self.score
 	}
 	/// Will add reputation strategies and algorithms
-	pub fn increase(&mut self) -> u32 {
-		self.score += Self::FIXED_REP_REWARD;
-		Self::FIXED_REP_REWARD
+	pub fn increase(&mut self, step: u32) -> u32 {
+		let before = self.score;
+		self.score = self.score.saturating_add(step);
+		self.score - before
+	}
+}
+
+/// How an account proves it is a distinct participant before it receives a score.
+#[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
+pub enum BootstrapProof {
+	/// A credential on one of the `TrustedSubjects`.
+	Credential(u32),
+	/// A KYC attestation by the identity credential manager.
+	Kyc,
+	/// `BootstrapBond` reserved until the account unbonds.
+	Bond,
+}
+
+/// The side of a task an account was rated for.
+#[derive(Encode, Decode, Copy, Clone, RuntimeDebug, Eq, PartialEq)]
+pub enum RatedRole {
+	Issuer,
+	Worker,
+}
+
+/// A rating left by one side of a finished task for the other.
+#[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
+pub struct Rating<AccountId, Hash> {
+	pub ratees: Vec<AccountId>,
+	pub role: RatedRole,
+	/// From 1 to `MAX_RATING`.
+	pub rating: u8,
+	pub review: Hash,
+}
+
+/// Ratings received by an account in one role.
+#[derive(Encode, Decode, Copy, Clone, RuntimeDebug, Default, Eq, PartialEq)]
+pub struct RatingSummary {
+	pub count: u32,
+	pub total: u32,
+}
+
+impl RatingSummary {
+	/// Add the ratings summed up in `other`
+	pub fn merge(&mut self, other: RatingSummary) {
+		self.count = self.count.saturating_add(other.count);
+		self.total = self.total.saturating_add(other.total);
 	}
 }
 
@@ -43,49 +100,305 @@ pub enum ReputationOp {
 
 decl_event! {
 	pub enum Event <T>
-	where AccountId = <T as system::Trait>::AccountId,
+	where
+		AccountId = <T as system::Trait>::AccountId,
+		Hash = <T as system::Trait>::Hash,
+		Balance = <T as balances::Trait>::Balance,
 	{
 		CompletionIncrease(AccountId, u32),
 		FailedReduce(AccountId, u32),
+		/// params: new initial score
+		InitialScoreChanged(u32),
+		/// params: new reputation step
+		ReputationStepChanged(u32),
+		/// params: rater, task-hash, rating
+		Rated(AccountId, Hash, u8),
+		/// params: new rating prior weight
+		RatingPriorWeightChanged(u32),
+		/// params: account, proof
+		Bootstrapped(AccountId, BootstrapProof),
+		/// params: account, unreserved bond
+		Unbonded(AccountId, Balance),
+		/// params: primary account, linked account
+		LinkOffered(AccountId, AccountId),
+		AccountLinked(AccountId, AccountId),
+		AccountUnlinked(AccountId, AccountId),
+		/// params: new trusted subjects, new bootstrap bond, new max linked accounts
+		BootstrapParamsChanged(Vec<u32>, Balance, u32),
 	}
 
 }
 
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		RatingInvalid,
+		RatingDuplicated,
+		AlreadyBootstrapped,
+		NotBootstrapped,
+		BootstrapProofInvalid,
+		NotBonded,
+		ScoreBelowInitial,
+		LinkInvalid,
+		LinkLimitReached,
+	}
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as ReputationModule {
-		Reputation: map T::AccountId => ReputationLevel;
+		/// Scores of bootstrapped primary accounts.
+		pub Reputation: map T::AccountId => ReputationLevel;
+		/// Score an account receives when it is bootstrapped.
+		InitialScore get(initial_score) config(): u32 = 50;
+		/// Score gained on completion and lost on failure.
+		ReputationStep get(reputation_step) config(): u32 = 5;
+
+		/// Ratings by (task-hash, rater).
+		Ratings get(rating): map (T::Hash, T::AccountId) => Option<Rating<T::AccountId, T::Hash>>;
+		/// Ratings received as an issuer, by primary account.
+		pub IssuerRatings get(issuer_ratings): map T::AccountId => RatingSummary;
+		/// Ratings received as a worker, by primary account.
+		pub WorkerRatings get(worker_ratings): map T::AccountId => RatingSummary;
+		/// Number of `InitialScore` ratings blended into every derived score, so that a
+		/// single rating cannot make or break an account.
+		RatingPriorWeight get(rating_prior_weight) config(): u32 = 2;
+
+		/// Block at which a primary account was bootstrapped.
+		BootstrappedAt get(bootstrapped_at): map T::AccountId => T::BlockNumber;
+		/// Bond reserved by accounts bootstrapped with `BootstrapProof::Bond`.
+		Bonds get(bond): map T::AccountId => T::Balance;
+		/// Subjects whose credentials bootstrap a score.
+		TrustedSubjects get(trusted_subjects) config(): Vec<u32>;
+		/// Bond reserved to bootstrap a score without a verified identity.
+		BootstrapBond get(bootstrap_bond) config(): T::Balance;
+
+		/// Primary account whose score a linked account shares.
+		pub PrimaryOf get(primary_of): map T::AccountId => Option<T::AccountId>;
+		/// Accounts linked to a primary account.
+		pub Linked get(linked_accounts): map T::AccountId => Vec<T::AccountId>;
+		/// Link offered by a primary account, waiting to be accepted by the linked account.
+		PendingLinks get(pending_link): map T::AccountId => Option<T::AccountId>;
+		/// Maximum number of accounts linked to one primary account.
+		MaxLinkedAccounts get(max_linked_accounts) config(): u32 = 4;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
+	}
+	add_extra_genesis {
+		/// Reputation scores at genesis.
+		config(scores): Vec<(T::AccountId, u32)>;
+		build(|config| {
+			for (who, score) in &config.scores {
+				<Reputation<T>>::insert(who, ReputationLevel { score: *score });
+				<BootstrappedAt<T>>::insert(who, T::BlockNumber::zero());
+			}
+		});
 	}
 }
 
 decl_module! {
 	pub struct Module < T: Trait > for enum Call where origin: T::Origin {
+		type Error = Error<T>;
 		fn deposit_event() = default;
+
+		fn on_initialize(_n: T::BlockNumber) {
+			Self::ensure_storage_upgraded();
+		}
+
+		/// Changing the initial score through governance
+		fn set_initial_score(origin, score: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<InitialScore>::put(score);
+			Self::deposit_event(RawEvent::InitialScoreChanged(score));
+		}
+
+		/// Changing the reputation step through governance
+		fn set_reputation_step(origin, step: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<ReputationStep>::put(step);
+			Self::deposit_event(RawEvent::ReputationStepChanged(step));
+		}
+
+		/// Changing the rating prior weight through governance
+		fn set_rating_prior_weight(origin, weight: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<RatingPriorWeight>::put(weight);
+			Self::deposit_event(RawEvent::RatingPriorWeightChanged(weight));
+		}
+
+		/// Changing the bootstrap rules through governance
+		fn set_bootstrap_params(origin, trusted_subjects: Vec<u32>, bond: T::Balance, max_linked_accounts: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<TrustedSubjects>::put(&trusted_subjects);
+			<BootstrapBond<T>>::put(bond);
+			<MaxLinkedAccounts>::put(max_linked_accounts);
+			Self::deposit_event(RawEvent::BootstrapParamsChanged(trusted_subjects, bond, max_linked_accounts));
+		}
+
+		/// Receive the initial score by proving a distinct identity
+		pub fn bootstrap(origin, proof: BootstrapProof) {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::is_bootstrapped(&sender), Error::<T>::AlreadyBootstrapped);
+			match proof {
+				BootstrapProof::Credential(subject) => {
+					ensure!(
+						Self::trusted_subjects().contains(&subject) && <identity::Module<T>>::check_credential(&sender, &subject),
+						Error::<T>::BootstrapProofInvalid
+					);
+				}
+				BootstrapProof::Kyc => {
+					ensure!(<identity::Module<T>>::kyc_verified(&sender), Error::<T>::BootstrapProofInvalid);
+				}
+				BootstrapProof::Bond => {
+					let bond = Self::bootstrap_bond();
+					<balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, bond)?;
+					<Bonds<T>>::insert(&sender, bond);
+				}
+			}
+			<Reputation<T>>::insert(&sender, ReputationLevel { score: Self::initial_score() });
+			<BootstrappedAt<T>>::insert(&sender, <system::Module<T>>::block_number());
+			Self::deposit_event(RawEvent::Bootstrapped(sender, proof));
+		}
+
+		/// Give up a bonded score and its links, unreserving the bond
+		///
+		/// A score below `InitialScore` keeps the bond locked, so it cannot be reset by bonding again.
+		pub fn unbond(origin) {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Bonds<T>>::exists(&sender), Error::<T>::NotBonded);
+			ensure!(<Reputation<T>>::get(&sender).score >= Self::initial_score(), Error::<T>::ScoreBelowInitial);
+			let bond = <Bonds<T>>::take(&sender);
+			<balances::Module<T> as ReservableCurrency<_>>::unreserve(&sender, bond);
+			for linked in <Linked<T>>::take(&sender) {
+				<PrimaryOf<T>>::remove(&linked);
+			}
+			<Reputation<T>>::remove(&sender);
+			<BootstrappedAt<T>>::remove(&sender);
+			Self::deposit_event(RawEvent::Unbonded(sender, bond));
+		}
+
+		/// Offer `who` to share the score of the sender
+		pub fn link_account(origin, who: T::AccountId) {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Reputation<T>>::exists(&sender), Error::<T>::NotBootstrapped);
+			ensure!(who != sender, Error::<T>::LinkInvalid);
+			ensure!((Self::linked_accounts(&sender).len() as u32) < Self::max_linked_accounts(), Error::<T>::LinkLimitReached);
+			<PendingLinks<T>>::insert(&who, &sender);
+			Self::deposit_event(RawEvent::LinkOffered(sender, who));
+		}
+
+		/// Accept the link offered by `primary`, sharing its score from now on
+		pub fn accept_link(origin, primary: T::AccountId) {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::pending_link(&sender) == Some(primary.clone()), Error::<T>::LinkInvalid);
+			ensure!(!Self::is_bootstrapped(&sender), Error::<T>::AlreadyBootstrapped);
+			ensure!(<Reputation<T>>::exists(&primary), Error::<T>::NotBootstrapped);
+			let mut linked = Self::linked_accounts(&primary);
+			ensure!((linked.len() as u32) < Self::max_linked_accounts(), Error::<T>::LinkLimitReached);
+
+			linked.push(sender.clone());
+			<Linked<T>>::insert(&primary, linked);
+			<PrimaryOf<T>>::insert(&sender, &primary);
+			// the ratings the sender received on its own are shared from now on too.
+			<IssuerRatings<T>>::mutate(&primary, |summary| summary.merge(<IssuerRatings<T>>::take(&sender)));
+			<WorkerRatings<T>>::mutate(&primary, |summary| summary.merge(<WorkerRatings<T>>::take(&sender)));
+			<PendingLinks<T>>::remove(&sender);
+			Self::deposit_event(RawEvent::AccountLinked(primary, sender));
+		}
+
+		/// Stop `who` sharing the score of its primary account; callable by either of them
+		pub fn unlink_account(origin, who: T::AccountId) {
+			let sender = ensure_signed(origin)?;
+			let primary = Self::primary_of(&who).ok_or(Error::<T>::LinkInvalid)?;
+			ensure!(sender == primary || sender == who, Error::<T>::LinkInvalid);
+			<Linked<T>>::mutate(&primary, |linked| linked.retain(|a| a != &who));
+			<PrimaryOf<T>>::remove(&who);
+			Self::deposit_event(RawEvent::AccountUnlinked(primary, who));
+		}
 	}
 }
 
 impl<T: Trait> Module<T> {
-	/// View the credit score of the current account
+	/// Ensures storage is upgraded to the layout expected by `STORAGE_VERSION`.
+	fn ensure_storage_upgraded() {
+		<StorageVersion>::mutate(|version| {
+			if *version >= STORAGE_VERSION { return }
+			Self::migrate_to_v1(version);
+			Self::migrate_to_v2(version);
+		});
+	}
+
+	// migrate storage from v0 to v1.
+	//
+	// this re-encodes every `Reputation` entry, dropping scores that no longer decode.
+	fn migrate_to_v1(version: &mut VersionNumber) {
+		if *version != 0 { return }
+		*version += 1;
+
+		migration::translate_values::<Reputation<T>, ReputationLevel, _, _>(|rep| rep);
+
+		frame_support::print("Finished migrating ReputationModule storage to v1.");
+	}
+
+	// migrate storage from v1 to v2.
+	//
+	// v1 created a `Reputation` entry at `InitialScore` for any account whose score was read, so
+	// an entry did not prove a distinct identity. Entries still at `InitialScore` are removed and
+	// have to be bootstrapped; the others keep their score as bootstrapped primary accounts whose
+	// judge warmup starts at the upgrade. `BootstrappedAt` shares the hasher of `Reputation`, so
+	// the hashed account of an entry addresses it there too.
+	fn migrate_to_v2(version: &mut VersionNumber) {
+		if *version != 1 { return }
+		*version += 1;
+
+		let initial = Self::initial_score();
+		let now = <system::Module<T>>::block_number();
+		let bootstrapped_at = <BootstrappedAt<T>>::final_prefix();
+		migration::retain_values::<Reputation<T>, ReputationLevel, _>(|hashed_account, rep| {
+			if rep.score == initial {
+				return false;
+			}
+			let mut key = bootstrapped_at.to_vec();
+			key.extend_from_slice(hashed_account);
+			unhashed::put(&key, &now);
+			true
+		});
+
+		frame_support::print("Finished migrating ReputationModule storage to v2.");
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// View the credit score of the current account, shared by all accounts linked to it
+	///
+	/// Accounts that were never bootstrapped have a score of 0.
 	pub fn get_account_reputation_level(account_id: &T::AccountId) -> ReputationLevel {
-		if !<Reputation<T>>::exists(account_id) {
-			let rep = ReputationLevel {
-				score: 50
-			};
-			<Reputation<T>>::insert(account_id, &rep);
-			rep
-		} else {
-			<Reputation<T>>::get(account_id)
-		}
+		<Reputation<T>>::get(Self::primary(account_id))
+	}
+
+	/// The account holding the score of `account_id`
+	pub fn primary(account_id: &T::AccountId) -> T::AccountId {
+		Self::primary_of(account_id).unwrap_or_else(|| account_id.clone())
+	}
+
+	/// Whether `account_id` has a score of its own or shares one
+	pub fn is_bootstrapped(account_id: &T::AccountId) -> bool {
+		<Reputation<T>>::exists(account_id) || <PrimaryOf<T>>::exists(account_id)
 	}
 
 	/// Change of reputation according to different status
 	pub fn reputation_change(account_id: T::AccountId, op: ReputationOp) -> DispatchResult {
+		let account_id = Self::primary(&account_id);
+		// Only bootstrapped accounts hold a score that can move.
+		if !<Reputation<T>>::exists(&account_id) {
+			return Ok(());
+		}
 		let mut rep = Self::get_account_reputation_level(&account_id);
 		match op {
 			ReputationOp::CompleteIncrease => {
-				Self::deposit_event(RawEvent::FailedReduce(account_id.clone(), rep.reduce()));
+				Self::deposit_event(RawEvent::CompletionIncrease(account_id.clone(), rep.increase(Self::reputation_step())));
 			}
 			ReputationOp::FailedReduce => {
-				Self::deposit_event(RawEvent::CompletionIncrease(account_id.clone(), rep.increase()));
+				Self::deposit_event(RawEvent::FailedReduce(account_id.clone(), rep.reduce(Self::reputation_step())));
 			}
 			_ => {}
 		}
@@ -93,4 +406,50 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Record the rating `rater` left for `ratees` on the task `hash`
+	///
+	/// Every rater can rate a task once. The caller checks that `rater` took part in the task.
+	pub fn rate(hash: T::Hash, rater: T::AccountId, ratees: Vec<T::AccountId>, role: RatedRole, rating: u8, review: T::Hash) -> DispatchResult {
+		ensure!(rating >= 1 && rating <= MAX_RATING, Error::<T>::RatingInvalid);
+		ensure!(!<Ratings<T>>::exists((hash.clone(), rater.clone())), Error::<T>::RatingDuplicated);
+		// linked accounts share one summary, which is rated once.
+		let mut scored: Vec<T::AccountId> = ratees.iter().map(Self::primary).collect();
+		scored.sort();
+		scored.dedup();
+		for ratee in scored.iter() {
+			let add = |summary: &mut RatingSummary| summary.merge(RatingSummary { count: 1, total: rating as u32 });
+			match role {
+				RatedRole::Issuer => <IssuerRatings<T>>::mutate(ratee, add),
+				RatedRole::Worker => <WorkerRatings<T>>::mutate(ratee, add),
+			}
+		}
+		<Ratings<T>>::insert((hash.clone(), rater.clone()), Rating { ratees, role, rating, review });
+		Self::deposit_event(RawEvent::Rated(rater, hash, rating));
+		Ok(())
+	}
+
+	/// Score of `account` as an issuer, derived from the ratings left by workers and shared by
+	/// all accounts linked to it
+	pub fn issuer_score(account: &T::AccountId) -> u32 {
+		Self::derived_score(Self::issuer_ratings(Self::primary(account)))
+	}
+
+	/// Score of `account` as a worker, derived from the ratings left by issuers and shared by
+	/// all accounts linked to it
+	pub fn worker_score(account: &T::AccountId) -> u32 {
+		Self::derived_score(Self::worker_ratings(Self::primary(account)))
+	}
+
+	/// Map the ratings onto the scale of `InitialScore`, where `MAX_RATING` is worth 100
+	fn derived_score(summary: RatingSummary) -> u32 {
+		let weight = Self::rating_prior_weight() as u64;
+		let rated = summary.total as u64 * 100 / MAX_RATING as u64;
+		let prior = Self::initial_score() as u64 * weight;
+		let count = summary.count as u64 + weight;
+		if count == 0 {
+			return Self::initial_score();
+		}
+		((rated + prior) / count) as u32
+	}
+
 }
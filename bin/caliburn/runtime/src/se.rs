@@ -1,98 +1,76 @@
-//use core::u32::MAX as MAX_SUBJECT;
+//! Deprecated proxy of the DeWork calls, kept so that clients built against it keep working
+//! until `dework::LegacyCallsEnd`. Use the `DeWork` calls instead.
+//!
+//! The module and its calls keep their original indices in the runtime, and the calls forward
+//! to the same functions as the `DeWork` calls do.
 
-use codec::{Decode, Encode};
-
-use frame_support::{decl_event, decl_module, decl_storage, ensure, StorageMap, StorageValue};
-//use sp_std::prelude::*;
-//use runtime_primitives::traits::{Hash};
-//use nicks;
-//use identity;
+use frame_support::{decl_event, decl_module};
 use sp_runtime::DispatchResult;
-//use sp_runtime::traits::Hash;
 use sp_std::prelude::*;
-use sp_std::prelude::Vec;
 use system::ensure_signed;
 
-use crate::identity::{self};
-use crate::task_board::{self, Error as BoardError, Task, TaskKind};
-
-//use log::info;
+use crate::dework;
 
-pub trait Trait: system::Trait + timestamp::Trait + balances::Trait /*+ nicks::Trait*/ + task_board::Trait + identity::Trait {
+pub trait Trait: dework::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 }
 
-//pub type Subject = u32;
-
-#[cfg_attr(feature = "std", derive(Debug))]
-#[derive(Encode, Decode, Clone, Default, PartialEq)]
-pub struct Credential<Timestamp, AccountId> {
-	subject: u32,
-	when: Timestamp,
-	by: AccountId,
-}
-
-decl_storage! {
-    trait Store for Module<T: Trait> as Caliburn {
-    }
-}
-
 decl_event!(
-    pub enum Event<T>
-    where
-        AccountId = <T as system::Trait>::AccountId,
-        Hash = <T as system::Trait>::Hash,
-
-    {
-        //A new task is published.
-        TaskPublished(AccountId),
-        //A new task is claimed.
-        TaskClaimed(AccountId, Hash),
-    }
+	pub enum Event<T>
+	where
+		AccountId = <T as system::Trait>::AccountId,
+	{
+		/// A deprecated call was dispatched by this account.
+		LegacyCallUsed(AccountId),
+	}
 );
 
 decl_module! {
-    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		fn deposit_event() = default;
 
-        /// Issue a credential to an identity.
-        /// Only an issuer can call this function.
-        pub fn issue(origin, to: T::AccountId, subject: u32) -> DispatchResult{
-			identity::Module::<T>::do_issue(origin, to, subject)
-        }
-
-        /// Publish a task.
-        pub fn publish_task(origin, _description: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) -> DispatchResult {
-          task_board::Module::<T>::publish_task(origin, _description, min_rep, pay, judge_pay, req_subjects)
-        }
+		/// Deprecated: use `DeWork::issue`.
+		pub fn issue(origin, to: T::AccountId, subject: u32) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::issue(origin, to, subject))
+		}
 
-        /// Claim a task.
-        pub fn claim_task(origin, task_hash: T::Hash, players: Vec<T::AccountId>) -> DispatchResult {
-          <task_board::Module<T>>::claim_task(origin, task_hash, players)
-        }
+		/// Deprecated: use `DeWork::publish_task`.
+		pub fn publish_task(origin, description: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::publish_task(origin, description, min_rep, pay, judge_pay, req_subjects))
+		}
 
-        /// Revoke a credential.
-        /// Only an issuer can call this function.
-        pub fn revoke_credential(origin, to: T::AccountId, subject: u32) -> DispatchResult {
-			identity::Module::<T>::do_revoke_credential(origin, to, subject)
-        }
+		/// Deprecated: use `DeWork::claim_task`.
+		pub fn claim_task(origin, task_hash: T::Hash, players: Vec<T::AccountId>) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::claim_task(origin, task_hash, players))
+		}
 
-        /// Verify a credential.
-        pub fn verify_credential(origin, holder: T::AccountId, subject: u32) -> DispatchResult {
-			identity::Module::<T>::do_verify_credential(origin, holder, subject)
-        }
+		/// Deprecated: use `DeWork::revoke_credential`.
+		pub fn revoke_credential(origin, to: T::AccountId, subject: u32) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::revoke_credential(origin, to, subject))
+		}
 
-        /// Create a new subject.
-        pub fn create_subject(origin) -> DispatchResult {
-			identity::Module::<T>::do_create_subject(origin)
-        }
+		/// Deprecated: use `DeWork::verify_credential`.
+		pub fn verify_credential(origin, holder: T::AccountId, subject: u32) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::verify_credential(origin, holder, subject))
+		}
 
-        ///register User
-        pub fn register_user(origin, name: Vec<u8>, email: Vec<u8>, description: Vec<u8>, additional: Vec<u8>, kyc_hash: Vec<u8>) -> DispatchResult{
-			identity::Module::<T>::do_register_user(origin, name, email, description, additional, kyc_hash)
+		/// Deprecated: use `DeWork::create_subject`.
+		pub fn create_subject(origin) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::create_subject(origin))
 		}
 
-    }
+		/// Deprecated: use `DeWork::register_user`.
+		pub fn register_user(origin, name: Vec<u8>, email: Vec<u8>, description: Vec<u8>, additional: Vec<u8>, kyc_hash: Vec<u8>) -> DispatchResult {
+			Self::forward(origin, |origin| <dework::Module<T>>::register_user(origin, name, email, description, additional, kyc_hash))
+		}
+	}
 }
 
+impl<T: Trait> Module<T> {
+	fn forward(origin: T::Origin, call: impl FnOnce(T::Origin) -> DispatchResult) -> DispatchResult {
+		let who = ensure_signed(origin.clone())?;
+		call(origin)?;
+		Self::deposit_event(RawEvent::LegacyCallUsed(who));
+		Ok(())
+	}
+}
@@ -1,28 +1,75 @@
 use codec::{Decode, Encode};
 
 use frame_support::{
+	debug,
 	decl_error,
 	decl_event,
 	decl_module,
 	decl_storage,
 	ensure, StorageMap,
 	StorageValue,
+	weights::Weight,
 	traits::{
 		Currency,
 		ExistenceRequirement,
+		Get,
+		Imbalance,
+		OnUnbalanced,
+		ReservableCurrency,
 		WithdrawReason,
 		WithdrawReasons,
 	},
 };
-use sp_runtime::{DispatchResult, RuntimeDebug, traits::Hash};
-use sp_std::{ops::Div};
+use frame_support::storage::StoragePrefixedMap;
+use sp_core::offchain::{Duration, StorageKind};
+use sp_runtime::{DispatchResult, Permill, RuntimeDebug, traits::{CheckedAdd, EnsureOrigin, Hash, One, SaturatedConversion, Saturating, Zero}};
+use sp_runtime::offchain::http;
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionPriority, TransactionValidity, ValidTransaction,
+};
+use sp_std::{cmp::Ordering, ops::Div};
 use sp_std::prelude::*;
-use system::{self, ensure_signed};
+use system::{self, ensure_none, ensure_signed, offchain::SubmitUnsignedTransaction};
+
+use crate::{identity, judge_pool, organisation, reputation};
+use crate::reputation::{RatedRole, ReputationOp};
+use crate::migration::{self, VersionNumber};
+
+/// The current version of this module's storage layout.
+pub const STORAGE_VERSION: VersionNumber = 2;
+
+/// Offchain local storage key holding the URL the offchain worker posts notifications to.
+pub const WEBHOOK_KEY: &[u8] = b"dework::webhook";
+
+/// Offchain local storage prefix marking the deadline a task was last notified about.
+const NOTIFIED_PREFIX: &[u8] = b"dework::notified::";
+
+/// How long the offchain worker waits for the webhook to answer.
+const WEBHOOK_TIMEOUT_MS: u64 = 3_000;
+
+/// Blocks a poke stays valid in the transaction pool.
+const POKE_LONGEVITY: u64 = 64;
+
+/// Most tasks `eligible_tasks` returns at once.
+pub const MAX_ELIGIBLE_PAGE: u32 = 100;
+
+/// Weight of handling one due schedule in `on_initialize`.
+const SCHEDULE_PUBLISH_WEIGHT: Weight = 100_000;
 
-use crate::{identity, judge_pool, reputation};
+type NegativeImbalanceOf<T> = <balances::Module<T> as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
 
-pub trait Trait: system::Trait + timestamp::Trait + balances::Trait + reputation::Trait + identity::Trait + judge_pool::Trait {
+pub trait Trait: system::Trait + timestamp::Trait + balances::Trait + reputation::Trait + identity::Trait + judge_pool::Trait + organisation::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Origin allowed to change the task board parameters.
+	type ParameterOrigin: EnsureOrigin<Self::Origin>;
+	/// Receiver of the protocol fee taken from the pay of every completed task.
+	type ProtocolFeeDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+	/// The overarching call type the offchain worker submits pokes as.
+	type Call: From<Call<Self>>;
+	/// Submits the unsigned pokes of the offchain worker.
+	type SubmitTransaction: SubmitUnsignedTransaction<Self, <Self as Trait>::Call>;
+	/// Most recurring schedules handled in one block; the rest carry over to the next block.
+	type MaxScheduledPerBlock: Get<u32>;
 }
 
 #[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
@@ -39,6 +86,12 @@ pub struct Task<T: Trait> {
 	pub history: Vec<(TaskKind, T::Moment)>,
 	pub req_subjects: Vec<u32>,
 	pub delivery_certificate: T::Hash,
+	/// The task whose pay funds this sub-task
+	pub parent: Option<T::Hash>,
+	/// Sub-tasks that must be `Done` before this task can be delivered
+	pub children: Vec<T::Hash>,
+	/// Tasks that must be `Done` before this task can be claimed
+	pub blocked_by: Vec<T::Hash>,
 }
 
 impl<T: Trait> Default for Task<T> {
@@ -55,6 +108,52 @@ impl<T: Trait> Default for Task<T> {
 			history: Default::default(),
 			req_subjects: Default::default(),
 			delivery_certificate: Default::default(),
+			parent: Default::default(),
+			children: Default::default(),
+			blocked_by: Default::default(),
+		}
+	}
+}
+
+impl<T: Trait> Task<T> {
+	pub fn is_final(&self) -> bool {
+		self.kind == TaskKind::Failure || self.kind == TaskKind::Done
+	}
+}
+
+/// `Task` as laid out by storage version 1, before sub-tasks.
+#[derive(Encode, Decode)]
+struct TaskV1<T: Trait> {
+	hash: T::Hash,
+	issuer: T::AccountId,
+	receivers: Vec<T::AccountId>,
+	description: Vec<u8>,
+	judge_pay: T::Balance,
+	pay: T::Balance,
+	min_rep: u32,
+	kind: TaskKind,
+	history: Vec<(TaskKind, T::Moment)>,
+	req_subjects: Vec<u32>,
+	delivery_certificate: T::Hash,
+}
+
+impl<T: Trait> From<TaskV1<T>> for Task<T> {
+	fn from(old: TaskV1<T>) -> Self {
+		Task {
+			hash: old.hash,
+			issuer: old.issuer,
+			receivers: old.receivers,
+			description: old.description,
+			judge_pay: old.judge_pay,
+			pay: old.pay,
+			min_rep: old.min_rep,
+			kind: old.kind,
+			history: old.history,
+			req_subjects: old.req_subjects,
+			delivery_certificate: old.delivery_certificate,
+			parent: None,
+			children: Vec::new(),
+			blocked_by: Vec::new(),
 		}
 	}
 }
@@ -91,17 +190,129 @@ pub struct Board<Hash> {
 	pub task_kind: TaskKind,
 }
 
+/// A reusable task definition stored per issuer.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct TaskTemplate<Balance> {
+	pub description: Vec<u8>,
+	pub min_rep: u32,
+	pub pay: Balance,
+	pub judge_pay: Balance,
+	pub req_subjects: Vec<u32>,
+}
+
+/// A `Published` task an account can claim, as listed by `Module::eligible_tasks`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, Eq, PartialEq)]
+pub struct EligibleTask<Hash, Balance, Moment, BlockNumber> {
+	pub hash: Hash,
+	pub pay: Balance,
+	pub judge_pay: Balance,
+	pub min_rep: u32,
+	/// Required subjects, every one of which the account holds a credential on
+	pub req_subjects: Vec<u32>,
+	pub published_at: Moment,
+	/// Last block the task can be claimed in, if its issuer set one
+	pub claim_deadline: Option<BlockNumber>,
+}
+
+/// A participant's claim for compensation out of the insurance pool.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct Appeal<Hash, Balance> {
+	/// Hash of the statement backing the appeal
+	pub statement: Hash,
+	pub resolved: bool,
+	/// Paid out of the insurance pool when the appeal was upheld
+	pub compensation: Balance,
+	/// Reserved from the appellant; returned when the appeal is upheld, added to the insurance
+	/// pool when it is rejected
+	pub deposit: Balance,
+}
+
+/// State of the insurance pool, as reported by `Module::insurance_solvency`.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct InsuranceSolvency<Balance> {
+	/// Balance available to compensate appellants
+	pub pool: Balance,
+	/// Pay escrowed in claimed tasks that are not final yet
+	pub exposure: Balance,
+	/// Appeals governance has not resolved yet
+	pub pending_appeals: u32,
+}
+
+/// Template fields replaced when publishing from a template; `None` keeps the template value.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct TemplateOverrides<Balance> {
+	pub description: Option<Vec<u8>>,
+	pub min_rep: Option<u32>,
+	pub pay: Option<Balance>,
+	pub judge_pay: Option<Balance>,
+	pub req_subjects: Option<Vec<u32>>,
+}
+
+impl<Balance> TaskTemplate<Balance> {
+	pub fn apply(self, overrides: TemplateOverrides<Balance>) -> Self {
+		TaskTemplate {
+			description: overrides.description.unwrap_or(self.description),
+			min_rep: overrides.min_rep.unwrap_or(self.min_rep),
+			pay: overrides.pay.unwrap_or(self.pay),
+			judge_pay: overrides.judge_pay.unwrap_or(self.judge_pay),
+			req_subjects: overrides.req_subjects.unwrap_or(self.req_subjects),
+		}
+	}
+}
+
+/// Publishes a template every `period` blocks out of the issuer's reserved `funds`.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, Eq, PartialEq)]
+pub struct RecurringSchedule<BlockNumber, Balance> {
+	pub period: BlockNumber,
+	pub next: BlockNumber,
+	/// Balance still reserved on the issuer for future tasks.
+	pub funds: Balance,
+}
+
 decl_event! {
 	pub enum Event < T >
 	where
 		AccountId = <T as system::Trait >::AccountId,
 		Hash = < T as system::Trait >::Hash,
 		Timestamp = < T as timestamp::Trait >::Moment,
+		BlockNumber = < T as system::Trait >::BlockNumber,
+		Balance = < T as balances::Trait >::Balance,
 	{
 		/// params: task-hash, form_kind, to_kind
 		TaskChangeState(Hash, u8, u8, Timestamp),
 		TaskPublish(AccountId, Hash, Timestamp),
 		TaskClaimed(AccountId, Hash),
+		/// params: issuer, template id
+		TemplateCreated(AccountId, u32),
+		TemplateRemoved(AccountId, u32),
+		/// params: issuer, template id, first publication
+		ScheduleCreated(AccountId, u32, BlockNumber),
+		/// params: issuer, template id, unreserved remainder
+		ScheduleEnded(AccountId, u32, Balance),
+		/// params: parent task-hash, sub-task-hash
+		SubTaskPublished(Hash, Hash),
+		/// params: task-hash, last block it can be claimed in
+		ClaimDeadlineSet(Hash, Option<BlockNumber>),
+		/// params: task-hash, fee sent to the protocol fee destination
+		ProtocolFeePaid(Hash, Balance),
+		/// params: new max sub-tasks, new max blocked-by
+		TaskLimitsChanged(u32, u32),
+		/// params: new protocol fee
+		ProtocolFeeChanged(Permill),
+		/// params: task-hash accepted after its acceptance period ran out
+		DeliveryAutoAccepted(Hash),
+		/// params: new acceptance period, new deadline notice
+		DeadlineParamsChanged(BlockNumber, BlockNumber),
+		/// params: task-hash, premium added to the insurance pool
+		InsurancePremiumPaid(Hash, Balance),
+		/// params: new insurance premium
+		InsurancePremiumChanged(Permill),
+		/// params: new appeal deposit
+		AppealDepositChanged(Balance),
+		/// params: appellant, task-hash, statement
+		AppealFiled(AccountId, Hash, Hash),
+		/// params: task-hash, appellant, compensation paid out of the insurance pool
+		AppealResolved(Hash, AccountId, Balance),
 	}
 }
 
@@ -126,28 +337,121 @@ decl_error! {
 		FundsRecvRewardWrongTime,
 		FundsIssuserBackWrongTime,
 		PermissionError,
+		TemplateNotFound,
+		ScheduleDuplicated,
+		ScheduleNotFound,
+		SchedulePeriodZero,
+		ScheduleUnderfunded,
+		TaskBlocked,
+		TaskHasSubTasks,
+		SubTasksPending,
+		SubTaskLimitReached,
+		ParentInvalid,
+		ParentFundsInsufficient,
+		DependencyInvalid,
+		DependencyLimitReached,
+		DeadlineNotReached,
+		ClaimDeadlinePassed,
+		AppealInvalid,
+		AppealDuplicated,
+		AppealNotFound,
+		InsuranceInsufficient,
 	}
 
 }
 
 decl_storage! {
 	trait Store for Module < T: Trait > as DeWorkTasks {
-		Tasks get(tasks): map u64 => Task<T>;
-		TaskCount get(task_count): u64;
-		TaskIndex: map T::Hash => u64;
+		pub Tasks get(tasks): map u64 => Task<T>;
+		pub TaskCount get(task_count): u64;
+		pub TaskIndex: map T::Hash => u64;
 
-		BoardManager get(load_board): map u8 => Board < T::Hash >;
+		pub BoardManager get(load_board): map u8 => Board < T::Hash >;
 		Nonce: u64;
-		IssuerPayPool: map (T::AccountId, T::Hash) => T::Balance;
-		StakingPayPool: map (T::AccountId, T::Hash) => T::Balance;
+		IssuerPayPool get(issuer_pay_pool): map (T::AccountId, T::Hash) => T::Balance;
+		StakingPayPool get(staking_pay_pool): map (T::AccountId, T::Hash) => T::Balance;
+
+		/// Task templates by (issuer, template id).
+		Templates get(templates): map (T::AccountId, u32) => Option<TaskTemplate<T::Balance>>;
+		/// Next template id of each issuer.
+		TemplateCount get(template_count): map T::AccountId => u32;
+		/// Recurring publication of a template, by (issuer, template id).
+		Schedules get(schedules): map (T::AccountId, u32) => Option<RecurringSchedule<T::BlockNumber, T::Balance>>;
+		/// Schedules due at a block.
+		ScheduleQueue: map T::BlockNumber => Vec<(T::AccountId, u32)>;
+
+		/// Maximum number of sub-tasks of a single task.
+		MaxSubTasks get(max_sub_tasks) config(): u32 = 16;
+		/// Maximum number of tasks a single task can be blocked by.
+		MaxBlockedBy get(max_blocked_by) config(): u32 = 16;
+		/// Share of the pay of a completed task sent to `ProtocolFeeDestination`.
+		ProtocolFee get(protocol_fee) config(): Permill;
+
+		/// Lowest issuer score each worker accepts when claiming a task.
+		MinIssuerScore get(min_issuer_score): map T::AccountId => u32;
+
+		/// Blocks the issuer has to dispute a delivery before it is accepted on their behalf;
+		/// zero never accepts deliveries automatically.
+		AcceptancePeriod get(acceptance_period) config(): T::BlockNumber;
+		/// How many blocks ahead of a deadline the offchain worker notifies the local webhook.
+		DeadlineNotice get(deadline_notice) config(): T::BlockNumber;
+		/// Last block each published task can be claimed in, if its issuer set one.
+		ClaimDeadline get(claim_deadline): map T::Hash => Option<T::BlockNumber>;
+		/// Block at which each task waiting for acceptance was delivered.
+		DeliveredAt get(delivered_at): map T::Hash => Option<T::BlockNumber>;
+
+		/// Share of the pay of every task added to the insurance pool when it is published.
+		InsurancePremium get(insurance_premium) config(): Permill;
+		/// Balance of the insurance pool, held outside of any account like the task escrow.
+		InsurancePool get(insurance_pool): T::Balance;
+		/// Appeals by (task-hash, appellant).
+		Appeals get(appeals): map (T::Hash, T::AccountId) => Option<Appeal<T::Hash, T::Balance>>;
+		/// Number of appeals governance has not resolved yet.
+		PendingAppeals get(pending_appeals): u32;
+		/// Reserved from every appellant until governance resolves the appeal.
+		AppealDeposit get(appeal_deposit) config(): T::Balance;
+
+		/// The version of storage for upgrade.
+		StorageVersion get(storage_version) build(|_| STORAGE_VERSION): VersionNumber;
+	}
+	add_extra_genesis {
+		/// Tasks published at genesis: issuer, description, min_rep, pay, judge_pay, req_subjects.
+		config(tasks): Vec<(T::AccountId, Vec<u8>, u32, T::Balance, T::Balance, Vec<u32>)>;
+		build(|config| {
+			for (issuer, description, min_rep, pay, judge_pay, req_subjects) in &config.tasks {
+				let template = TaskTemplate {
+					description: description.clone(),
+					min_rep: *min_rep,
+					pay: *pay,
+					judge_pay: *judge_pay,
+					req_subjects: req_subjects.clone(),
+				};
+				Module::<T>::publish(issuer.clone(), template).expect("genesis task issuer must be able to fund it; qed");
+			}
+		});
 	}
 }
 
 decl_module! {
 	pub struct Module < T: Trait > for enum Call where origin: T::Origin {
 		type Error = Error < T >;
+
+		/// Most recurring schedules handled in one block.
+		const MaxScheduledPerBlock: u32 = T::MaxScheduledPerBlock::get();
+
 		fn deposit_event() = default;
 
+		fn on_initialize(n: T::BlockNumber) {
+			Self::ensure_storage_upgraded();
+			let weight = Self::publish_scheduled(n);
+			<system::Module<T>>::register_extra_weight_unchecked(weight);
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			debug::RuntimeLogger::init();
+			Self::scan_deadlines(now);
+		}
+
 		/// Publish tasks on bulletin boards
 		pub fn publish_task(origin , desc: Vec < u8 >, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec < u32 > ) {
 			Self::do_publish_task(origin, desc, min_rep, pay, judge_pay, req_subjects)?;
@@ -172,6 +476,150 @@ decl_module! {
 		pub fn task_to_final(origin, hash: T::Hash) {
 			Self::do_task_to_final(origin, hash)?;
 		}
+
+		/// Publish a sub-task funded out of the pay of a parent task
+		pub fn publish_sub_task(origin, parent: T::Hash, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>, blocked_by: Vec<T::Hash>) {
+			let template = TaskTemplate { description: desc, min_rep, pay, judge_pay, req_subjects };
+			Self::do_publish_sub_task(origin, parent, template, blocked_by)?;
+		}
+
+		/// Withdraw a task nobody has claimed yet
+		pub fn cancel_task(origin, hash: T::Hash) {
+			Self::do_cancel_task(origin, hash)?;
+		}
+
+		/// Rate the other side of a task that is `Done` or `Failure`
+		pub fn rate_task(origin, hash: T::Hash, rating: u8, review: T::Hash) {
+			Self::do_rate_task(origin, hash, rating, review)?;
+		}
+
+		/// Accept a delivery or close a dispute whose deadline passed
+		///
+		/// Submitted unsigned by the offchain worker; anybody else may submit it too.
+		pub fn poke(origin, hash: T::Hash) {
+			ensure_none(origin)?;
+			Self::do_poke(hash)?;
+		}
+
+		/// Ask governance for compensation out of the insurance pool
+		///
+		/// Open to the participants of a claimed task whose receivers disappeared, or of a task
+		/// a judgement finalised.
+		pub fn appeal(origin, hash: T::Hash, statement: T::Hash) {
+			Self::do_appeal(origin, hash, statement)?;
+		}
+
+		/// Only claim tasks whose issuer has at least `score`
+		pub fn set_min_issuer_score(origin, score: u32) {
+			let sender = ensure_signed(origin)?;
+			<MinIssuerScore<T>>::insert(sender, score);
+		}
+
+		/// Store a reusable task template
+		pub fn create_template(origin, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) {
+			let sender = ensure_signed(origin)?;
+			let id = <TemplateCount<T>>::get(&sender);
+			let next = id.checked_add(1).ok_or(Error::<T>::TaskCheckAddFail)?;
+			let template = TaskTemplate { description: desc, min_rep, pay, judge_pay, req_subjects };
+			<Templates<T>>::insert((sender.clone(), id), template);
+			<TemplateCount<T>>::insert(&sender, next);
+			Self::deposit_event(RawEvent::TemplateCreated(sender, id));
+		}
+
+		/// Remove a template together with its schedule
+		pub fn remove_template(origin, id: u32) {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Templates<T>>::exists((sender.clone(), id)), Error::<T>::TemplateNotFound);
+			Self::end_schedule(&sender, id);
+			<Templates<T>>::remove((sender.clone(), id));
+			Self::deposit_event(RawEvent::TemplateRemoved(sender, id));
+		}
+
+		/// Publish a task from a template, replacing the given fields
+		pub fn publish_from_template(origin, id: u32, overrides: TemplateOverrides<T::Balance>) {
+			let sender = ensure_signed(origin)?;
+			let template = Self::templates((sender.clone(), id)).ok_or(Error::<T>::TemplateNotFound)?;
+			Self::publish(sender, template.apply(overrides))?;
+		}
+
+		/// Publish a template every `period` blocks, reserving `funds` to pay for the tasks
+		pub fn schedule_template(origin, id: u32, period: T::BlockNumber, funds: T::Balance) {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Templates<T>>::exists((sender.clone(), id)), Error::<T>::TemplateNotFound);
+			ensure!(!<Schedules<T>>::exists((sender.clone(), id)), Error::<T>::ScheduleDuplicated);
+			ensure!(!period.is_zero(), Error::<T>::SchedulePeriodZero);
+			<balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, funds)?;
+
+			let next = <system::Module<T>>::block_number() + period;
+			<Schedules<T>>::insert((sender.clone(), id), RecurringSchedule { period, next, funds });
+			<ScheduleQueue<T>>::mutate(next, |due| due.push((sender.clone(), id)));
+			Self::deposit_event(RawEvent::ScheduleCreated(sender, id, next));
+		}
+
+		/// Reserve more funds for a recurring schedule
+		pub fn fund_schedule(origin, id: u32, amount: T::Balance) {
+			let sender = ensure_signed(origin)?;
+			let mut schedule = Self::schedules((sender.clone(), id)).ok_or(Error::<T>::ScheduleNotFound)?;
+			schedule.funds = schedule.funds.checked_add(&amount).ok_or(Error::<T>::TaskCheckAddFail)?;
+			<balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, amount)?;
+			<Schedules<T>>::insert((sender, id), schedule);
+		}
+
+		/// Stop a recurring schedule and unreserve its remaining funds
+		pub fn cancel_schedule(origin, id: u32) {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Schedules<T>>::exists((sender.clone(), id)), Error::<T>::ScheduleNotFound);
+			Self::end_schedule(&sender, id);
+		}
+
+		/// Changing the sub-task and dependency limits through governance
+		fn set_task_limits(origin, max_sub_tasks: u32, max_blocked_by: u32) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<MaxSubTasks>::put(max_sub_tasks);
+			<MaxBlockedBy>::put(max_blocked_by);
+			Self::deposit_event(RawEvent::TaskLimitsChanged(max_sub_tasks, max_blocked_by));
+		}
+
+		/// Changing the protocol fee through governance
+		fn set_protocol_fee(origin, fee: Permill) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<ProtocolFee>::put(fee);
+			Self::deposit_event(RawEvent::ProtocolFeeChanged(fee));
+		}
+
+		/// Changing the acceptance period and deadline notice through governance
+		fn set_deadline_params(origin, acceptance_period: T::BlockNumber, deadline_notice: T::BlockNumber) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<AcceptancePeriod<T>>::put(acceptance_period);
+			<DeadlineNotice<T>>::put(deadline_notice);
+			Self::deposit_event(RawEvent::DeadlineParamsChanged(acceptance_period, deadline_notice));
+		}
+
+		/// Changing the insurance premium through governance
+		fn set_insurance_premium(origin, premium: Permill) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<InsurancePremium>::put(premium);
+			Self::deposit_event(RawEvent::InsurancePremiumChanged(premium));
+		}
+
+		/// Resolve an appeal through governance, paying `compensation` out of the insurance pool;
+		/// zero rejects the appeal
+		fn resolve_appeal(origin, hash: T::Hash, appellant: T::AccountId, compensation: T::Balance) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			Self::do_resolve_appeal(hash, appellant, compensation)?;
+		}
+
+		/// Stop a published task being claimed after `deadline`; `None` lifts the deadline
+		pub fn set_claim_deadline(origin, hash: T::Hash, deadline: Option<T::BlockNumber>) {
+			Self::do_set_claim_deadline(origin, hash, deadline)?;
+		}
+
+		/// Changing the appeal deposit through governance
+		fn set_appeal_deposit(origin, deposit: T::Balance) {
+			<T as Trait>::ParameterOrigin::ensure_origin(origin)?;
+			<AppealDeposit<T>>::put(deposit);
+			Self::deposit_event(RawEvent::AppealDepositChanged(deposit));
+		}
 	}
 }
 
@@ -179,24 +627,83 @@ impl<T: Trait> Module<T> {
 	/// Publish tasks on bulletin boards
 	pub fn do_publish_task(origin: T::Origin, desc: Vec<u8>, min_rep: u32, pay: T::Balance, judge_pay: T::Balance, req_subjects: Vec<u32>) -> DispatchResult {
 		let sender = ensure_signed(origin)?;
-		let mut task = Task::default();
-		task.description = desc;
-		task.min_rep = min_rep;
-		task.pay = pay;
-		task.judge_pay = judge_pay;
-		task.issuer = sender.clone();
-		task.req_subjects = req_subjects.clone();
-		task.hash = Self::task_hash(&task);
-		ensure!( ! ( < TaskIndex < T > >::exists(task.hash.clone())), Error::< T >::TaskDuplicated);
+		Self::publish(sender, TaskTemplate { description: desc, min_rep, pay, judge_pay, req_subjects })
+	}
+
+	/// Publish a task described by `template` on behalf of `issuer`
+	pub fn publish(issuer: T::AccountId, template: TaskTemplate<T::Balance>) -> DispatchResult {
+		let mut task = Self::new_task(issuer, template);
 		Self::change_task_status(&mut task, TaskKind::Published)?;
 		Ok(())
 	}
 
+	/// Publish a sub-task of `parent`, blocked until every task in `blocked_by` is done
+	pub fn do_publish_sub_task(origin: T::Origin, parent: T::Hash, template: TaskTemplate<T::Balance>, blocked_by: Vec<T::Hash>) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		let mut parent_task = Self::query_task_by_hash(parent.clone())?;
+		ensure!(parent_task.issuer == sender, Error::<T>::PermissionError);
+		ensure!(parent_task.kind == TaskKind::Published || parent_task.kind == TaskKind::InDelivery, Error::<T>::ParentInvalid);
+		ensure!((parent_task.children.len() as u32) < Self::max_sub_tasks(), Error::<T>::SubTaskLimitReached);
+		ensure!(Self::issuer_pay_pool((sender.clone(), parent.clone())) >= template.pay, Error::<T>::ParentFundsInsufficient);
+
+		let mut blocked_by = blocked_by;
+		blocked_by.sort();
+		blocked_by.dedup();
+		ensure!((blocked_by.len() as u32) <= Self::max_blocked_by(), Error::<T>::DependencyLimitReached);
+		for dependency in &blocked_by {
+			let dependency = Self::query_task_by_hash(dependency.clone()).map_err(|_| Error::<T>::DependencyInvalid)?;
+			ensure!(dependency.issuer == sender, Error::<T>::DependencyInvalid);
+		}
+
+		let mut task = Self::new_task(sender, template);
+		task.parent = Some(parent.clone());
+		task.blocked_by = blocked_by;
+		Self::change_task_status(&mut task, TaskKind::Published)?;
+
+		parent_task.children.push(task.hash.clone());
+		Self::store_task(&parent_task);
+		Self::deposit_event(RawEvent::SubTaskPublished(parent, task.hash));
+		Ok(())
+	}
+
+	/// Withdraw a published task, returning its funds to the issuer or to its parent
+	pub fn do_cancel_task(origin: T::Origin, hash: T::Hash) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		let mut task = Self::query_task_by_hash(hash)?;
+		ensure!(task.issuer == sender, Error::<T>::PermissionError);
+		ensure!(task.kind == TaskKind::Published, Error::<T>::TaskKindInvalid);
+		ensure!(task.children.is_empty(), Error::<T>::TaskHasSubTasks);
+		Self::change_task_status(&mut task, TaskKind::Failure)
+	}
+
+	/// Set or lift the claim deadline of a published task
+	pub fn do_set_claim_deadline(origin: T::Origin, hash: T::Hash, deadline: Option<T::BlockNumber>) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		let task = Self::query_task_by_hash(hash.clone())?;
+		ensure!(task.issuer == sender, Error::<T>::PermissionError);
+		ensure!(task.kind == TaskKind::Published, Error::<T>::TaskKindInvalid);
+		match deadline {
+			Some(deadline) => {
+				ensure!(deadline >= <system::Module<T>>::block_number(), Error::<T>::ClaimDeadlinePassed);
+				<ClaimDeadline<T>>::insert(&hash, deadline);
+			},
+			None => <ClaimDeadline<T>>::remove(&hash),
+		}
+		Self::deposit_event(RawEvent::ClaimDeadlineSet(hash, deadline));
+		Ok(())
+	}
+
+	/// Whether the claim deadline of `hash` has passed
+	pub fn claim_deadline_passed(hash: &T::Hash) -> bool {
+		Self::claim_deadline(hash).map_or(false, |deadline| <system::Module<T>>::block_number() > deadline)
+	}
+
 	/// Claim accept task
 	pub fn do_claim_task(leader: T::Origin, hash: T::Hash, players: Vec<T::AccountId>) -> DispatchResult {
 		let sender = ensure_signed(leader)?;
 		let mut task = Self::query_task_by_hash(hash)?;
 		ensure!(task.kind.clone() == TaskKind::Published, <Error<T>>::TaskNotWaitForRecv);
+		ensure!(!Self::claim_deadline_passed(&task.hash), <Error<T>>::ClaimDeadlinePassed);
 		let mut players = players;
 		ensure!(!players.contains(&sender), <Error<T>>::TaskTeamLeaderRepeatSetting);
 		players.push(sender.clone());
@@ -205,11 +712,30 @@ impl<T: Trait> Module<T> {
 			ensure!(Self::verify_claim(&task, p), <Error<T>>::TaskPlayerIsInvalid);
 		}
 		task.receivers = players;
-		Self::change_task_status(&mut task, TaskKind::InDelivery);
+		Self::change_task_status(&mut task, TaskKind::InDelivery)?;
 		Self::deposit_event(RawEvent::TaskClaimed(sender, hash));
 		Ok(())
 	}
 
+	/// Rate the other side of a finished task
+	///
+	/// The issuer rates every receiver as a worker; each receiver rates the issuer.
+	pub fn do_rate_task(origin: T::Origin, hash: T::Hash, rating: u8, review: T::Hash) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		let task = Self::query_task_by_hash(hash.clone())?;
+		ensure!(task.is_final(), Error::<T>::TaskKindInvalid);
+		ensure!(!task.receivers.is_empty(), Error::<T>::TaskRecvEmpty);
+		let (ratees, role) = if sender == task.issuer {
+			// A squad's members share the ratings it earns.
+			(<organisation::Module<T>>::expand_squads(task.receivers.clone()), RatedRole::Worker)
+		} else if task.receivers.contains(&sender) {
+			(vec![task.issuer.clone()], RatedRole::Issuer)
+		} else {
+			return Err(Error::<T>::TaskParticipantInvalid.into());
+		};
+		<reputation::Module<T>>::rate(hash, sender, ratees, role, rating, review)
+	}
+
 	/// Team leader to deliver tasks
 	pub fn do_claim_deliver_task(leader: T::Origin, hash: T::Hash, delivery_certificate: T::Hash) -> DispatchResult {
 		let sender = ensure_signed(leader)?;
@@ -220,6 +746,7 @@ impl<T: Trait> Module<T> {
 		ensure!(recv.pop() == Some(sender), Error::<T>::PermissionError);
 		task.delivery_certificate = delivery_certificate;
 		Self::change_task_status(&mut task, TaskKind::Deliveryed)?;
+		<DeliveredAt<T>>::insert(&hash, <system::Module<T>>::block_number());
 		Ok(())
 	}
 
@@ -229,8 +756,11 @@ impl<T: Trait> Module<T> {
 		let mut task: Task<T> = Self::query_task_by_hash(hash.clone())?;
 		ensure!(Self::is_task_participant(&task, sender.clone()), Error::<T>::TaskParticipantInvalid);
 		ensure!(task.kind.clone() == TaskKind::Deliveryed, Error::<T>::TaskKindInvalid);
-		<judge_pool::Module<T>>::begin_judgement(hash.clone(), sender.clone(), task.judge_pay)?;
-		Self::change_task_status(&mut task, TaskKind::Arbitration);
+		let mut parties = task.receivers.clone();
+		parties.push(task.issuer.clone());
+		<judge_pool::Module<T>>::begin_judgement(hash.clone(), sender.clone(), task.judge_pay, parties)?;
+		Self::change_task_status(&mut task, TaskKind::Arbitration)?;
+		<DeliveredAt<T>>::remove(&hash);
 		Ok(())
 	}
 
@@ -263,22 +793,205 @@ impl<T: Trait> Module<T> {
 			}
 		}
 	}
+
+	/// Accept an overdue delivery, or close an overdue dispute with the votes cast and finalise the task
+	pub fn do_poke(hash: T::Hash) -> DispatchResult {
+		let mut task = Self::query_task_by_hash(hash.clone())?;
+		ensure!(Self::is_overdue(&task), Error::<T>::DeadlineNotReached);
+		match task.kind.clone() {
+			TaskKind::Deliveryed => {
+				Self::change_task_status(&mut task, TaskKind::Done)?;
+				<DeliveredAt<T>>::remove(&hash);
+				Self::deposit_event(RawEvent::DeliveryAutoAccepted(hash));
+			}
+			_ => {
+				<judge_pool::Module<T>>::close_stalled(&hash)?;
+				let to_task_kind = match <judge_pool::Module<T>>::view_result(hash)? {
+					judge_pool::ResultKind::ResultTrue => TaskKind::Done,
+					judge_pool::ResultKind::ResultFalse => TaskKind::Failure,
+				};
+				Self::change_task_status(&mut task, to_task_kind)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// File an appeal on a task the sender took part in
+	pub fn do_appeal(origin: T::Origin, hash: T::Hash, statement: T::Hash) -> DispatchResult {
+		let sender = ensure_signed(origin)?;
+		let task = Self::query_task_by_hash(hash.clone())?;
+		ensure!(Self::is_task_participant(&task, sender.clone()), Error::<T>::TaskParticipantInvalid);
+		// a judged task is appealed against its dispute, so only while the dispute is kept.
+		let judged = task.is_final() && task.history.iter().any(|t| t.0 == TaskKind::Arbitration)
+			&& <judge_pool::Module<T>>::dispute_kept(&hash);
+		ensure!(task.kind == TaskKind::InDelivery || judged, Error::<T>::AppealInvalid);
+		ensure!(!<Appeals<T>>::exists((hash.clone(), sender.clone())), Error::<T>::AppealDuplicated);
+		let deposit = Self::appeal_deposit();
+		<balances::Module<T> as ReservableCurrency<_>>::reserve(&sender, deposit)?;
+
+		<Appeals<T>>::insert((hash.clone(), sender.clone()), Appeal { statement, resolved: false, compensation: Zero::zero(), deposit });
+		<PendingAppeals>::mutate(|n| *n += 1);
+		<judge_pool::Module<T>>::hold_dispute(&hash);
+		Self::deposit_event(RawEvent::AppealFiled(sender, hash, statement));
+		Ok(())
+	}
+
+	/// Close a pending appeal, paying `compensation` to the appellant out of the insurance pool
+	///
+	/// A rejected appeal, with no compensation, forfeits its deposit to the insurance pool.
+	pub fn do_resolve_appeal(hash: T::Hash, appellant: T::AccountId, compensation: T::Balance) -> DispatchResult {
+		let key = (hash.clone(), appellant.clone());
+		let mut appeal = Self::appeals(&key).filter(|a| !a.resolved).ok_or(Error::<T>::AppealNotFound)?;
+		let mut pool = Self::insurance_pool();
+		ensure!(pool >= compensation, Error::<T>::InsuranceInsufficient);
+		if compensation.is_zero() {
+			let (slashed, _) = <balances::Module<T> as ReservableCurrency<_>>::slash_reserved(&appellant, appeal.deposit);
+			pool = pool.saturating_add(slashed.peek());
+		} else {
+			<balances::Module<T> as Currency<_>>::deposit_into_existing(&appellant, compensation)?;
+			<balances::Module<T> as ReservableCurrency<_>>::unreserve(&appellant, appeal.deposit);
+			pool -= compensation;
+		}
+
+		<InsurancePool<T>>::put(pool);
+		appeal.resolved = true;
+		appeal.compensation = compensation;
+		<Appeals<T>>::insert(&key, appeal);
+		<PendingAppeals>::mutate(|n| *n = n.saturating_sub(1));
+		<judge_pool::Module<T>>::release_dispute(&hash);
+		Self::deposit_event(RawEvent::AppealResolved(hash, appellant, compensation));
+		Ok(())
+	}
 }
 
 impl<T: Trait> Module<T> {
+	/// Build an unpublished task from a template
+	pub fn new_task(issuer: T::AccountId, template: TaskTemplate<T::Balance>) -> Task<T> {
+		let mut task = Task::default();
+		task.description = template.description;
+		task.min_rep = template.min_rep;
+		task.pay = template.pay;
+		task.judge_pay = template.judge_pay;
+		task.issuer = issuer;
+		task.req_subjects = template.req_subjects;
+		task.hash = Self::task_hash(&task);
+		task
+	}
+
+	/// Whether `player` may claim `task`
+	///
+	/// A squad claims with the credentials of any of its members and their mean score.
 	pub fn verify_claim(task: &Task<T>, player: T::AccountId) -> bool {
-		for sub in &task.req_subjects {
-			if !identity::Module::<T>::check_credential(&player, sub) {
-				return false;
-			}
+		let squad = <organisation::Module<T>>::squad_members(&player);
+		let holds = |sub: &u32| if squad.is_empty() {
+			identity::Module::<T>::check_credential(&player, sub)
+		} else {
+			<organisation::Module<T>>::squad_holds(&player, sub)
+		};
+		let score = if squad.is_empty() {
+			reputation::Module::<T>::get_account_reputation_level(&player).score
+		} else {
+			<organisation::Module<T>>::squad_score(&player)
+		};
+		// Nobody claims the tasks of an organisation they belong to, alone or as a squad.
+		let issuer = reputation::Module::<T>::primary(&task.issuer);
+		let independent = |who: &T::AccountId| {
+			reputation::Module::<T>::primary(who) != issuer && !<organisation::Module<T>>::is_member(&task.issuer, who)
+		};
+		task.req_subjects.iter().all(holds)
+			&& score >= task.min_rep
+			&& reputation::Module::<T>::issuer_score(&task.issuer) >= Self::min_issuer_score(&player)
+			&& independent(&player)
+			&& squad.iter().all(independent)
+	}
+
+	/// The `Published` tasks `who` can claim, skipping `offset` and returning at most `limit`
+	///
+	/// Ranked like `all_eligible_tasks`.
+	pub fn eligible_tasks(who: &T::AccountId, offset: u32, limit: u32) -> Vec<EligibleTask<T::Hash, T::Balance, T::Moment, T::BlockNumber>> {
+		Self::all_eligible_tasks(who).into_iter()
+			.skip(offset as usize)
+			.take(limit.min(MAX_ELIGIBLE_PAGE) as usize)
+			.collect()
+	}
+
+	/// Every `Published` task `who` can claim, in a single scan of the board
+	///
+	/// Best paid first, then the ones requiring the most of `who`'s credentials, then the ones
+	/// whose claim deadline comes first. Among equals the longest waiting comes first.
+	pub fn all_eligible_tasks(who: &T::AccountId) -> Vec<EligibleTask<T::Hash, T::Balance, T::Moment, T::BlockNumber>> {
+		let mut tasks: Vec<EligibleTask<T::Hash, T::Balance, T::Moment, T::BlockNumber>> = Self::load_board(TaskKind::Published as u8)
+			.inner_board
+			.into_iter()
+			.filter_map(|hash| Self::query_task_by_hash(hash).ok())
+			.filter(|task| !Self::claim_deadline_passed(&task.hash))
+			.filter(|task| Self::verify_claim(task, who.clone()) && Self::ensure_dependencies(task, &TaskKind::InDelivery).is_ok())
+			.map(|task| EligibleTask {
+				published_at: task.history.iter()
+					.find(|(kind, _)| *kind == TaskKind::Published)
+					.map(|(_, at)| at.clone())
+					.unwrap_or_default(),
+				claim_deadline: Self::claim_deadline(&task.hash),
+				hash: task.hash,
+				pay: task.pay,
+				judge_pay: task.judge_pay,
+				min_rep: task.min_rep,
+				req_subjects: task.req_subjects,
+			})
+			.collect();
+		// Tasks without a deadline wait behind every task that has one.
+		tasks.sort_by(|a, b| b.pay.cmp(&a.pay)
+			.then_with(|| b.req_subjects.len().cmp(&a.req_subjects.len()))
+			.then_with(|| match (a.claim_deadline, b.claim_deadline) {
+				(Some(a), Some(b)) => a.cmp(&b),
+				(Some(_), None) => Ordering::Less,
+				(None, Some(_)) => Ordering::Greater,
+				(None, None) => Ordering::Equal,
+			})
+			.then_with(|| a.published_at.cmp(&b.published_at)));
+		tasks
+	}
+
+	/// Balance of the insurance pool against the pay escrowed in claimed tasks
+	pub fn insurance_solvency() -> InsuranceSolvency<T::Balance> {
+		let exposure = [TaskKind::InDelivery, TaskKind::Deliveryed, TaskKind::Arbitration].iter()
+			.flat_map(|kind| Self::load_board(kind.clone() as u8).inner_board)
+			.filter_map(|hash| Self::query_task_by_hash(hash).ok())
+			.fold(Zero::zero(), |exposure: T::Balance, task| {
+				exposure.saturating_add(Self::issuer_pay_pool((task.issuer, task.hash)))
+			});
+		InsuranceSolvency {
+			pool: Self::insurance_pool(),
+			exposure,
+			pending_appeals: Self::pending_appeals(),
 		}
-		reputation::Module::<T>::get_account_reputation_level(&player).score >= task.min_rep && task.issuer.clone() != player.clone()
 	}
 
 	pub fn is_task_participant(task: &Task<T>, player: T::AccountId) -> bool {
 		task.issuer.clone() == player || task.receivers.contains(&player)
 	}
 
+	/// Block from which a poke advances `task`: the end of its acceptance period while delivered,
+	/// the end of its voting period while in arbitration
+	pub fn deadline(task: &Task<T>) -> Option<T::BlockNumber> {
+		match task.kind.clone() {
+			TaskKind::Deliveryed => {
+				let period = Self::acceptance_period();
+				if period.is_zero() {
+					return None;
+				}
+				Self::delivered_at(&task.hash).map(|delivered| delivered + period)
+			}
+			TaskKind::Arbitration => <judge_pool::Module<T>>::voting_deadline(&task.hash),
+			_ => None,
+		}
+	}
+
+	/// Whether the deadline of `task` has passed
+	pub fn is_overdue(task: &Task<T>) -> bool {
+		Self::deadline(task).map_or(false, |deadline| <system::Module<T>>::block_number() >= deadline)
+	}
+
 	/// Get task hash
 	pub fn task_hash(task: &Task<T>) -> T::Hash {
 		let nonce = <Nonce>::get();
@@ -316,6 +1029,25 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
+	/// Overwrite a stored task without moving any funds
+	fn store_task(task: &Task<T>) {
+		let index = <TaskIndex<T>>::get(task.hash.clone());
+		<Tasks<T>>::insert(index, task);
+	}
+
+	/// Ensure the tasks `task` depends on allow it to move to `to_task_kind`
+	pub fn ensure_dependencies(task: &Task<T>, to_task_kind: &TaskKind) -> DispatchResult {
+		let all_done = |hashes: &Vec<T::Hash>| hashes.iter().all(|h| {
+			Self::query_task_by_hash(h.clone()).map(|t| t.kind == TaskKind::Done).unwrap_or(false)
+		});
+		match to_task_kind {
+			TaskKind::InDelivery => ensure!(all_done(&task.blocked_by), Error::<T>::TaskBlocked),
+			TaskKind::Deliveryed => ensure!(all_done(&task.children), Error::<T>::SubTasksPending),
+			_ => {}
+		}
+		Ok(())
+	}
+
 	/// Task query
 	pub fn query_task_by_hash(hash: T::Hash) -> sp_std::result::Result<Task<T>, Error<T>> {
 		let index = <TaskIndex<T>>::get(hash);
@@ -327,30 +1059,47 @@ impl<T: Trait> Module<T> {
 
 	/// Task state flow
 	pub fn change_task_status(task: &mut Task<T>, to_task_kind: TaskKind) -> DispatchResult {
-		task.kind = to_task_kind.clone();
-		task.history.push((task.kind.clone(), <timestamp::Module<T>>::get()));
+		let from_task_kind = task.kind.clone();
+		let now = <timestamp::Module<T>>::get();
 
 		match to_task_kind {
 			TaskKind::Published => {
 				ensure!( ! ( < TaskIndex < T >>::exists(task.hash.clone())), Error::< T >::TaskDuplicated);
+				task.kind = to_task_kind.clone();
+				task.history.push((task.kind.clone(), now.clone()));
 				Self::save_task(&task)?;
-				Self::deposit_event(RawEvent::TaskPublish(task.issuer.clone(), task.hash.clone(), <timestamp::Module<T>>::get()));
+
+				let mut bm_to = <BoardManager<T>>::get(to_task_kind.clone() as u8);
+				ensure!( ! bm_to.inner_board.contains( & task.hash), Error::< T >::TaskInWrongBoard);
+				bm_to.task_kind = to_task_kind.clone();
+				bm_to.inner_board.push(task.hash.clone());
+				<BoardManager<T>>::insert(to_task_kind.clone() as u8, bm_to);
+
+				Self::deposit_event(RawEvent::TaskPublish(task.issuer.clone(), task.hash.clone(), now));
 			}
 			_ => {
-				ensure!((task.kind.clone() as u32) < (to_task_kind.clone() as u32), Error::< T >::TaskChangeStatusFail);
-				let mut bm_form = <BoardManager<T>>::get(task.kind.clone() as u8);
+				ensure!((from_task_kind.clone() as u32) < (to_task_kind.clone() as u32), Error::< T >::TaskChangeStatusFail);
+				Self::ensure_dependencies(task, &to_task_kind)?;
+				let mut bm_form = <BoardManager<T>>::get(from_task_kind.clone() as u8);
 				let mut bm_to = <BoardManager<T>>::get(to_task_kind.clone() as u8);
 				ensure!(bm_form.inner_board.contains( &task.hash), Error::< T >::TaskNotInBoard);
 				ensure!( ! bm_to.inner_board.contains( & task.hash), Error::< T >::TaskInWrongBoard);
 
+				task.kind = to_task_kind.clone();
+				task.history.push((task.kind.clone(), now.clone()));
 				Self::save_task(&task)?;
-				bm_form.inner_board.remove_item(&task.hash);
+
+				bm_form.inner_board.retain(|h| h != &task.hash);
+				if from_task_kind == TaskKind::Published {
+					<ClaimDeadline<T>>::remove(&task.hash);
+				}
+				bm_to.task_kind = to_task_kind.clone();
 				bm_to.inner_board.push(task.hash.clone());
 
-				<BoardManager<T>>::insert(bm_form.task_kind.clone() as u8, bm_form);
-				<BoardManager<T>>::insert(bm_to.task_kind.clone() as u8, bm_to);
+				<BoardManager<T>>::insert(from_task_kind.clone() as u8, bm_form);
+				<BoardManager<T>>::insert(to_task_kind.clone() as u8, bm_to);
 
-				Self::deposit_event(RawEvent::TaskChangeState(task.hash.clone(), task.kind.clone() as u8, to_task_kind.clone() as u8, <timestamp::Module<T>>::get()));
+				Self::deposit_event(RawEvent::TaskChangeState(task.hash.clone(), from_task_kind as u8, to_task_kind as u8, now));
 			}
 		}
 		Ok(())
@@ -369,6 +1118,12 @@ impl<T: Trait> Module<T> {
 				ensure!(task.kind.clone() == TaskKind::Done, Error::<T>::FundsRecvRewardWrongTime);
 				ensure!(task.receivers.len() > 0, Error::<T>::TaskRecvEmpty);
 				let mut pay = <IssuerPayPool<T>>::get((task.issuer.clone(), task.hash.clone()));
+				let fee = Self::protocol_fee() * pay;
+				if !fee.is_zero() {
+					pay -= fee;
+					T::ProtocolFeeDestination::on_unbalanced(<balances::Module<T> as Currency<_>>::issue(fee));
+					Self::deposit_event(RawEvent::ProtocolFeePaid(task.hash.clone(), fee));
+				}
 				let each = pay.div(T::Balance::from(task.receivers.len() as u32));
 				task.receivers.iter().enumerate().map(|(i, r)| {
 					let mut _each = each;
@@ -379,6 +1134,12 @@ impl<T: Trait> Module<T> {
 					pay -= _each;
 				}).count();
 				<IssuerPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), T::Balance::from(0_u32));
+				// A delivery accepted without a dispute never spent the judge pay.
+				if task.history.iter().all(|t| t.0.clone() != TaskKind::Arbitration) {
+					Self::issuer_back_judge(task)?;
+					Self::receivers_back_judge(task)?;
+				}
+				Self::settle_reputation(task, ReputationOp::CompleteIncrease)?;
 			}
 			FundsExchange::IssuerBack => {
 				ensure!(task.kind.clone() == TaskKind::Failure, Error::<T>::FundsIssuserBackWrongTime);
@@ -388,19 +1149,184 @@ impl<T: Trait> Module<T> {
 				}).count() == 0 {
 					Self::issuer_back_judge(task)?;
 				}
+				Self::settle_reputation(task, ReputationOp::FailedReduce)?;
 			}
 			_ => {}
 		}
 		Ok(())
 	}
+
+	/// Move the score of the receivers of a settled task, once per score however many of
+	/// the accounts sharing it took part
+	fn settle_reputation(task: &Task<T>, op: ReputationOp) -> DispatchResult {
+		let mut scored: Vec<T::AccountId> = task.receivers.iter().map(reputation::Module::<T>::primary).collect();
+		scored.sort();
+		scored.dedup();
+		for account in scored {
+			<reputation::Module<T>>::reputation_change(account, op)?;
+		}
+		Ok(())
+	}
+}
+
+// migration
+impl<T: Trait> Module<T> {
+	/// Ensures storage is upgraded to the layout expected by `STORAGE_VERSION`.
+	fn ensure_storage_upgraded() {
+		<StorageVersion>::mutate(|version| {
+			if *version >= STORAGE_VERSION { return }
+			Self::migrate_to_v1(version);
+			Self::migrate_to_v2(version);
+		});
+	}
+
+	// migrate storage from v0 to v1.
+	//
+	// this re-encodes every `Tasks` entry and rebuilds `TaskIndex` and `BoardManager` from them.
+	// The v0 state machine never put published tasks on a board and filed later moves under the
+	// wrong kind, so the boards of a v0 chain cannot be trusted.
+	fn migrate_to_v1(version: &mut VersionNumber) {
+		if *version != 0 { return }
+		*version += 1;
+
+		<BoardManager<T>>::remove_all();
+		let mut boards: Vec<Board<T::Hash>> = Vec::new();
+		for index in 0..<TaskCount>::get() {
+			let key = <Tasks<T>>::hashed_key_for(index);
+			if let Some(task) = migration::translate_value::<TaskV1<T>, TaskV1<T>, _>(&key, |task| task) {
+				<TaskIndex<T>>::insert(task.hash.clone(), index);
+				let kind = task.kind.clone() as usize;
+				while boards.len() <= kind {
+					boards.push(Board::default());
+				}
+				boards[kind].task_kind = task.kind.clone();
+				boards[kind].inner_board.push(task.hash.clone());
+			}
+		}
+		for board in boards.into_iter().filter(|b| !b.inner_board.is_empty()) {
+			<BoardManager<T>>::insert(board.task_kind.clone() as u8, board);
+		}
+
+		frame_support::print("Finished migrating DeWorkTasks storage to v1.");
+	}
+
+	// migrate storage from v1 to v2.
+	//
+	// this adds the (empty) sub-task and dependency links to every `Tasks` entry.
+	fn migrate_to_v2(version: &mut VersionNumber) {
+		if *version != 1 { return }
+		*version += 1;
+
+		for index in 0..<TaskCount>::get() {
+			let key = <Tasks<T>>::hashed_key_for(index);
+			migration::translate_value::<TaskV1<T>, Task<T>, _>(&key, Task::from);
+		}
+
+		frame_support::print("Finished migrating DeWorkTasks storage to v2.");
+	}
+}
+
+// recurring
+impl<T: Trait> Module<T> {
+	/// Publish the schedules due at block `n` and queue their next publication.
+	///
+	/// At most `MaxScheduledPerBlock` schedules are handled; the rest are moved to the front
+	/// of the next block's queue. A schedule that cannot cover the next task ends and its
+	/// remainder is unreserved. Returns the weight consumed.
+	fn publish_scheduled(n: T::BlockNumber) -> Weight {
+		let mut due = <ScheduleQueue<T>>::take(n);
+		let limit = T::MaxScheduledPerBlock::get() as usize;
+		if due.len() > limit {
+			let carried = due.split_off(limit);
+			let later = n + One::one();
+			for (issuer, id) in &carried {
+				<Schedules<T>>::mutate((issuer.clone(), *id), |schedule| match schedule {
+					Some(schedule) if schedule.next == n => schedule.next = later,
+					_ => {}
+				});
+			}
+			<ScheduleQueue<T>>::mutate(later, |queue| {
+				let queued = sp_std::mem::replace(queue, carried);
+				queue.extend(queued);
+			});
+		}
+		let weight = SCHEDULE_PUBLISH_WEIGHT.saturating_mul(due.len() as Weight);
+
+		for (issuer, id) in due {
+			let (mut schedule, template) = match (Self::schedules((issuer.clone(), id)), Self::templates((issuer.clone(), id))) {
+				(Some(schedule), Some(template)) if schedule.next == n => (schedule, template),
+				_ => continue,
+			};
+			let cost = match Self::publish_cost(&template) {
+				Some(cost) if cost <= schedule.funds => cost,
+				_ => {
+					Self::end_schedule(&issuer, id);
+					continue;
+				}
+			};
+
+			let missing = <balances::Module<T> as ReservableCurrency<_>>::unreserve(&issuer, cost);
+			if !missing.is_zero() || Self::publish(issuer.clone(), template).is_err() {
+				let _ = <balances::Module<T> as ReservableCurrency<_>>::reserve(&issuer, cost - missing);
+				Self::end_schedule(&issuer, id);
+				continue;
+			}
+
+			schedule.funds -= cost;
+			schedule.next = n + schedule.period;
+			<ScheduleQueue<T>>::mutate(schedule.next, |due| due.push((issuer.clone(), id)));
+			<Schedules<T>>::insert((issuer, id), schedule);
+		}
+
+		weight
+	}
+
+	/// Remove the schedule of a template, unreserving whatever it still holds.
+	fn end_schedule(issuer: &T::AccountId, id: u32) {
+		if let Some(schedule) = <Schedules<T>>::take((issuer.clone(), id)) {
+			let missing = <balances::Module<T> as ReservableCurrency<_>>::unreserve(issuer, schedule.funds);
+			Self::deposit_event(RawEvent::ScheduleEnded(issuer.clone(), id, schedule.funds - missing));
+		}
+	}
 }
 
 // pay
 impl<T: Trait> Module<T> {
+	/// What publishing `template` withdraws from the issuer: pay, judge pay and insurance premium
+	pub fn publish_cost(template: &TaskTemplate<T::Balance>) -> Option<T::Balance> {
+		template.pay.checked_add(&template.judge_pay)?.checked_add(&(Self::insurance_premium() * template.pay))
+	}
+
+	/// Escrow the pay and judge pay of a new task and add its premium to the insurance pool
+	///
+	/// Sub-tasks are covered by the premium of their parent.
 	pub fn issuer_pay(task: &Task<T>) -> DispatchResult {
-		<balances::Module<T> as Currency<_>>::withdraw(&task.issuer, task.pay, WithdrawReasons::all(), ExistenceRequirement::KeepAlive)?;
+		if let Some(parent) = task.parent.clone() {
+			return Self::parent_pay(task, parent);
+		}
+		// Withdraw everything in one go so a failure cannot leave half of it escrowed.
+		let premium = Self::insurance_premium() * task.pay;
+		let total = task.pay.checked_add(&task.judge_pay)
+			.and_then(|total| total.checked_add(&premium))
+			.ok_or(Error::<T>::TaskCheckAddFail)?;
+		<balances::Module<T> as Currency<_>>::withdraw(&task.issuer, total, WithdrawReasons::all(), ExistenceRequirement::KeepAlive)?;
 		<IssuerPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), task.pay.clone());
+		<StakingPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), task.judge_pay.clone());
+		if !premium.is_zero() {
+			<InsurancePool<T>>::mutate(|pool| *pool = pool.saturating_add(premium));
+			Self::deposit_event(RawEvent::InsurancePremiumPaid(task.hash.clone(), premium));
+		}
+		Ok(())
+	}
+
+	/// Carve the pay of a sub-task out of its parent's escrow; the judge pay is still the issuer's.
+	pub fn parent_pay(task: &Task<T>, parent: T::Hash) -> DispatchResult {
+		let parent_key = (task.issuer.clone(), parent);
+		let remaining = <IssuerPayPool<T>>::get(&parent_key);
+		ensure!(remaining >= task.pay, Error::<T>::ParentFundsInsufficient);
 		<balances::Module<T> as Currency<_>>::withdraw(&task.issuer, task.judge_pay, WithdrawReasons::all(), ExistenceRequirement::KeepAlive)?;
+		<IssuerPayPool<T>>::insert(&parent_key, remaining - task.pay);
+		<IssuerPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), task.pay.clone());
 		<StakingPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), task.judge_pay.clone());
 		Ok(())
 	}
@@ -409,7 +1335,7 @@ impl<T: Trait> Module<T> {
 		for r in &task.receivers{
 			if !<StakingPayPool<T>>::exists((r, task.hash.clone())) {
 				<balances::Module<T> as Currency<_>>::withdraw(r, task.judge_pay, WithdrawReasons::all(), ExistenceRequirement::KeepAlive)?;
-				<IssuerPayPool<T>>::insert((r, task.hash.clone()), task.judge_pay.clone());
+				<StakingPayPool<T>>::insert((r, task.hash.clone()), task.judge_pay.clone());
 			}
 		}
 		Ok(())
@@ -418,7 +1344,20 @@ impl<T: Trait> Module<T> {
 
 	pub fn issuer_back_pay(task: &Task<T>) -> DispatchResult {
 		let pay = <IssuerPayPool<T>>::get((&task.issuer, task.hash.clone()));
-		<balances::Module<T> as Currency<_>>::deposit_into_existing(&task.issuer, pay)?;
+		let parent = task.parent.clone()
+			.and_then(|p| Self::query_task_by_hash(p).ok())
+			.filter(|p| !p.is_final());
+		match parent {
+			// A failed sub-task rolls its pay back up and no longer holds up its parent.
+			Some(mut parent) => {
+				<IssuerPayPool<T>>::mutate((task.issuer.clone(), parent.hash.clone()), |p| *p += pay);
+				parent.children.retain(|c| c != &task.hash);
+				Self::store_task(&parent);
+			}
+			None => {
+				<balances::Module<T> as Currency<_>>::deposit_into_existing(&task.issuer, pay)?;
+			}
+		}
 		<IssuerPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), T::Balance::from(0_u32));
 		Ok(())
 	}
@@ -429,4 +1368,119 @@ impl<T: Trait> Module<T> {
 		<StakingPayPool<T>>::insert((task.issuer.clone(), task.hash.clone()), T::Balance::from(0_u32));
 		Ok(())
 	}
+
+	pub fn receivers_back_judge(task: &Task<T>) -> DispatchResult {
+		for r in &task.receivers {
+			let judge_pay = <StakingPayPool<T>>::get((r, task.hash.clone()));
+			<balances::Module<T> as Currency<_>>::deposit_into_existing(r, judge_pay)?;
+			<StakingPayPool<T>>::insert((r.clone(), task.hash.clone()), T::Balance::from(0_u32));
+		}
+		Ok(())
+	}
+}
+
+// offchain
+impl<T: Trait> Module<T> {
+	/// Poke every overdue task and tell the local webhook about deadlines coming up.
+	///
+	/// Notifications are only sent when `WEBHOOK_KEY` holds a URL in the persistent offchain storage.
+	fn scan_deadlines(now: T::BlockNumber) {
+		let notice = Self::deadline_notice();
+		let webhook = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, WEBHOOK_KEY);
+		let webhook = webhook.as_ref().and_then(|url| sp_std::str::from_utf8(url).ok());
+		for kind in &[TaskKind::Deliveryed, TaskKind::Arbitration] {
+			for hash in Self::load_board(kind.clone() as u8).inner_board {
+				let task = match Self::query_task_by_hash(hash.clone()) {
+					Ok(task) => task,
+					Err(_) => continue,
+				};
+				let deadline = match Self::deadline(&task) {
+					Some(deadline) => deadline,
+					None => continue,
+				};
+				if now >= deadline && T::SubmitTransaction::submit_unsigned(Call::poke(hash.clone())).is_err() {
+					debug::warn!("Failed to submit a poke for task {:?}", hash);
+				}
+				if let Some(url) = webhook {
+					if now + notice >= deadline {
+						Self::notify(url, &task, deadline);
+					}
+				}
+			}
+		}
+	}
+
+	/// Post a notification about `task` to `url`, once for every deadline it gets.
+	fn notify(url: &str, task: &Task<T>, deadline: T::BlockNumber) {
+		let mut key = NOTIFIED_PREFIX.to_vec();
+		key.extend_from_slice(task.hash.as_ref());
+		let marker = deadline.encode();
+		if sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key).as_ref() == Some(&marker) {
+			return;
+		}
+
+		let timeout = sp_io::offchain::timestamp().add(Duration::from_millis(WEBHOOK_TIMEOUT_MS));
+		let delivered = http::Request::post(url, vec![Self::notification(task, deadline)])
+			.add_header("Content-Type", "application/json")
+			.deadline(timeout)
+			.send()
+			.ok()
+			.and_then(|pending| pending.try_wait(timeout).ok())
+			.and_then(|response| response.ok())
+			.map_or(false, |response| response.code >= 200 && response.code < 300);
+		if delivered {
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &key, &marker);
+		} else {
+			debug::warn!("Webhook did not accept the notification for task {:?}", task.hash);
+		}
+	}
+
+	/// JSON body of a notification: `{"kind":..,"task":"0x..","deadline":..}`.
+	fn notification(task: &Task<T>, deadline: T::BlockNumber) -> Vec<u8> {
+		const HEX: &[u8; 16] = b"0123456789abcdef";
+		let kind: &[u8] = match task.kind.clone() {
+			TaskKind::Deliveryed => b"acceptance",
+			_ => b"dispute",
+		};
+		let mut body = b"{\"kind\":\"".to_vec();
+		body.extend_from_slice(kind);
+		body.extend_from_slice(b"\",\"task\":\"0x");
+		for byte in task.hash.as_ref() {
+			body.push(HEX[(byte >> 4) as usize]);
+			body.push(HEX[(byte & 0xf) as usize]);
+		}
+		body.extend_from_slice(b"\",\"deadline\":");
+		let mut digits = Vec::new();
+		let mut n = deadline.saturated_into::<u64>();
+		loop {
+			digits.push(b'0' + (n % 10) as u8);
+			n /= 10;
+			if n == 0 { break }
+		}
+		body.extend(digits.into_iter().rev());
+		body.push(b'}');
+		body
+	}
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+		if let Call::poke(hash) = call {
+			let task = Self::query_task_by_hash(hash.clone()).map_err(|_| InvalidTransaction::Call)?;
+			if !Self::is_overdue(&task) {
+				return InvalidTransaction::Stale.into();
+			}
+			Ok(ValidTransaction {
+				priority: TransactionPriority::max_value(),
+				requires: vec![],
+				provides: vec![(b"dework_poke", hash).encode()],
+				longevity: POKE_LONGEVITY,
+				propagate: true,
+			})
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
 }
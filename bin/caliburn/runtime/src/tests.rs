@@ -0,0 +1,1889 @@
+//! Tests for the DeWork pallets.
+
+use codec::{Decode, Encode};
+use frame_support::{assert_err, assert_noop, assert_ok, storage::unhashed, traits::Get, unsigned::ValidateUnsigned};
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use sp_core::{H256, hashing::{blake2_256, twox_128}};
+use sp_core::offchain::{OffchainExt, TransactionPoolExt, testing::{TestOffchainExt, TestTransactionPoolExt}};
+use sp_runtime::{Permill, traits::{BadOrigin, Dispatchable, OffchainWorker, OnInitialize, SignedExtension}};
+use sp_runtime::transaction_validity::InvalidTransaction;
+
+use crate::mock::*;
+use crate::dework::{Call as DeWorkCall, CheckLegacyCall};
+use crate::task_board::{Call as BoardCall, Error as BoardError, TaskKind, TemplateOverrides};
+use crate::judge_pool::{Error as JudgeError, JudgeKind};
+use crate::reputation::{BootstrapProof, Error as RepError, RatedRole, RatingSummary, ReputationOp};
+use crate::organisation::{Error as OrgError, Roles};
+
+const PAY: u64 = 100;
+const JUDGE_PAY: u64 = 10;
+/// Rationale hash attached to every vote.
+const RATIONALE: H256 = H256([0xaa; 32]);
+
+/// Every account that takes part in the tests.
+const ACCOUNTS: [u64; 8] = [ISSUER, LEADER, MEMBER, 4, 5, JUDGES[0], JUDGES[1], JUDGES[2]];
+
+fn total_endowment() -> u64 {
+	ENDOWMENT * ACCOUNTS.len() as u64
+}
+
+/// Publish a task from `issuer` and return its hash.
+fn publish(issuer: u64, pay: u64, judge_pay: u64, min_rep: u32, subjects: Vec<u32>) -> H256 {
+	assert_ok!(TaskBoard::publish_task(Origin::signed(issuer), b"audit".to_vec(), min_rep, pay, judge_pay, subjects));
+	*TaskBoard::load_board(TaskKind::Published as u8).inner_board.last().unwrap()
+}
+
+/// Publish, claim and deliver a task, returning its hash.
+fn delivered_task() -> H256 {
+	let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+	assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+	assert_ok!(TaskBoard::claim_deliver_task(Origin::signed(LEADER), hash, H256::repeat_byte(7)));
+	hash
+}
+
+fn vote_all(hash: H256, votes: [u32; 3]) {
+	for (judge, vote) in JUDGES.iter().zip(votes.iter()) {
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(*judge), hash, *vote, RATIONALE));
+	}
+}
+
+fn task_kind(hash: H256) -> TaskKind {
+	TaskBoard::query_task_by_hash(hash).unwrap().kind
+}
+
+fn assert_funds_conserved(hashes: &[H256]) {
+	let escrow: u64 = hashes.iter().map(|h| escrowed(*h, &ACCOUNTS)).sum();
+	assert_eq!(total_balances(&ACCOUNTS) + escrow + TaskBoard::insurance_pool(), total_endowment());
+}
+
+/// Every stored task sits on exactly the board matching its kind, and its history only moves forward.
+fn assert_boards_consistent() {
+	let boards = [
+		TaskKind::Published,
+		TaskKind::InDelivery,
+		TaskKind::Deliveryed,
+		TaskKind::Arbitration,
+		TaskKind::Failure,
+		TaskKind::Done,
+	];
+	for index in 0..TaskBoard::task_count() {
+		let task = TaskBoard::tasks(index);
+		for kind in boards.iter() {
+			let on_board = TaskBoard::load_board(kind.clone() as u8).inner_board.contains(&task.hash);
+			assert_eq!(on_board, *kind == task.kind, "task {:?} on board {:?}", task.hash, kind);
+		}
+		assert!(task.history.windows(2).all(|w| (w[0].0.clone() as u8) < (w[1].0.clone() as u8)));
+		assert_eq!(task.history.last().map(|h| h.0.clone()), Some(task.kind.clone()));
+	}
+}
+
+#[test]
+fn publish_escrows_pay_and_judge_pay() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - JUDGE_PAY);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, hash)), PAY);
+		assert_eq!(TaskBoard::staking_pay_pool((ISSUER, hash)), JUDGE_PAY);
+		assert_eq!(task_kind(hash), TaskKind::Published);
+		assert_eq!(TaskBoard::task_count(), 1);
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn publish_without_funds_escrows_nothing() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert!(TaskBoard::publish_task(Origin::signed(ISSUER), vec![], 0, ENDOWMENT, JUDGE_PAY, vec![]).is_err());
+
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT);
+		assert_eq!(TaskBoard::task_count(), 0);
+		assert!(TaskBoard::load_board(TaskKind::Published as u8).inner_board.is_empty());
+	});
+}
+
+#[test]
+fn claim_requires_credentials_and_reputation() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Identity::do_create_subject(Origin::signed(4)));
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![0]);
+
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+
+		assert_ok!(Identity::do_issue(Origin::signed(4), LEADER, 0));
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+
+		assert_ok!(Identity::do_issue(Origin::signed(4), MEMBER, 0));
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+		assert_eq!(task_kind(hash), TaskKind::InDelivery);
+
+		// A fresh account has no score and cannot claim a task asking for one.
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 1, vec![]);
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(5), hash, vec![]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+	});
+}
+
+#[test]
+fn genesis_seeds_subjects_credentials_reputation_and_tasks() {
+	ExtBuilder::default()
+		.subject(0, 4)
+		.credential(LEADER, 0)
+		.score(LEADER, 80)
+		.task((ISSUER, b"genesis".to_vec(), 60, PAY, JUDGE_PAY, vec![0]))
+		.build()
+		.execute_with(|| {
+			assert_eq!(JudgePool::judge_size(), 3);
+			assert_eq!(Identity::subject_count(), 1);
+			assert_eq!(Identity::subject_details(0).issuer, 4);
+			assert!(Identity::check_credential(&LEADER, &0));
+			assert_eq!(Reputation::get_account_reputation_level(&LEADER).score, 80);
+
+			assert_eq!(TaskBoard::task_count(), 1);
+			let hash = TaskBoard::tasks(0).hash;
+			assert_eq!(task_kind(hash), TaskKind::Published);
+			assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - JUDGE_PAY);
+			assert_boards_consistent();
+			assert_funds_conserved(&[hash]);
+
+			// Genesis storage is current, so the upgrade hooks leave the seeded task alone.
+			assert_eq!(TaskBoard::storage_version(), crate::task_board::STORAGE_VERSION);
+			run_upgrade_hooks();
+			assert_eq!(TaskBoard::tasks(0).description, b"genesis".to_vec());
+
+			assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![]));
+			assert_eq!(task_kind(hash), TaskKind::InDelivery);
+		});
+}
+
+#[test]
+fn issuer_cannot_claim_own_task() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_err!(
+			TaskBoard::claim_task(Origin::signed(ISSUER), hash, vec![]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![LEADER]),
+			BoardError::<Test>::TaskTeamLeaderRepeatSetting
+		);
+	});
+}
+
+#[test]
+fn claim_stakes_judge_pay_from_every_receiver() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+
+		for who in [LEADER, MEMBER].iter() {
+			assert_eq!(Balances::free_balance(who), ENDOWMENT - JUDGE_PAY);
+			assert_eq!(TaskBoard::staking_pay_pool((*who, hash)), JUDGE_PAY);
+			assert_eq!(TaskBoard::issuer_pay_pool((*who, hash)), 0);
+		}
+		assert_eq!(TaskBoard::query_task_by_hash(hash).unwrap().receivers, vec![MEMBER, LEADER]);
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn claimed_task_cannot_be_claimed_again() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![]));
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(MEMBER), hash, vec![]),
+			BoardError::<Test>::TaskNotWaitForRecv
+		);
+	});
+}
+
+#[test]
+fn only_team_leader_can_deliver() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_noop!(
+			TaskBoard::claim_deliver_task(Origin::signed(LEADER), hash, H256::repeat_byte(7)),
+			BoardError::<Test>::TaskKindInvalid
+		);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+		assert_noop!(
+			TaskBoard::claim_deliver_task(Origin::signed(MEMBER), hash, H256::repeat_byte(7)),
+			BoardError::<Test>::PermissionError
+		);
+		assert_ok!(TaskBoard::claim_deliver_task(Origin::signed(LEADER), hash, H256::repeat_byte(7)));
+
+		let task = TaskBoard::query_task_by_hash(hash).unwrap();
+		assert_eq!(task.kind, TaskKind::Deliveryed);
+		assert_eq!(task.delivery_certificate, H256::repeat_byte(7));
+		assert_boards_consistent();
+	});
+}
+
+#[test]
+fn only_participants_can_request_judgement() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		assert_noop!(
+			TaskBoard::request_for_judge(Origin::signed(4), hash),
+			BoardError::<Test>::TaskParticipantInvalid
+		);
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_eq!(task_kind(hash), TaskKind::Arbitration);
+		assert_eq!(JudgePool::view_progress(hash), JudgeKind::Processing);
+	});
+}
+
+#[test]
+fn approved_task_pays_receivers() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+
+		vote_all(hash, [1, 1, 0]);
+		for judge in JUDGES.iter() {
+			assert_eq!(Balances::free_balance(judge), ENDOWMENT - JUDGE_PAY);
+			assert_eq!(JudgePool::pledge_pool((*judge, hash)), JUDGE_PAY);
+		}
+		assert_eq!(JudgePool::view_progress(hash), JudgeKind::Done);
+
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(ISSUER), hash));
+		assert_eq!(task_kind(hash), TaskKind::Done);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, hash)), 0);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + PAY / 2);
+		assert_eq!(Balances::free_balance(&MEMBER), ENDOWMENT - JUDGE_PAY + PAY / 2);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - JUDGE_PAY);
+
+		// Finalising again is a no-op.
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + PAY / 2);
+
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn rejected_task_refunds_issuer() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		vote_all(hash, [0, 0, 1]);
+
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+		assert_eq!(task_kind(hash), TaskKind::Failure);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, hash)), 0);
+		// The judge pay stays escrowed because the task went through arbitration.
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - JUDGE_PAY);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY);
+
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn settled_tasks_move_the_receivers_reputation() {
+	ExtBuilder::default()
+		.score(LEADER, 80)
+		.score(MEMBER, 50)
+		.build()
+		.execute_with(|| {
+			let score = |who| Reputation::get_account_reputation_level(&who).score;
+
+			let done = delivered_task();
+			assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), done));
+			vote_all(done, [1, 1, 0]);
+			assert_ok!(TaskBoard::task_to_final(Origin::signed(ISSUER), done));
+			assert_eq!((score(LEADER), score(MEMBER)), (85, 55));
+			// Finalising again moves nothing.
+			assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), done));
+			assert_eq!((score(LEADER), score(MEMBER)), (85, 55));
+
+			let failed = delivered_task();
+			assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), failed));
+			vote_all(failed, [0, 0, 1]);
+			assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), failed));
+			assert_eq!((score(LEADER), score(MEMBER)), (80, 50));
+			// The issuer took no part as a receiver.
+			assert_eq!(score(ISSUER), 0);
+		});
+}
+
+#[test]
+fn odd_uneven_pay_goes_to_team_leader() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = publish(ISSUER, 101, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+		assert_ok!(TaskBoard::claim_deliver_task(Origin::signed(LEADER), hash, H256::repeat_byte(7)));
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+		vote_all(hash, [1, 1, 1]);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+
+		assert_eq!(Balances::free_balance(&MEMBER), ENDOWMENT - JUDGE_PAY + 50);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + 51);
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn cannot_finalise_before_judgement_is_done() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		assert_noop!(
+			TaskBoard::task_to_final(Origin::signed(ISSUER), hash),
+			BoardError::<Test>::TaskProcessing
+		);
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+		assert_noop!(
+			TaskBoard::task_to_final(Origin::signed(ISSUER), hash),
+			JudgeError::<Test>::JudgeProcessing
+		);
+		assert_eq!(task_kind(hash), TaskKind::Arbitration);
+	});
+}
+
+#[test]
+fn judge_panel_is_bounded_and_unique() {
+	ExtBuilder::default().judge_size(2).build().execute_with(|| {
+		let hash = delivered_task();
+		assert_err!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeKindInvalid
+		);
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeVerifyFaild
+		);
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[1]), hash, 1, RATIONALE));
+		assert_err!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[2]), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeKindInvalid
+		);
+		assert_eq!(Balances::free_balance(&JUDGES[2]), ENDOWMENT);
+	});
+}
+
+#[test]
+fn judges_below_threshold_are_rejected() {
+	ExtBuilder::default().threshold(51).build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeVerifyFaild
+		);
+		assert_eq!(Balances::free_balance(&JUDGES[0]), ENDOWMENT);
+	});
+}
+
+#[test]
+fn votes_require_a_rationale() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, H256::zero()),
+			JudgeError::<Test>::RationaleMissing
+		);
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+		assert_eq!(JudgePool::rationale((hash, JUDGES[0])), Some(RATIONALE));
+		assert_eq!(JudgePool::rationale((hash, JUDGES[1])), None);
+	});
+}
+
+#[test]
+fn parties_submit_evidence_against_a_deposit_while_processing() {
+	ExtBuilder::default().evidence_deposit(5).build().execute_with(|| {
+		let hash = delivered_task();
+		let content = H256::repeat_byte(0xee);
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(ISSUER), hash, content, vec![]),
+			JudgeError::<Test>::JudgeKindInvalid
+		);
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+
+		assert_ok!(JudgePool::submit_evidence(Origin::signed(ISSUER), hash, content, b"late".to_vec()));
+		assert_ok!(JudgePool::submit_evidence(Origin::signed(MEMBER), hash, content, vec![]));
+		assert_eq!(Balances::reserved_balance(&ISSUER), 5);
+		assert_eq!(JudgePool::evidences(hash).len(), 2);
+		assert_eq!(JudgePool::evidences(hash)[0].statement, b"late".to_vec());
+
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(4), hash, content, vec![]),
+			JudgeError::<Test>::EvidencePartyInvalid
+		);
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(JUDGES[0]), hash, content, vec![]),
+			JudgeError::<Test>::EvidencePartyInvalid
+		);
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(LEADER), hash, content, vec![0; 17]),
+			JudgeError::<Test>::EvidenceStatementTooLong
+		);
+		assert_ok!(JudgePool::submit_evidence(Origin::signed(ISSUER), hash, content, vec![]));
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(ISSUER), hash, content, vec![]),
+			JudgeError::<Test>::EvidenceLimitReached
+		);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 10);
+
+		vote_all(hash, [1, 1, 1]);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		assert_eq!(Balances::reserved_balance(&MEMBER), 0);
+		// The dispute is kept for appeals.
+		assert_eq!(JudgePool::evidences(hash).len(), 3);
+		assert_eq!(JudgePool::rationale((hash, JUDGES[0])), Some(RATIONALE));
+		assert_noop!(
+			JudgePool::submit_evidence(Origin::signed(LEADER), hash, content, vec![]),
+			JudgeError::<Test>::JudgeKindInvalid
+		);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn evidence_limits_are_set_by_the_parameter_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(JudgePool::set_evidence_limits(Origin::signed(ISSUER), 1, 1, 1), BadOrigin);
+		assert_ok!(JudgePool::set_evidence_limits(Origin::ROOT, 3, 1, 4));
+		assert_eq!(JudgePool::evidence_deposit(), 3);
+		assert_eq!(JudgePool::max_evidence(), 1);
+		assert_eq!(JudgePool::max_statement_len(), 4);
+	});
+}
+
+#[test]
+fn scores_are_bootstrapped_only_through_a_verified_identity_or_a_bond() {
+	ExtBuilder::default().subject(0, ISSUER).subject(1, ISSUER).build().execute_with(|| {
+		assert_eq!(Reputation::get_account_reputation_level(&LEADER).score, 0);
+		assert_noop!(
+			Reputation::bootstrap(Origin::signed(LEADER), BootstrapProof::Credential(0)),
+			RepError::<Test>::BootstrapProofInvalid
+		);
+		// Subject 1 is not trusted to bootstrap scores.
+		assert_ok!(Identity::do_issue(Origin::signed(ISSUER), LEADER, 1));
+		assert_noop!(
+			Reputation::bootstrap(Origin::signed(LEADER), BootstrapProof::Credential(1)),
+			RepError::<Test>::BootstrapProofInvalid
+		);
+		assert_ok!(Identity::do_issue(Origin::signed(ISSUER), LEADER, 0));
+		assert_ok!(Reputation::bootstrap(Origin::signed(LEADER), BootstrapProof::Credential(0)));
+		assert_eq!(Reputation::get_account_reputation_level(&LEADER).score, 50);
+		assert_noop!(
+			Reputation::bootstrap(Origin::signed(LEADER), BootstrapProof::Bond),
+			RepError::<Test>::AlreadyBootstrapped
+		);
+
+		assert_noop!(
+			Reputation::bootstrap(Origin::signed(MEMBER), BootstrapProof::Kyc),
+			RepError::<Test>::BootstrapProofInvalid
+		);
+		assert_noop!(Identity::do_attest_kyc(Origin::signed(LEADER), MEMBER, true), "Unauthorized.");
+		assert_ok!(Identity::do_attest_kyc(Origin::signed(ISSUER), MEMBER, true));
+		assert_ok!(Reputation::bootstrap(Origin::signed(MEMBER), BootstrapProof::Kyc));
+		assert_eq!(Reputation::get_account_reputation_level(&MEMBER).score, 50);
+
+		assert_ok!(Reputation::bootstrap(Origin::signed(4), BootstrapProof::Bond));
+		assert_eq!(Balances::reserved_balance(&4), BOND);
+		assert_noop!(Reputation::unbond(Origin::signed(LEADER)), RepError::<Test>::NotBonded);
+		assert_ok!(Reputation::reputation_change(4, ReputationOp::FailedReduce));
+		assert_noop!(Reputation::unbond(Origin::signed(4)), RepError::<Test>::ScoreBelowInitial);
+		assert_ok!(Reputation::reputation_change(4, ReputationOp::CompleteIncrease));
+		assert_ok!(Reputation::unbond(Origin::signed(4)));
+		assert_eq!(Balances::reserved_balance(&4), 0);
+		assert_eq!(Reputation::get_account_reputation_level(&4).score, 0);
+
+		// Unbootstrapped accounts do not gain a score from reputation changes.
+		assert_ok!(Reputation::reputation_change(5, ReputationOp::CompleteIncrease));
+		assert!(!Reputation::is_bootstrapped(&5));
+	});
+}
+
+#[test]
+fn linked_accounts_share_one_score() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(Reputation::link_account(Origin::signed(4), 5), RepError::<Test>::NotBootstrapped);
+		assert_ok!(Reputation::bootstrap(Origin::signed(4), BootstrapProof::Bond));
+		assert_noop!(Reputation::accept_link(Origin::signed(5), 4), RepError::<Test>::LinkInvalid);
+		assert_ok!(Reputation::link_account(Origin::signed(4), 5));
+		assert_ok!(Reputation::accept_link(Origin::signed(5), 4));
+		assert_eq!(Reputation::primary(&5), 4);
+
+		assert_ok!(Reputation::reputation_change(5, ReputationOp::CompleteIncrease));
+		assert_eq!(Reputation::get_account_reputation_level(&4).score, 55);
+		assert_eq!(Reputation::get_account_reputation_level(&5).score, 55);
+		assert_noop!(
+			Reputation::bootstrap(Origin::signed(5), BootstrapProof::Bond),
+			RepError::<Test>::AlreadyBootstrapped
+		);
+
+		// A linked account cannot claim the tasks of its primary account.
+		let hash = publish(4, PAY, JUDGE_PAY, 0, vec![]);
+		assert_noop!(
+			TaskBoard::claim_task(Origin::signed(5), hash, vec![]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+
+		assert_ok!(Reputation::link_account(Origin::signed(4), MEMBER));
+		assert_ok!(Reputation::link_account(Origin::signed(4), LEADER));
+		assert_ok!(Reputation::accept_link(Origin::signed(MEMBER), 4));
+		assert_noop!(Reputation::accept_link(Origin::signed(LEADER), 4), RepError::<Test>::LinkLimitReached);
+		assert_noop!(Reputation::link_account(Origin::signed(4), ISSUER), RepError::<Test>::LinkLimitReached);
+
+		assert_noop!(Reputation::unlink_account(Origin::signed(LEADER), 5), RepError::<Test>::LinkInvalid);
+		assert_ok!(Reputation::unlink_account(Origin::signed(5), 5));
+		assert_eq!(Reputation::get_account_reputation_level(&5).score, 0);
+		assert_eq!(Reputation::linked_accounts(4), vec![MEMBER]);
+	});
+}
+
+#[test]
+fn linked_accounts_share_rating_scores() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Reputation::rate(H256::repeat_byte(1), ISSUER, vec![LEADER], RatedRole::Worker, 1, H256::zero()));
+		assert_ok!(Reputation::bootstrap(Origin::signed(4), BootstrapProof::Bond));
+		assert_ok!(Reputation::link_account(Origin::signed(4), LEADER));
+		assert_ok!(Reputation::accept_link(Origin::signed(LEADER), 4));
+		// The ratings received before linking are shared too.
+		assert_eq!(Reputation::worker_ratings(4), RatingSummary { count: 1, total: 1 });
+		assert_eq!(Reputation::worker_ratings(LEADER), RatingSummary::default());
+
+		// Linked accounts rated on the same task count once.
+		let hash = H256::repeat_byte(2);
+		assert_ok!(Reputation::rate(hash, ISSUER, vec![LEADER, 4], RatedRole::Worker, 5, H256::zero()));
+		assert_eq!(Reputation::worker_ratings(4), RatingSummary { count: 2, total: 6 });
+		assert_eq!(Reputation::worker_score(&LEADER), Reputation::worker_score(&4));
+
+		assert_ok!(Reputation::rate(hash, MEMBER, vec![LEADER], RatedRole::Issuer, 4, H256::zero()));
+		assert_eq!(Reputation::issuer_ratings(4), RatingSummary { count: 1, total: 4 });
+		assert_eq!(Reputation::issuer_score(&LEADER), Reputation::issuer_score(&4));
+		assert_ne!(Reputation::issuer_score(&LEADER), Reputation::initial_score());
+	});
+}
+
+#[test]
+fn one_identity_sits_on_a_panel_once_and_never_judges_its_own_dispute() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Reputation::link_account(Origin::signed(JUDGES[0]), 4));
+		assert_ok!(Reputation::accept_link(Origin::signed(4), JUDGES[0]));
+		assert_ok!(Reputation::link_account(Origin::signed(JUDGES[1]), LEADER));
+		assert_ok!(Reputation::accept_link(Origin::signed(LEADER), JUDGES[1]));
+
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(4), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeIdentityRepeat
+		);
+		// JUDGES[1] shares an identity with the team leader.
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[1]), hash, 1, RATIONALE),
+			JudgeError::<Test>::JudgeIdentityRepeat
+		);
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[2]), hash, 1, RATIONALE));
+	});
+}
+
+#[test]
+fn new_identities_join_panels_after_a_warmup_and_at_a_limited_rate() {
+	ExtBuilder::default().panel_limits(10, 100, 1).build().execute_with(|| {
+		System::set_block_number(5);
+		assert_ok!(Reputation::bootstrap(Origin::signed(4), BootstrapProof::Bond));
+		let first = delivered_task();
+		let second = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), first));
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), second));
+
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(4), first, 1, RATIONALE),
+			JudgeError::<Test>::JudgeWarmingUp
+		);
+		System::set_block_number(15);
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(4), first, 1, RATIONALE));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(4), second, 1, RATIONALE),
+			JudgeError::<Test>::JudgeRateLimited
+		);
+		System::set_block_number(115);
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(4), second, 1, RATIONALE));
+
+		assert_noop!(JudgePool::set_panel_limits(Origin::signed(4), 0, 0, 0), BadOrigin);
+		assert_ok!(JudgePool::set_panel_limits(Origin::ROOT, 0, 0, 0));
+		assert_eq!(JudgePool::panel_period(), 0);
+	});
+}
+
+#[test]
+fn tasks_cannot_move_backwards() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = delivered_task();
+		let mut task = TaskBoard::query_task_by_hash(hash).unwrap();
+		assert_noop!(
+			TaskBoard::change_task_status(&mut task, TaskKind::InDelivery),
+			BoardError::<Test>::TaskChangeStatusFail
+		);
+		assert_noop!(
+			TaskBoard::change_task_status(&mut task, TaskKind::Published),
+			BoardError::<Test>::TaskDuplicated
+		);
+		assert_boards_consistent();
+	});
+}
+
+fn all_task_hashes() -> Vec<H256> {
+	(0..TaskBoard::task_count()).map(|i| TaskBoard::tasks(i).hash).collect()
+}
+
+fn create_template(issuer: u64) -> u32 {
+	let id = TaskBoard::template_count(issuer);
+	assert_ok!(TaskBoard::create_template(Origin::signed(issuer), b"weekly audit".to_vec(), 0, PAY, JUDGE_PAY, vec![]));
+	id
+}
+
+#[test]
+fn publish_from_template_applies_overrides() {
+	ExtBuilder::default().build().execute_with(|| {
+		let id = create_template(ISSUER);
+		assert_eq!(id, 0);
+		assert_eq!(create_template(ISSUER), 1);
+
+		assert_ok!(TaskBoard::publish_from_template(Origin::signed(ISSUER), id, Default::default()));
+		let task = TaskBoard::tasks(0);
+		assert_eq!(task.description, b"weekly audit".to_vec());
+		assert_eq!((task.pay, task.judge_pay), (PAY, JUDGE_PAY));
+
+		let overrides = TemplateOverrides { pay: Some(2 * PAY), min_rep: Some(60), ..Default::default() };
+		assert_ok!(TaskBoard::publish_from_template(Origin::signed(ISSUER), id, overrides));
+		let task = TaskBoard::tasks(1);
+		assert_eq!(task.description, b"weekly audit".to_vec());
+		assert_eq!((task.pay, task.judge_pay, task.min_rep), (2 * PAY, JUDGE_PAY, 60));
+		assert_ne!(TaskBoard::tasks(0).hash, task.hash);
+
+		// The template itself is left untouched.
+		assert_eq!(TaskBoard::templates((ISSUER, id)).unwrap().pay, PAY);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - 3 * PAY - 2 * JUDGE_PAY);
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn templates_belong_to_their_issuer() {
+	ExtBuilder::default().build().execute_with(|| {
+		let id = create_template(ISSUER);
+		assert_noop!(
+			TaskBoard::publish_from_template(Origin::signed(4), id, Default::default()),
+			BoardError::<Test>::TemplateNotFound
+		);
+		assert_noop!(
+			TaskBoard::remove_template(Origin::signed(4), id),
+			BoardError::<Test>::TemplateNotFound
+		);
+		assert_ok!(TaskBoard::remove_template(Origin::signed(ISSUER), id));
+		assert_noop!(
+			TaskBoard::publish_from_template(Origin::signed(ISSUER), id, Default::default()),
+			BoardError::<Test>::TemplateNotFound
+		);
+	});
+}
+
+#[test]
+fn recurring_schedule_publishes_until_funds_run_out() {
+	ExtBuilder::default().build().execute_with(|| {
+		let id = create_template(ISSUER);
+		assert_noop!(
+			TaskBoard::schedule_template(Origin::signed(ISSUER), id, 0, 250),
+			BoardError::<Test>::SchedulePeriodZero
+		);
+		assert_ok!(TaskBoard::schedule_template(Origin::signed(ISSUER), id, 10, 250));
+		assert_noop!(
+			TaskBoard::schedule_template(Origin::signed(ISSUER), id, 10, 250),
+			BoardError::<Test>::ScheduleDuplicated
+		);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 250);
+
+		run_to_block(10);
+		assert_eq!(TaskBoard::task_count(), 0);
+		run_to_block(11);
+		assert_eq!(TaskBoard::task_count(), 1);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 140);
+		run_to_block(21);
+		assert_eq!(TaskBoard::task_count(), 2);
+		assert_eq!(TaskBoard::schedules((ISSUER, id)).unwrap().next, 31);
+		assert_funds_conserved(&all_task_hashes());
+
+		// 30 left cannot pay for a third task, so the schedule ends and the rest is unreserved.
+		run_to_block(31);
+		assert_eq!(TaskBoard::task_count(), 2);
+		assert_eq!(TaskBoard::schedules((ISSUER, id)), None);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - 2 * (PAY + JUDGE_PAY));
+		assert_boards_consistent();
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn scheduled_publishing_is_capped_per_block() {
+	ExtBuilder::default().build().execute_with(|| {
+		let ids: Vec<u32> = (0..3).map(|_| create_template(ISSUER)).collect();
+		for id in &ids {
+			assert_ok!(TaskBoard::schedule_template(Origin::signed(ISSUER), *id, 10, PAY + JUDGE_PAY));
+		}
+
+		// Only `MaxScheduledPerBlock` schedules are published; the last one carries over.
+		run_to_block(11);
+		assert_eq!(TaskBoard::task_count(), 2);
+		assert_eq!(TaskBoard::schedules((ISSUER, ids[2])).unwrap().next, 12);
+		run_to_block(12);
+		assert_eq!(TaskBoard::task_count(), 3);
+		assert_eq!(TaskBoard::schedules((ISSUER, ids[2])).unwrap().next, 22);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn schedules_can_be_topped_up_and_cancelled() {
+	ExtBuilder::default().build().execute_with(|| {
+		let id = create_template(ISSUER);
+		assert_noop!(
+			TaskBoard::fund_schedule(Origin::signed(ISSUER), id, 100),
+			BoardError::<Test>::ScheduleNotFound
+		);
+		assert_ok!(TaskBoard::schedule_template(Origin::signed(ISSUER), id, 5, 100));
+		assert_ok!(TaskBoard::fund_schedule(Origin::signed(ISSUER), id, 20));
+		assert_eq!(TaskBoard::schedules((ISSUER, id)).unwrap().funds, 120);
+
+		run_to_block(6);
+		assert_eq!(TaskBoard::task_count(), 1);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 10);
+
+		assert_ok!(TaskBoard::cancel_schedule(Origin::signed(ISSUER), id));
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		run_to_block(11);
+		assert_eq!(TaskBoard::task_count(), 1);
+
+		// Removing a template ends its schedule as well.
+		assert_ok!(TaskBoard::schedule_template(Origin::signed(ISSUER), id, 5, 100));
+		assert_ok!(TaskBoard::remove_template(Origin::signed(ISSUER), id));
+		assert_eq!(TaskBoard::schedules((ISSUER, id)), None);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+/// Publish a sub-task of `parent` from `ISSUER` and return its hash.
+fn publish_sub(parent: H256, pay: u64, blocked_by: Vec<H256>) -> H256 {
+	assert_ok!(TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, b"part".to_vec(), 0, pay, JUDGE_PAY, vec![], blocked_by));
+	*TaskBoard::query_task_by_hash(parent).unwrap().children.last().unwrap()
+}
+
+/// Deliver a claimed task led by `leader` and have the judges rule on it.
+fn deliver_and_judge(hash: H256, leader: u64, votes: [u32; 3]) {
+	assert_ok!(TaskBoard::claim_deliver_task(Origin::signed(leader), hash, H256::repeat_byte(9)));
+	assert_ok!(TaskBoard::request_for_judge(Origin::signed(leader), hash));
+	vote_all(hash, votes);
+	assert_ok!(TaskBoard::task_to_final(Origin::signed(leader), hash));
+}
+
+#[test]
+fn sub_task_pay_is_carved_from_parent() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let child = publish_sub(parent, 40, vec![]);
+
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, parent)), PAY - 40);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, child)), 40);
+		assert_eq!(TaskBoard::staking_pay_pool((ISSUER, child)), JUDGE_PAY);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - 2 * JUDGE_PAY);
+		assert_eq!(TaskBoard::query_task_by_hash(child).unwrap().parent, Some(parent));
+		assert_boards_consistent();
+		assert_funds_conserved(&all_task_hashes());
+
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, vec![], 0, 61, JUDGE_PAY, vec![], vec![]),
+			BoardError::<Test>::ParentFundsInsufficient
+		);
+	});
+}
+
+#[test]
+fn sub_tasks_are_restricted_to_the_parent_issuer() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let foreign = publish(4, PAY, JUDGE_PAY, 0, vec![]);
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(4), parent, vec![], 0, 10, JUDGE_PAY, vec![], vec![]),
+			BoardError::<Test>::PermissionError
+		);
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, vec![], 0, 10, JUDGE_PAY, vec![], vec![foreign]),
+			BoardError::<Test>::DependencyInvalid
+		);
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, vec![], 0, 10, JUDGE_PAY, vec![], vec![H256::repeat_byte(42)]),
+			BoardError::<Test>::DependencyInvalid
+		);
+	});
+}
+
+#[test]
+fn parent_is_deliverable_only_once_children_are_done() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let child = publish_sub(parent, 40, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), parent, vec![]));
+		assert_ok!(TaskBoard::claim_task(Origin::signed(MEMBER), child, vec![]));
+
+		assert_noop!(
+			TaskBoard::claim_deliver_task(Origin::signed(LEADER), parent, H256::repeat_byte(7)),
+			BoardError::<Test>::SubTasksPending
+		);
+		deliver_and_judge(child, MEMBER, [1, 1, 1]);
+		assert_eq!(task_kind(child), TaskKind::Done);
+		assert_eq!(Balances::free_balance(&MEMBER), ENDOWMENT - JUDGE_PAY + 40);
+
+		// Pledges are per task, so the same panel can sit on the parent.
+		deliver_and_judge(parent, LEADER, [1, 1, 1]);
+		assert_eq!(task_kind(parent), TaskKind::Done);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + PAY - 40);
+		assert_boards_consistent();
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn blocked_task_cannot_be_claimed_before_its_dependencies_are_done() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let first = publish_sub(parent, 30, vec![]);
+		let second = publish_sub(parent, 30, vec![first, first]);
+		assert_eq!(TaskBoard::query_task_by_hash(second).unwrap().blocked_by, vec![first]);
+
+		assert_err!(
+			TaskBoard::claim_task(Origin::signed(MEMBER), second, vec![]),
+			BoardError::<Test>::TaskBlocked
+		);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), first, vec![]));
+		deliver_and_judge(first, LEADER, [1, 1, 0]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(MEMBER), second, vec![]));
+		assert_boards_consistent();
+	});
+}
+
+#[test]
+fn cancelled_sub_task_rolls_funds_back_to_parent() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let child = publish_sub(parent, 40, vec![]);
+
+		assert_noop!(
+			TaskBoard::cancel_task(Origin::signed(ISSUER), parent),
+			BoardError::<Test>::TaskHasSubTasks
+		);
+		assert_noop!(
+			TaskBoard::cancel_task(Origin::signed(LEADER), child),
+			BoardError::<Test>::PermissionError
+		);
+		assert_ok!(TaskBoard::cancel_task(Origin::signed(ISSUER), child));
+
+		assert_eq!(task_kind(child), TaskKind::Failure);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, parent)), PAY);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, child)), 0);
+		// The judge pay of an unjudged task goes back to the issuer.
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - JUDGE_PAY);
+		assert!(TaskBoard::query_task_by_hash(parent).unwrap().children.is_empty());
+
+		assert_ok!(TaskBoard::cancel_task(Origin::signed(ISSUER), parent));
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT);
+		assert_boards_consistent();
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn failed_sub_task_unblocks_parent_delivery() {
+	ExtBuilder::default().build().execute_with(|| {
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let child = publish_sub(parent, 40, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), parent, vec![]));
+		assert_ok!(TaskBoard::claim_task(Origin::signed(MEMBER), child, vec![]));
+
+		deliver_and_judge(child, MEMBER, [0, 0, 1]);
+		assert_eq!(task_kind(child), TaskKind::Failure);
+		assert_eq!(TaskBoard::issuer_pay_pool((ISSUER, parent)), PAY);
+		assert!(TaskBoard::query_task_by_hash(parent).unwrap().children.is_empty());
+
+		assert_ok!(TaskBoard::claim_deliver_task(Origin::signed(LEADER), parent, H256::repeat_byte(7)));
+		assert_boards_consistent();
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+/// Publish, claim and judge a task from `ISSUER` led by `LEADER` with `MEMBER`, returning its hash.
+fn finished_task(votes: [u32; 3]) -> H256 {
+	let hash = delivered_task();
+	assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+	vote_all(hash, votes);
+	assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+	hash
+}
+
+#[test]
+fn both_sides_rate_a_finished_task_once() {
+	ExtBuilder::default().build().execute_with(|| {
+		let review = H256::repeat_byte(0xbb);
+		let pending = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_noop!(
+			TaskBoard::rate_task(Origin::signed(ISSUER), pending, 5, review),
+			BoardError::<Test>::TaskKindInvalid
+		);
+
+		let hash = finished_task([0, 0, 1]);
+		assert_noop!(TaskBoard::rate_task(Origin::signed(4), hash, 5, review), BoardError::<Test>::TaskParticipantInvalid);
+		assert_noop!(TaskBoard::rate_task(Origin::signed(LEADER), hash, 0, review), RepError::<Test>::RatingInvalid);
+		assert_noop!(TaskBoard::rate_task(Origin::signed(LEADER), hash, 6, review), RepError::<Test>::RatingInvalid);
+
+		assert_ok!(TaskBoard::rate_task(Origin::signed(LEADER), hash, 1, review));
+		assert_ok!(TaskBoard::rate_task(Origin::signed(MEMBER), hash, 2, review));
+		assert_noop!(TaskBoard::rate_task(Origin::signed(LEADER), hash, 1, review), RepError::<Test>::RatingDuplicated);
+		assert_ok!(TaskBoard::rate_task(Origin::signed(ISSUER), hash, 4, review));
+
+		assert_eq!(Reputation::issuer_ratings(ISSUER), RatingSummary { count: 2, total: 3 });
+		assert_eq!(Reputation::worker_ratings(LEADER), RatingSummary { count: 1, total: 4 });
+		assert_eq!(Reputation::worker_ratings(MEMBER), RatingSummary { count: 1, total: 4 });
+		assert_eq!(Reputation::rating((hash, MEMBER)).unwrap().review, review);
+		// (20 + 40 + 2 * 50) / 4
+		assert_eq!(Reputation::issuer_score(&ISSUER), 40);
+		// (80 + 2 * 50) / 3
+		assert_eq!(Reputation::worker_score(&LEADER), 60);
+		assert_eq!(Reputation::issuer_score(&4), 50);
+	});
+}
+
+#[test]
+fn workers_filter_tasks_by_issuer_score() {
+	ExtBuilder::default().build().execute_with(|| {
+		let hash = finished_task([1, 1, 1]);
+		assert_ok!(TaskBoard::rate_task(Origin::signed(LEADER), hash, 1, H256::zero()));
+
+		let next = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::set_min_issuer_score(Origin::signed(4), 45));
+		assert_err!(
+			TaskBoard::claim_task(Origin::signed(4), next, vec![]),
+			BoardError::<Test>::TaskPlayerIsInvalid
+		);
+		assert_ok!(TaskBoard::set_min_issuer_score(Origin::signed(4), 40));
+		assert_ok!(TaskBoard::claim_task(Origin::signed(4), next, vec![]));
+	});
+}
+
+#[test]
+fn parameters_can_only_be_changed_by_the_parameter_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(JudgePool::change_judge_size(Origin::signed(ISSUER), 5), BadOrigin);
+		assert_noop!(JudgePool::change_judge_threshold(Origin::signed(ISSUER), 0), BadOrigin);
+		assert_noop!(Reputation::set_initial_score(Origin::signed(ISSUER), 0), BadOrigin);
+		assert_noop!(Reputation::set_reputation_step(Origin::signed(ISSUER), 0), BadOrigin);
+		assert_noop!(TaskBoard::set_task_limits(Origin::signed(ISSUER), 0, 0), BadOrigin);
+		assert_noop!(TaskBoard::set_protocol_fee(Origin::signed(ISSUER), Permill::one()), BadOrigin);
+
+		assert_ok!(JudgePool::change_judge_size(Origin::ROOT, 5));
+		assert_ok!(JudgePool::change_judge_threshold(Origin::ROOT, 0));
+		assert_ok!(Reputation::set_initial_score(Origin::ROOT, 80));
+		assert_ok!(Reputation::set_reputation_step(Origin::ROOT, 3));
+		assert_ok!(TaskBoard::set_task_limits(Origin::ROOT, 1, 0));
+		assert_ok!(TaskBoard::set_protocol_fee(Origin::ROOT, Permill::from_percent(10)));
+		assert_eq!(JudgePool::judge_size(), 5);
+		assert_eq!(JudgePool::threshold(), 0);
+		assert_eq!(TaskBoard::max_sub_tasks(), 1);
+		assert_eq!(TaskBoard::max_blocked_by(), 0);
+		assert_eq!(TaskBoard::protocol_fee(), Permill::from_percent(10));
+
+		assert_ok!(Reputation::bootstrap(Origin::signed(4), BootstrapProof::Bond));
+		assert_eq!(Reputation::get_account_reputation_level(&4).score, 80);
+		assert_eq!(Reputation::reputation_step(), 3);
+	});
+}
+
+#[test]
+fn reputation_rises_on_completion_and_falls_on_failure() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Reputation::set_initial_score(Origin::ROOT, 80));
+		assert_ok!(Reputation::set_reputation_step(Origin::ROOT, 3));
+
+		assert_ok!(Reputation::reputation_change(4, ReputationOp::CompleteIncrease));
+		assert_eq!(Reputation::get_account_reputation_level(&4).score, 83);
+		assert_ok!(Reputation::reputation_change(4, ReputationOp::FailedReduce));
+		assert_ok!(Reputation::reputation_change(4, ReputationOp::FailedReduce));
+		assert_eq!(Reputation::get_account_reputation_level(&4).score, 77);
+	});
+}
+
+#[test]
+fn task_limits_are_read_from_storage() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(TaskBoard::set_task_limits(Origin::ROOT, 1, 0));
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let child = publish_sub(parent, 40, vec![]);
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, vec![], 0, 10, JUDGE_PAY, vec![], vec![]),
+			BoardError::<Test>::SubTaskLimitReached
+		);
+		assert_ok!(TaskBoard::set_task_limits(Origin::ROOT, 2, 0));
+		assert_noop!(
+			TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, vec![], 0, 10, JUDGE_PAY, vec![], vec![child]),
+			BoardError::<Test>::DependencyLimitReached
+		);
+	});
+}
+
+#[test]
+fn completed_task_pays_protocol_fee_to_treasury() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(TaskBoard::set_protocol_fee(Origin::ROOT, Permill::from_percent(10)));
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+		vote_all(hash, [1, 1, 1]);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+
+		let fee = PAY / 10;
+		assert_eq!(Balances::free_balance(&TREASURY), fee);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + (PAY - fee) / 2);
+		assert_eq!(Balances::free_balance(&MEMBER), ENDOWMENT - JUDGE_PAY + (PAY - fee) / 2);
+		assert_eq!(total_balances(&ACCOUNTS) + escrowed(hash, &ACCOUNTS) + fee, total_endowment());
+	});
+}
+
+#[test]
+fn failed_task_pays_no_protocol_fee() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(TaskBoard::set_protocol_fee(Origin::ROOT, Permill::from_percent(10)));
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		vote_all(hash, [0, 0, 1]);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(LEADER), hash));
+
+		assert_eq!(Balances::free_balance(&TREASURY), 0);
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn unanswered_delivery_is_accepted_once_the_acceptance_period_ends() {
+	ExtBuilder::default().deadlines(10, 2, 0).build().execute_with(|| {
+		System::set_block_number(1);
+		let hash = delivered_task();
+		assert_eq!(TaskBoard::delivered_at(hash), Some(1));
+
+		assert_noop!(TaskBoard::poke(Origin::signed(ISSUER), hash), BadOrigin);
+		run_to_block(10);
+		assert_noop!(TaskBoard::poke(Origin::NONE, hash), BoardError::<Test>::DeadlineNotReached);
+
+		run_to_block(11);
+		assert_ok!(TaskBoard::poke(Origin::NONE, hash));
+		assert_eq!(task_kind(hash), TaskKind::Done);
+		assert_eq!(TaskBoard::delivered_at(hash), None);
+		// Nobody disputed the delivery, so every judge pay stake is returned.
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT + PAY / 2);
+		assert_eq!(Balances::free_balance(&MEMBER), ENDOWMENT + PAY / 2);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY);
+		assert_noop!(TaskBoard::poke(Origin::NONE, hash), BoardError::<Test>::DeadlineNotReached);
+
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn disputed_delivery_has_no_acceptance_deadline() {
+	ExtBuilder::default().deadlines(10, 2, 0).build().execute_with(|| {
+		System::set_block_number(1);
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_eq!(TaskBoard::delivered_at(hash), None);
+
+		run_to_block(100);
+		assert_noop!(TaskBoard::poke(Origin::NONE, hash), BoardError::<Test>::DeadlineNotReached);
+	});
+}
+
+#[test]
+fn stalled_dispute_is_closed_with_the_votes_cast() {
+	ExtBuilder::default().deadlines(0, 0, 20).build().execute_with(|| {
+		System::set_block_number(1);
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_eq!(JudgePool::voting_deadline(&hash), Some(21));
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+
+		run_to_block(20);
+		assert_noop!(TaskBoard::poke(Origin::NONE, hash), BoardError::<Test>::DeadlineNotReached);
+
+		run_to_block(21);
+		assert_ok!(TaskBoard::poke(Origin::NONE, hash));
+		assert_eq!(JudgePool::view_progress(hash), JudgeKind::Done);
+		assert_eq!(JudgePool::voting_deadline(&hash), None);
+		assert_eq!(JudgePool::judgement_done(hash), Some(21));
+		assert_eq!(JudgePool::rationale((hash, JUDGES[0])), Some(RATIONALE));
+		assert_eq!(task_kind(hash), TaskKind::Done);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + PAY / 2);
+
+		assert_boards_consistent();
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn votes_after_a_stalled_dispute_is_closed_are_rejected() {
+	ExtBuilder::default().deadlines(0, 0, 20).build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_ok!(JudgePool::exec_judgement(Origin::signed(JUDGES[0]), hash, 1, RATIONALE));
+
+		run_to_block(21);
+		assert_ok!(TaskBoard::poke(Origin::NONE, hash));
+		assert!(!JudgePool::verify_judge_for_hash(JUDGES[1], &hash));
+		assert_noop!(
+			JudgePool::exec_judgement(Origin::signed(JUDGES[1]), hash, 0, RATIONALE),
+			JudgeError::<Test>::JudgeKindInvalid
+		);
+		assert_eq!(Balances::free_balance(&JUDGES[1]), ENDOWMENT);
+		assert_eq!(JudgePool::judges(hash).judges.len(), 1);
+		assert_funds_conserved(&[hash]);
+	});
+}
+
+#[test]
+fn dispute_nobody_voted_on_fails_the_task() {
+	ExtBuilder::default().deadlines(0, 0, 20).build().execute_with(|| {
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(LEADER), hash));
+
+		run_to_block(20);
+		assert_ok!(TaskBoard::poke(Origin::NONE, hash));
+		assert_eq!(task_kind(hash), TaskKind::Failure);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - JUDGE_PAY);
+
+		assert_noop!(JudgePool::set_voting_period(Origin::signed(ISSUER), 0), BadOrigin);
+		assert_ok!(JudgePool::set_voting_period(Origin::ROOT, 0));
+		assert_eq!(JudgePool::voting_period(), 0);
+	});
+}
+
+#[test]
+fn offchain_worker_pokes_overdue_tasks() {
+	let mut ext = ExtBuilder::default().deadlines(10, 2, 0).build();
+	let (offchain, _offchain_state) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let hash = delivered_task();
+		let poke = BoardCall::poke(hash);
+
+		run_to_block(10);
+		TaskBoard::offchain_worker(10);
+		assert!(pool_state.read().transactions.is_empty());
+		assert!(TaskBoard::validate_unsigned(&poke).is_err());
+
+		run_to_block(11);
+		TaskBoard::offchain_worker(11);
+		let transaction = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let extrinsic = Extrinsic::decode(&mut &*transaction).unwrap();
+		assert_eq!(extrinsic.0, None);
+		assert_eq!(extrinsic.1, poke);
+		assert!(TaskBoard::validate_unsigned(&poke).is_ok());
+
+		assert_noop!(TaskBoard::set_deadline_params(Origin::signed(ISSUER), 0, 0), BadOrigin);
+		assert_ok!(TaskBoard::set_deadline_params(Origin::ROOT, 0, 0));
+		assert!(TaskBoard::validate_unsigned(&poke).is_err());
+	});
+}
+
+#[test]
+fn insurance_premium_is_pooled_and_reported() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(TaskBoard::set_insurance_premium(Origin::signed(ISSUER), Permill::from_percent(10)), BadOrigin);
+		assert_ok!(TaskBoard::set_insurance_premium(Origin::ROOT, Permill::from_percent(10)));
+		let premium = PAY / 10;
+
+		let parent = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_eq!(Balances::free_balance(&ISSUER), ENDOWMENT - PAY - JUDGE_PAY - premium);
+		assert_eq!(TaskBoard::insurance_pool(), premium);
+
+		// A sub-task is covered by the premium of its parent.
+		assert_ok!(TaskBoard::publish_sub_task(Origin::signed(ISSUER), parent, b"part".to_vec(), 0, PAY / 2, JUDGE_PAY, vec![], vec![]));
+		assert_eq!(TaskBoard::insurance_pool(), premium);
+
+		// Only claimed tasks count towards the exposure.
+		let solvency = TaskBoard::insurance_solvency();
+		assert_eq!((solvency.pool, solvency.exposure, solvency.pending_appeals), (premium, 0, 0));
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), parent, vec![]));
+		assert_eq!(TaskBoard::insurance_solvency().exposure, PAY / 2);
+
+		// Cancelling does not refund the premium.
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::cancel_task(Origin::signed(ISSUER), hash));
+		assert_eq!(TaskBoard::insurance_pool(), 2 * premium);
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn appeals_are_compensated_out_of_the_insurance_pool() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(TaskBoard::set_insurance_premium(Origin::ROOT, Permill::from_percent(10)));
+		assert_noop!(TaskBoard::set_appeal_deposit(Origin::signed(ISSUER), 3), BadOrigin);
+		assert_ok!(TaskBoard::set_appeal_deposit(Origin::ROOT, 3));
+		let statement = H256::repeat_byte(3);
+		let published = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_noop!(TaskBoard::appeal(Origin::signed(ISSUER), published, statement), BoardError::<Test>::AppealInvalid);
+
+		// The receivers disappear once they claimed the task.
+		let abandoned = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), abandoned, vec![]));
+		assert_noop!(TaskBoard::appeal(Origin::signed(4), abandoned, statement), BoardError::<Test>::TaskParticipantInvalid);
+		assert_ok!(TaskBoard::appeal(Origin::signed(ISSUER), abandoned, statement));
+		assert_noop!(TaskBoard::appeal(Origin::signed(ISSUER), abandoned, statement), BoardError::<Test>::AppealDuplicated);
+		assert_eq!(TaskBoard::pending_appeals(), 1);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 3);
+
+		let pool = TaskBoard::insurance_pool();
+		assert_noop!(TaskBoard::resolve_appeal(Origin::signed(ISSUER), abandoned, ISSUER, 1), BadOrigin);
+		assert_noop!(
+			TaskBoard::resolve_appeal(Origin::ROOT, abandoned, ISSUER, pool + 1),
+			BoardError::<Test>::InsuranceInsufficient
+		);
+		let before = Balances::free_balance(&ISSUER);
+		assert_ok!(TaskBoard::resolve_appeal(Origin::ROOT, abandoned, ISSUER, pool - 5));
+		// An upheld appeal gets its deposit back.
+		assert_eq!(Balances::free_balance(&ISSUER), before + pool - 5 + 3);
+		assert_eq!(Balances::reserved_balance(&ISSUER), 0);
+		assert_eq!(TaskBoard::insurance_pool(), 5);
+		assert_eq!(TaskBoard::pending_appeals(), 0);
+		assert_eq!(TaskBoard::appeals((abandoned, ISSUER)).unwrap().compensation, pool - 5);
+		assert_noop!(TaskBoard::resolve_appeal(Origin::ROOT, abandoned, ISSUER, 0), BoardError::<Test>::AppealNotFound);
+
+		// A worker can appeal a judgement, and governance can reject the appeal.
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		vote_all(hash, [0, 0, 0]);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(ISSUER), hash));
+		assert_ok!(TaskBoard::appeal(Origin::signed(MEMBER), hash, statement));
+		let before = total_balances(&[MEMBER]);
+		assert_ok!(TaskBoard::resolve_appeal(Origin::ROOT, hash, MEMBER, 0));
+		assert!(TaskBoard::appeals((hash, MEMBER)).unwrap().resolved);
+		// A rejected appeal forfeits its deposit to the insurance pool.
+		assert_eq!(total_balances(&[MEMBER]), before - 3);
+		assert_eq!(TaskBoard::insurance_pool(), 5 + PAY / 10 + 3);
+		assert_funds_conserved(&all_task_hashes());
+	});
+}
+
+#[test]
+fn disputes_are_kept_until_appeals_are_resolved_and_retention_passed() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(JudgePool::set_dispute_retention(Origin::signed(ISSUER), 10), BadOrigin);
+		let hash = delivered_task();
+		assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+		assert_ok!(JudgePool::submit_evidence(Origin::signed(MEMBER), hash, H256::repeat_byte(0xee), vec![]));
+		vote_all(hash, [0, 0, 0]);
+		assert_ok!(TaskBoard::task_to_final(Origin::signed(ISSUER), hash));
+		assert_eq!(JudgePool::judgement_done(hash), Some(1));
+
+		// Disputes are kept forever until governance sets a retention.
+		run_to_block(100);
+		assert_noop!(JudgePool::prune_dispute(Origin::signed(4), hash), JudgeError::<Test>::DisputeRetained);
+		assert_ok!(JudgePool::set_dispute_retention(Origin::ROOT, 10));
+
+		// A pending appeal holds the dispute past the retention.
+		assert_ok!(TaskBoard::appeal(Origin::signed(MEMBER), hash, H256::repeat_byte(0xaa)));
+		assert_noop!(JudgePool::prune_dispute(Origin::signed(4), hash), JudgeError::<Test>::DisputeRetained);
+		assert_eq!(JudgePool::evidences(hash).len(), 1);
+		assert_ok!(TaskBoard::resolve_appeal(Origin::ROOT, hash, MEMBER, 0));
+
+		assert_ok!(JudgePool::prune_dispute(Origin::signed(4), hash));
+		assert!(JudgePool::evidences(hash).is_empty());
+		assert!(JudgePool::parties(hash).is_empty());
+		assert_eq!(JudgePool::rationale((hash, JUDGES[0])), None);
+		assert_noop!(JudgePool::prune_dispute(Origin::signed(4), hash), JudgeError::<Test>::JudgementNotFound);
+		// With the dispute gone, the judgement can't be appealed any more.
+		assert_noop!(
+			TaskBoard::appeal(Origin::signed(ISSUER), hash, H256::repeat_byte(0xaa)),
+			BoardError::<Test>::AppealInvalid
+		);
+	});
+}
+
+#[test]
+fn company_members_act_within_their_roles() {
+	ExtBuilder::default().build().execute_with(|| {
+		let org = Utility::sub_account_id(ISSUER, 0);
+		assert_ok!(Organisations::create_company(Origin::signed(ISSUER), 0));
+		assert_noop!(Organisations::create_company(Origin::signed(ISSUER), 0), OrgError::<Test>::OrganisationDuplicated);
+		assert_ok!(Balances::transfer(Origin::signed(ISSUER), org, 500));
+
+		assert_noop!(Organisations::set_roles(Origin::signed(4), org, 4, Roles::PUBLISH), OrgError::<Test>::PermissionError);
+		assert_ok!(Organisations::set_roles(Origin::signed(ISSUER), org, 4, Roles::PUBLISH));
+
+		let publish_call = || Box::new(BoardCall::publish_task(b"audit".to_vec(), 0, PAY, JUDGE_PAY, vec![]));
+		assert_ok!(Organisations::act_as(Origin::signed(4), org, publish_call()));
+		let hash = *TaskBoard::load_board(TaskKind::Published as u8).inner_board.last().unwrap();
+		assert_eq!(TaskBoard::query_task_by_hash(hash).unwrap().issuer, org);
+		assert_eq!(Balances::free_balance(&org), 500 - PAY - JUDGE_PAY);
+
+		// The founder acts as the company through its sub-account directly.
+		assert_ok!(utility::Call::<Test>::as_sub(0, publish_call()).dispatch(Origin::signed(ISSUER)));
+		assert_eq!(Balances::free_balance(&org), 500 - 2 * (PAY + JUDGE_PAY));
+
+		assert_noop!(
+			Organisations::act_as(Origin::signed(4), org, Box::new(BoardCall::request_for_judge(hash))),
+			OrgError::<Test>::PermissionError
+		);
+		assert_noop!(
+			Organisations::act_as(Origin::signed(4), org, Box::new(BoardCall::set_min_issuer_score(0))),
+			OrgError::<Test>::CallNotAllowed
+		);
+		assert_noop!(
+			Organisations::act_as(Origin::signed(LEADER), org, publish_call()),
+			OrgError::<Test>::PermissionError
+		);
+
+		// Members do not claim the tasks of their own company.
+		assert_noop!(TaskBoard::claim_task(Origin::signed(4), hash, vec![]), BoardError::<Test>::TaskPlayerIsInvalid);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![]));
+
+		assert_noop!(Organisations::set_roles(Origin::signed(ISSUER), org, ISSUER, Roles::PUBLISH), OrgError::<Test>::LastAdmin);
+		assert_ok!(Organisations::set_roles(Origin::signed(ISSUER), org, 4, Roles::default()));
+		assert!(!Organisations::is_member(&org, &4));
+		assert_eq!(Organisations::members(&org), vec![ISSUER]);
+		assert_noop!(Organisations::act_as(Origin::signed(4), org, publish_call()), OrgError::<Test>::PermissionError);
+	});
+}
+
+#[test]
+fn squads_act_with_the_approval_of_their_members_and_share_their_reputation() {
+	ExtBuilder::default()
+		.subject(0, 4)
+		.credential(LEADER, 0)
+		.score(LEADER, 80)
+		.score(MEMBER, 50)
+		.build()
+		.execute_with(|| {
+			assert_noop!(Organisations::create_squad(Origin::signed(LEADER), vec![], 1), OrgError::<Test>::SquadInvalid);
+			assert_noop!(Organisations::create_squad(Origin::signed(LEADER), vec![MEMBER], 3), OrgError::<Test>::SquadInvalid);
+			assert_ok!(Organisations::create_squad(Origin::signed(MEMBER), vec![LEADER], 2));
+			let squad = Utility::multi_account_id(&[LEADER, MEMBER], 2);
+			assert_eq!(Organisations::members(&squad), vec![LEADER, MEMBER]);
+			assert_ok!(Balances::transfer(Origin::signed(LEADER), squad, 100));
+			assert_noop!(
+				Organisations::set_roles(Origin::signed(squad), squad, 4, Roles::WORK),
+				OrgError::<Test>::SquadMembersFixed
+			);
+			// The squad acts once both members approved the call through `as_multi`.
+			let as_multi = |who: u64, other: u64, timepoint, call: BoardCall<Test>| {
+				utility::Call::<Test>::as_multi(2, vec![other], timepoint, Box::new(call)).dispatch(Origin::signed(who))
+			};
+			let approve = |call: BoardCall<Test>| {
+				let timepoint = Utility::timepoint();
+				assert_ok!(as_multi(MEMBER, LEADER, None, call.clone()));
+				assert_ok!(as_multi(LEADER, MEMBER, Some(timepoint), call));
+			};
+
+			// Only LEADER holds the credential, and the squad claims with its mean score.
+			let out_of_reach = publish(ISSUER, PAY, 0, 66, vec![0]);
+			approve(BoardCall::claim_task(out_of_reach, vec![]));
+			assert_eq!(task_kind(out_of_reach), TaskKind::Published);
+
+			let hash = publish(ISSUER, PAY, JUDGE_PAY, 65, vec![0]);
+			assert_noop!(
+				Organisations::act_as(Origin::signed(MEMBER), squad, Box::new(BoardCall::claim_task(hash, vec![]))),
+				OrgError::<Test>::SquadNeedsApprovals
+			);
+			let timepoint = Utility::timepoint();
+			assert_ok!(as_multi(MEMBER, LEADER, None, BoardCall::claim_task(hash, vec![])));
+			assert_eq!(task_kind(hash), TaskKind::Published);
+			assert_ok!(as_multi(LEADER, MEMBER, Some(timepoint), BoardCall::claim_task(hash, vec![])));
+			approve(BoardCall::claim_deliver_task(hash, H256::zero()));
+			let task = TaskBoard::query_task_by_hash(hash).unwrap();
+			assert_eq!((task.receivers, task.kind), (vec![squad], TaskKind::Deliveryed));
+
+			// The rating the squad earns is credited to every member.
+			assert_ok!(TaskBoard::request_for_judge(Origin::signed(ISSUER), hash));
+			vote_all(hash, [1, 1, 1]);
+			assert_ok!(TaskBoard::task_to_final(Origin::signed(ISSUER), hash));
+			assert_ok!(TaskBoard::rate_task(Origin::signed(ISSUER), hash, 5, H256::zero()));
+			for member in &[LEADER, MEMBER] {
+				assert_eq!(Reputation::worker_ratings(member), RatingSummary { count: 1, total: 5 });
+			}
+
+			// A squad does not claim the tasks of its own members.
+			let own = publish(MEMBER, PAY, 0, 0, vec![]);
+			approve(BoardCall::claim_task(own, vec![]));
+			assert_eq!(task_kind(own), TaskKind::Published);
+		});
+}
+
+#[test]
+fn dework_call_indices_are_frozen() {
+	let index = |call: DeWorkCall<Test>| call.encode()[0];
+	assert_eq!(crate::dework::CALL_INDEX_VERSION, 1);
+	assert_eq!(index(DeWorkCall::register_user(vec![], vec![], vec![], vec![], vec![])), 0);
+	assert_eq!(index(DeWorkCall::create_subject()), 1);
+	assert_eq!(index(DeWorkCall::issue(LEADER, 0)), 2);
+	assert_eq!(index(DeWorkCall::attest_kyc(LEADER, true)), 5);
+	assert_eq!(index(DeWorkCall::publish_task(vec![], 0, PAY, JUDGE_PAY, vec![])), 6);
+	assert_eq!(index(DeWorkCall::claim_task(H256::zero(), vec![])), 9);
+	assert_eq!(index(DeWorkCall::deliver_task(H256::zero(), H256::zero())), 10);
+	assert_eq!(index(DeWorkCall::rate_task(H256::zero(), 5, H256::zero())), 13);
+	assert_eq!(index(DeWorkCall::cancel_schedule(0)), 20);
+	assert_eq!(index(DeWorkCall::submit_evidence(H256::zero(), H256::zero(), vec![])), 21);
+	assert_eq!(index(DeWorkCall::verify_judgement_done(H256::zero())), 23);
+	assert_eq!(index(DeWorkCall::bootstrap(BootstrapProof::Bond)), 24);
+	assert_eq!(index(DeWorkCall::unlink_account(MEMBER)), 28);
+	assert_eq!(index(DeWorkCall::appeal(H256::zero(), H256::zero())), 29);
+	assert_eq!(index(DeWorkCall::set_claim_deadline(H256::zero(), None)), 30);
+}
+
+#[test]
+fn legacy_module_and_call_indices_are_frozen() {
+	use frame_support::traits::ModuleToIndex as _;
+	use crate::{Call as RuntimeCall, ModuleToIndex, Runtime, se::Call as LegacyCall};
+
+	assert_eq!(ModuleToIndex::module_to_index::<crate::Caliburn>(), Some(9));
+	assert_eq!(ModuleToIndex::module_to_index::<crate::DeWorkTasks>(), Some(10));
+	assert_eq!(ModuleToIndex::module_to_index::<crate::Identity>(), Some(11));
+	assert_eq!(ModuleToIndex::module_to_index::<crate::ReputationModule>(), Some(12));
+	assert_eq!(ModuleToIndex::module_to_index::<crate::JudgePoolModule>(), Some(13));
+
+	let index = |call: LegacyCall<Runtime>| RuntimeCall::Caliburn(call).encode()[..2].to_vec();
+	assert_eq!(index(LegacyCall::issue(Default::default(), 0)), vec![7, 0]);
+	assert_eq!(index(LegacyCall::publish_task(vec![], 0, 0, 0, vec![])), vec![7, 1]);
+	assert_eq!(index(LegacyCall::claim_task(H256::zero(), vec![])), vec![7, 2]);
+	assert_eq!(index(LegacyCall::revoke_credential(Default::default(), 0)), vec![7, 3]);
+	assert_eq!(index(LegacyCall::verify_credential(Default::default(), 0)), vec![7, 4]);
+	assert_eq!(index(LegacyCall::create_subject()), vec![7, 5]);
+	assert_eq!(index(LegacyCall::register_user(vec![], vec![], vec![], vec![], vec![])), vec![7, 6]);
+	assert_eq!(RuntimeCall::DeWorkTasks(BoardCall::<Runtime>::cancel_task(H256::zero())).encode()[0], 8);
+}
+
+#[test]
+fn dework_calls_drive_tasks_like_the_pallet_calls() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(DeWork::publish_task(Origin::signed(ISSUER), b"audit".to_vec(), 0, PAY, JUDGE_PAY, vec![]));
+		let hash = *TaskBoard::load_board(TaskKind::Published as u8).inner_board.last().unwrap();
+		assert_ok!(DeWork::claim_task(Origin::signed(LEADER), hash, vec![MEMBER]));
+		assert_ok!(DeWork::deliver_task(Origin::signed(LEADER), hash, H256::repeat_byte(7)));
+		assert_ok!(DeWork::request_for_judge(Origin::signed(LEADER), hash));
+		for judge in JUDGES.iter() {
+			assert_ok!(DeWork::exec_judgement(Origin::signed(*judge), hash, 1, RATIONALE));
+		}
+		assert_ok!(DeWork::task_to_final(Origin::signed(LEADER), hash));
+
+		assert_eq!(task_kind(hash), TaskKind::Done);
+		assert_eq!(Balances::free_balance(&LEADER), ENDOWMENT - JUDGE_PAY + PAY / 2);
+		assert_funds_conserved(&[hash]);
+		assert_boards_consistent();
+	});
+}
+
+#[test]
+fn legacy_proxy_forwards_to_dework() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Caliburn::create_subject(Origin::signed(4)));
+		assert_ok!(Caliburn::issue(Origin::signed(4), LEADER, 0));
+		assert!(Identity::check_credential(&LEADER, &0));
+
+		assert_ok!(Caliburn::publish_task(Origin::signed(ISSUER), b"audit".to_vec(), 0, PAY, JUDGE_PAY, vec![0]));
+		let hash = *TaskBoard::load_board(TaskKind::Published as u8).inner_board.last().unwrap();
+		assert_noop!(Caliburn::claim_task(Origin::signed(MEMBER), hash, vec![]), BoardError::<Test>::TaskPlayerIsInvalid);
+		assert_ok!(Caliburn::claim_task(Origin::signed(LEADER), hash, vec![]));
+		assert_eq!(task_kind(hash), TaskKind::InDelivery);
+	});
+}
+
+#[test]
+fn legacy_calls_are_accepted_for_one_window_after_the_upgrade() {
+	let check = || CheckLegacyCall::<Test>::new().validate(&ISSUER, &(), Default::default(), 0);
+
+	// A chain started with the DeWork calls never accepted legacy calls.
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(DeWork::storage_version(), crate::dework::STORAGE_VERSION);
+		assert_eq!(check(), InvalidTransaction::Call.into());
+	});
+
+	// An upgraded chain accepts them until `LegacyCallWindow` blocks after the upgrade.
+	ExtBuilder::default().build().execute_with(|| {
+		unhashed::put(&value_key(b"DeWork", b"StorageVersion"), &0u32);
+		System::set_block_number(5);
+		DeWork::on_initialize(5);
+		let end = 5 + LegacyCallWindow::get();
+		assert_eq!(DeWork::legacy_calls_end(), end);
+		assert_eq!(DeWork::storage_version(), crate::dework::STORAGE_VERSION);
+
+		System::set_block_number(end - 1);
+		assert!(check().is_ok());
+		System::set_block_number(end);
+		assert_eq!(check(), InvalidTransaction::Call.into());
+
+		// The migration runs once.
+		DeWork::on_initialize(end);
+		assert_eq!(DeWork::legacy_calls_end(), end);
+	});
+}
+
+#[test]
+fn only_the_listed_runtime_calls_are_legacy() {
+	use crate::{Call as RuntimeCall, Runtime, dework::LegacyCall};
+	let hash = H256::repeat_byte(1);
+
+	assert!(RuntimeCall::DeWorkTasks(BoardCall::<Runtime>::cancel_task(hash)).is_legacy_call());
+	assert!(RuntimeCall::Caliburn(crate::se::Call::<Runtime>::create_subject()).is_legacy_call());
+	assert!(RuntimeCall::JudgePoolModule(crate::judge_pool::Call::<Runtime>::exec_judgement(hash, 1, RATIONALE)).is_legacy_call());
+	// Governance and the unsigned poke have no DeWork counterpart.
+	assert!(!RuntimeCall::DeWorkTasks(BoardCall::<Runtime>::poke(hash)).is_legacy_call());
+	assert!(!RuntimeCall::DeWork(DeWorkCall::<Runtime>::cancel_task(hash)).is_legacy_call());
+	assert!(RuntimeCall::Utility(utility::Call::<Runtime>::batch(vec![
+		RuntimeCall::DeWork(DeWorkCall::<Runtime>::cancel_task(hash)),
+		RuntimeCall::DeWorkTasks(BoardCall::<Runtime>::cancel_task(hash)),
+	])).is_legacy_call());
+}
+
+#[test]
+fn eligible_tasks_are_ranked_by_pay_subjects_and_age() {
+	ExtBuilder::default()
+		.subject(0, 4)
+		.credential(LEADER, 0)
+		.score(LEADER, 50)
+		.build()
+		.execute_with(|| {
+			Timestamp::set_timestamp(1);
+			let plain = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+			let credentialed = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![0]);
+			let best_paid = publish(ISSUER, 2 * PAY, JUDGE_PAY, 0, vec![]);
+			// Out of reach of everyone's score.
+			publish(ISSUER, 3 * PAY, JUDGE_PAY, 60, vec![]);
+			Timestamp::set_timestamp(2);
+			let younger = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+			let own = publish(LEADER, 3 * PAY, JUDGE_PAY, 0, vec![]);
+
+			let hashes = |who| TaskBoard::eligible_tasks(&who, 0, 10).into_iter().map(|t| t.hash).collect::<Vec<_>>();
+			assert_eq!(hashes(LEADER), vec![best_paid, credentialed, plain, younger]);
+			assert_eq!(hashes(MEMBER), vec![own, best_paid, plain, younger]);
+
+			let first = &TaskBoard::eligible_tasks(&LEADER, 0, 1)[0];
+			assert_eq!((first.pay, first.judge_pay, first.min_rep, first.published_at), (2 * PAY, JUDGE_PAY, 0, 1));
+
+			assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), best_paid, vec![]));
+			assert_eq!(hashes(LEADER), vec![credentialed, plain, younger]);
+		});
+}
+
+#[test]
+fn eligible_tasks_with_the_nearest_claim_deadline_come_first() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(1);
+		let open = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		Timestamp::set_timestamp(2);
+		let later = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		let sooner = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+
+		assert_noop!(TaskBoard::set_claim_deadline(Origin::signed(LEADER), sooner, Some(5)), BoardError::<Test>::PermissionError);
+		assert_noop!(TaskBoard::set_claim_deadline(Origin::signed(ISSUER), sooner, Some(0)), BoardError::<Test>::ClaimDeadlinePassed);
+		assert_ok!(TaskBoard::set_claim_deadline(Origin::signed(ISSUER), later, Some(10)));
+		assert_ok!(TaskBoard::set_claim_deadline(Origin::signed(ISSUER), sooner, Some(5)));
+
+		let hashes = || TaskBoard::all_eligible_tasks(&LEADER).into_iter().map(|t| t.hash).collect::<Vec<_>>();
+		assert_eq!(hashes(), vec![sooner, later, open]);
+		assert_eq!(TaskBoard::eligible_tasks(&LEADER, 0, 1)[0].claim_deadline, Some(5));
+
+		// Past its deadline a task is neither listed nor claimable.
+		System::set_block_number(6);
+		assert_eq!(hashes(), vec![later, open]);
+		assert_noop!(TaskBoard::claim_task(Origin::signed(LEADER), sooner, vec![]), BoardError::<Test>::ClaimDeadlinePassed);
+
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), later, vec![]));
+		assert_eq!(TaskBoard::claim_deadline(later), None);
+		assert_noop!(TaskBoard::set_claim_deadline(Origin::signed(ISSUER), later, None), BoardError::<Test>::TaskKindInvalid);
+	});
+}
+
+#[test]
+fn eligible_tasks_are_paged_and_capped() {
+	ExtBuilder::default().build().execute_with(|| {
+		let max = crate::task_board::MAX_ELIGIBLE_PAGE;
+		let hashes: Vec<H256> = (0..=max).map(|_| publish(ISSUER, 1, 1, 0, vec![])).collect();
+
+		assert_eq!(TaskBoard::eligible_tasks(&LEADER, 0, u32::max_value()).len(), max as usize);
+		let page = TaskBoard::eligible_tasks(&LEADER, 2, 3).into_iter().map(|t| t.hash).collect::<Vec<_>>();
+		assert_eq!(page, hashes[2..5].to_vec());
+		assert_eq!(TaskBoard::eligible_tasks(&LEADER, max, 10).len(), 1);
+		assert!(TaskBoard::eligible_tasks(&LEADER, max + 1, 10).is_empty());
+	});
+}
+
+/// Final storage key of a `map` item, hashed with the default `blake2_256` hasher.
+fn map_key<K: Encode>(module: &[u8], item: &[u8], key: K) -> Vec<u8> {
+	let mut final_key = value_key(module, item);
+	final_key.extend_from_slice(&key.using_encoded(blake2_256));
+	final_key
+}
+
+/// Final storage key of a plain value item.
+fn value_key(module: &[u8], item: &[u8]) -> Vec<u8> {
+	let mut final_key = twox_128(module).to_vec();
+	final_key.extend_from_slice(&twox_128(item));
+	final_key
+}
+
+/// `task_board::Task` as laid out by storage version 0.
+#[derive(Encode)]
+struct TaskV0 {
+	hash: H256,
+	issuer: u64,
+	receivers: Vec<u64>,
+	description: Vec<u8>,
+	judge_pay: u64,
+	pay: u64,
+	min_rep: u32,
+	kind: u8,
+	history: Vec<(u8, u64)>,
+	req_subjects: Vec<u32>,
+	delivery_certificate: H256,
+}
+
+/// `judge_pool::Judgement` as laid out by storage version 0.
+#[derive(Encode)]
+struct JudgementV0 {
+	sender: u64,
+	judges: Vec<(u64, u8)>,
+	kind: u8,
+	threshold: u32,
+	pool_size: u32,
+	pledge_limit: u64,
+}
+
+/// Write a storage version 0 snapshot: a published task missing from its board, a claimed task
+/// filed on the wrong board, one readable and one corrupt judgement and a stored reputation.
+fn load_v0_snapshot(published: H256, claimed: H256, disputed: H256, corrupt: H256) {
+	let task = |hash, receivers, kind: u8| TaskV0 {
+		hash,
+		issuer: ISSUER,
+		receivers,
+		description: b"translate".to_vec(),
+		judge_pay: JUDGE_PAY,
+		pay: PAY,
+		min_rep: 0,
+		kind,
+		history: (0..=kind).map(|k| (k, 0)).collect(),
+		req_subjects: vec![],
+		delivery_certificate: H256::zero(),
+	};
+	unhashed::put(&map_key(b"DeWorkTasks", b"Tasks", 0u64), &task(published, vec![], 0));
+	unhashed::put(&map_key(b"DeWorkTasks", b"Tasks", 1u64), &task(claimed, vec![LEADER], 1));
+	unhashed::put(&map_key(b"DeWorkTasks", b"TaskIndex", published), &0u64);
+	unhashed::put(&map_key(b"DeWorkTasks", b"TaskIndex", claimed), &1u64);
+	unhashed::put(&value_key(b"DeWorkTasks", b"TaskCount"), &2u64);
+	unhashed::put(&map_key(b"DeWorkTasks", b"BoardManager", 0u8), &(vec![claimed], 0u8));
+
+	unhashed::put(&map_key(b"JudgePoolModule", b"Judges", disputed), &JudgementV0 {
+		sender: LEADER,
+		judges: vec![(JUDGES[0], 0)],
+		kind: 1,
+		threshold: 50,
+		pool_size: 3,
+		pledge_limit: JUDGE_PAY,
+	});
+	unhashed::put_raw(&map_key(b"JudgePoolModule", b"Judges", corrupt), &[0xff]);
+
+	unhashed::put(&map_key(b"ReputationModule", b"Reputation", LEADER), &70u32);
+
+	// Storage version 0 predates the version items, which genesis now stamps.
+	for module in [&b"DeWorkTasks"[..], b"JudgePoolModule", b"ReputationModule", b"Identity"].iter() {
+		unhashed::kill(&value_key(module, b"StorageVersion"));
+	}
+}
+
+fn run_upgrade_hooks() {
+	TaskBoard::on_initialize(1);
+	JudgePool::on_initialize(1);
+	Reputation::on_initialize(1);
+	Identity::on_initialize(1);
+}
+
+#[test]
+fn v0_snapshot_is_migrated_to_current_layout() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (published, claimed) = (H256::repeat_byte(1), H256::repeat_byte(2));
+		let (disputed, corrupt) = (H256::repeat_byte(3), H256::repeat_byte(4));
+		load_v0_snapshot(published, claimed, disputed, corrupt);
+
+		run_upgrade_hooks();
+
+		assert_eq!(TaskBoard::storage_version(), crate::task_board::STORAGE_VERSION);
+		assert_eq!(JudgePool::storage_version(), crate::judge_pool::STORAGE_VERSION);
+		assert_eq!(Reputation::storage_version(), crate::reputation::STORAGE_VERSION);
+		assert_eq!(Identity::storage_version(), crate::identity::STORAGE_VERSION);
+
+		assert_eq!(TaskBoard::load_board(TaskKind::Published as u8).inner_board, vec![published]);
+		assert_eq!(TaskBoard::load_board(TaskKind::InDelivery as u8).inner_board, vec![claimed]);
+		assert_boards_consistent();
+		let task = TaskBoard::query_task_by_hash(claimed).unwrap();
+		assert_eq!(task.receivers, vec![LEADER]);
+		assert_eq!(task.pay, PAY);
+		assert_eq!(task.parent, None);
+		assert!(task.children.is_empty() && task.blocked_by.is_empty());
+
+		let judgement = JudgePool::judges(disputed);
+		assert_eq!(judgement.kind, JudgeKind::Processing);
+		assert_eq!(judgement.pool_size, 3);
+		assert!(!unhashed::exists(&map_key(b"JudgePoolModule", b"Judges", corrupt)));
+
+		assert_eq!(Reputation::get_account_reputation_level(&LEADER).score, 70);
+
+		// The repaired board lets the published task move on.
+		assert_ok!(TaskBoard::claim_task(Origin::signed(MEMBER), published, vec![]));
+		assert_boards_consistent();
+	});
+}
+
+#[test]
+fn upgrade_hooks_are_noop_on_current_storage() {
+	ExtBuilder::default().build().execute_with(|| {
+		run_upgrade_hooks();
+		let hash = publish(ISSUER, PAY, JUDGE_PAY, 0, vec![]);
+		assert_ok!(TaskBoard::claim_task(Origin::signed(LEADER), hash, vec![]));
+
+		let root = frame_support::storage_root();
+		run_upgrade_hooks();
+		assert_eq!(root, frame_support::storage_root());
+	});
+}
+
+#[test]
+fn v1_scores_at_the_initial_score_must_be_bootstrapped_again() {
+	ExtBuilder::default().build().execute_with(|| {
+		unhashed::put(&value_key(b"ReputationModule", b"StorageVersion"), &1u32);
+		unhashed::put(&map_key(b"ReputationModule", b"Reputation", LEADER), &70u32);
+		unhashed::put(&map_key(b"ReputationModule", b"Reputation", MEMBER), &50u32);
+
+		System::set_block_number(7);
+		Reputation::on_initialize(7);
+
+		assert_eq!(Reputation::storage_version(), crate::reputation::STORAGE_VERSION);
+		assert!(Reputation::is_bootstrapped(&LEADER));
+		assert_eq!(Reputation::get_account_reputation_level(&LEADER).score, 70);
+		assert_eq!(Reputation::bootstrapped_at(&LEADER), 7);
+		assert!(!Reputation::is_bootstrapped(&MEMBER));
+		assert_eq!(Reputation::get_account_reputation_level(&MEMBER).score, 0);
+	});
+}
+
+/// One step of a randomly generated DeWork session.
+#[derive(Debug, Clone)]
+enum Action {
+	Publish { issuer: usize, pay: u64, judge_pay: u64 },
+	Claim { task: usize, leader: usize, member: Option<usize> },
+	Deliver { task: usize, who: usize },
+	RequestJudge { task: usize, who: usize },
+	Vote { task: usize, judge: usize, result: u32 },
+	Final { task: usize, who: usize },
+}
+
+impl Arbitrary for Action {
+	fn arbitrary<G: Gen>(gen: &mut G) -> Self {
+		let account = |gen: &mut G| (gen.next_u32() % ACCOUNTS.len() as u32) as usize;
+		let task = |gen: &mut G| (gen.next_u32() % 4) as usize;
+		match gen.next_u32() % 6 {
+			0 => Action::Publish {
+				issuer: account(gen),
+				pay: (gen.next_u32() % 300) as u64,
+				judge_pay: (gen.next_u32() % 50) as u64,
+			},
+			1 => Action::Claim {
+				task: task(gen),
+				leader: account(gen),
+				member: if gen.next_u32() % 2 == 0 { Some(account(gen)) } else { None },
+			},
+			2 => Action::Deliver { task: task(gen), who: account(gen) },
+			3 => Action::RequestJudge { task: task(gen), who: account(gen) },
+			4 => Action::Vote {
+				task: task(gen),
+				judge: account(gen),
+				result: gen.next_u32() % 2,
+			},
+			_ => Action::Final { task: task(gen), who: account(gen) },
+		}
+	}
+}
+
+/// Apply `action`, ignoring dispatch errors; the invariants must hold either way.
+fn apply(action: &Action) {
+	let hash_of = |index: usize| -> H256 {
+		let count = TaskBoard::task_count();
+		if count == 0 {
+			H256::zero()
+		} else {
+			TaskBoard::tasks(index as u64 % count).hash
+		}
+	};
+	let _ = match action.clone() {
+		Action::Publish { issuer, pay, judge_pay } =>
+			TaskBoard::publish_task(Origin::signed(ACCOUNTS[issuer]), vec![], 0, pay, judge_pay, vec![]),
+		Action::Claim { task, leader, member } =>
+			TaskBoard::claim_task(
+				Origin::signed(ACCOUNTS[leader]),
+				hash_of(task),
+				member.map(|m| vec![ACCOUNTS[m]]).unwrap_or_default(),
+			),
+		Action::Deliver { task, who } =>
+			TaskBoard::claim_deliver_task(Origin::signed(ACCOUNTS[who]), hash_of(task), H256::repeat_byte(1)),
+		Action::RequestJudge { task, who } =>
+			TaskBoard::request_for_judge(Origin::signed(ACCOUNTS[who]), hash_of(task)),
+		Action::Vote { task, judge, result } =>
+			JudgePool::exec_judgement(Origin::signed(ACCOUNTS[judge]), hash_of(task), result, RATIONALE),
+		Action::Final { task, who } =>
+			TaskBoard::task_to_final(Origin::signed(ACCOUNTS[who]), hash_of(task)),
+	};
+}
+
+#[test]
+fn random_sessions_keep_state_machine_and_funds_consistent() {
+	fn property(actions: Vec<Action>) -> bool {
+		ExtBuilder::default().build().execute_with(|| {
+			for action in actions.iter() {
+				apply(action);
+				assert_boards_consistent();
+				assert_funds_conserved(&all_task_hashes());
+			}
+			true
+		})
+	}
+
+	QuickCheck::new()
+		.tests(200)
+		.quickcheck(property as fn(Vec<Action>) -> bool)
+}
@@ -1,6 +1,8 @@
 use caliburn_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, IdentityConfig,
-	IndicesConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, Balance, BalancesConfig, CouncilConfig, DAYS, DemocracyConfig,
+	DeWorkConfig, DeWorkTasksConfig, GenesisConfig, GrandpaConfig, IdentityConfig, IndicesConfig,
+	JudgePoolModuleConfig, Permill, ReputationModuleConfig, Signature, SudoConfig, SystemConfig,
+	TreasuryConfig, WASM_BINARY,
 };
 use grandpa_primitives::AuthorityId as GrandpaId;
 use sc_service;
@@ -8,6 +10,13 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{Pair, Public, sr25519};
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
+/// Pay of each demo task published at genesis.
+const DEMO_TASK_PAY: Balance = 1_000_000;
+
+/// Reputation of the endowed accounts at genesis and the score judges need, so that any
+/// three endowed accounts can fill a panel.
+const DEMO_SCORE: u32 = 50;
+
 // Note this is the URL for the telemetry server
 //const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
@@ -16,7 +25,8 @@ pub type ChainSpec = sc_service::ChainSpec<GenesisConfig>;
 
 /// The chain specification option. This is expected to come in from the CLI and
 /// is little more than one of a number of alternatives which can easily be converted
-/// from a string (`--chain=...`) into a `ChainSpec`.
+/// from a string (`--chain=...`) into a `ChainSpec`. Any other `--chain` value is read as
+/// the path of a chain spec JSON file, e.g. one written by `build-spec`.
 #[derive(Clone, Debug)]
 pub enum Alternative {
 	/// Whatever the current runtime is, with just Alice as an auth.
@@ -118,6 +128,9 @@ fn testnet_genesis(initial_authorities: Vec<(AuraId, GrandpaId)>,
 				   root_key: AccountId,
 				   endowed_accounts: Vec<AccountId>,
 				   _enable_println: bool) -> GenesisConfig {
+	let num_endowed_accounts = endowed_accounts.len();
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+	let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
 	GenesisConfig {
 		system: Some(SystemConfig {
 			code: WASM_BINARY.to_vec(),
@@ -140,10 +153,60 @@ fn testnet_genesis(initial_authorities: Vec<(AuraId, GrandpaId)>,
 			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
 		}),
 		identity: Some(IdentityConfig {
-			subjects: vec![(1, get_account_id_from_seed::<sr25519::Public>("Alice")), (2, get_account_id_from_seed::<sr25519::Public>("Bob"))],
+			subjects: vec![(1, alice.clone()), (2, bob.clone())],
 			subject_count: 3,
-			rep: vec![(get_account_id_from_seed::<sr25519::Public>("Alice"), 50)],
-			cred_manager: get_account_id_from_seed::<sr25519::Public>("Alice"),
+			rep: vec![(alice.clone(), 50)],
+			cred_manager: alice.clone(),
+			subject_details: vec![
+				(1, b"Rust".to_vec(), b"development".to_vec(), b"Writes and reviews Rust code".to_vec()),
+				(2, b"Translation".to_vec(), b"language".to_vec(), b"Translates documents".to_vec()),
+			],
+			credentials: endowed_accounts.iter().cloned().map(|k| (k, 1)).collect(),
+		}),
+		reputation: Some(ReputationModuleConfig {
+			initial_score: DEMO_SCORE,
+			reputation_step: 5,
+			rating_prior_weight: 2,
+			trusted_subjects: vec![1],
+			bootstrap_bond: DEMO_TASK_PAY,
+			max_linked_accounts: 4,
+			scores: endowed_accounts.iter().cloned().map(|k| {
+				let score = if k == alice { 80 } else if k == bob { 70 } else { DEMO_SCORE };
+				(k, score)
+			}).collect(),
+		}),
+		judge_pool: Some(JudgePoolModuleConfig {
+			judge_size: 3,
+			threshold: DEMO_SCORE,
+			evidence_deposit: DEMO_TASK_PAY / 100,
+			max_evidence: 8,
+			max_statement_len: 256,
+			judge_warmup: DAYS,
+			panel_period: DAYS,
+			max_panels_per_period: 4,
+			voting_period: 7 * DAYS,
+		}),
+		task_board: Some(DeWorkTasksConfig {
+			max_sub_tasks: 16,
+			max_blocked_by: 16,
+			protocol_fee: Permill::from_percent(1),
+			insurance_premium: Permill::from_percent(1),
+			appeal_deposit: DEMO_TASK_PAY / 100,
+			acceptance_period: 7 * DAYS,
+			deadline_notice: DAYS,
+			tasks: vec![
+				(alice.clone(), b"Review the DeWork pallets".to_vec(), 50, DEMO_TASK_PAY, DEMO_TASK_PAY / 10, vec![1]),
+				(bob.clone(), b"Translate the DeWork guide".to_vec(), 0, DEMO_TASK_PAY, DEMO_TASK_PAY / 10, vec![]),
+			],
+		}),
+		dework: Some(DeWorkConfig {
+			legacy_calls_end: 0,
+		}),
+		democracy: Some(DemocracyConfig::default()),
+		collective_Instance1: Some(CouncilConfig {
+			members: endowed_accounts.iter().cloned().take((num_endowed_accounts + 1) / 2).collect(),
+			phantom: Default::default(),
 		}),
+		treasury: Some(TreasuryConfig::default()),
 	}
 }
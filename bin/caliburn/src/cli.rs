@@ -3,12 +3,38 @@ use futures::{future::{select, Map}, FutureExt, TryFutureExt, channel::oneshot,
 use std::cell::RefCell;
 use tokio::runtime::Runtime;
 pub use sc_cli::{VersionInfo, IntoExit, error};
-use sc_cli::{display_role, informant, parse_and_prepare, ParseAndPrepare, NoCustom};
+use sc_cli::{display_role, informant, parse_and_prepare, ParseAndPrepare, NoCustom, GetSharedParams, SharedParams};
+use structopt::StructOpt;
 use sc_service::{AbstractService, Roles as ServiceRoles, Configuration};
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
 use crate::chain_spec;
+use crate::dework::DeWorkCmd;
 use log::info;
 
+/// Custom subcommands.
+#[derive(Debug, Clone, StructOpt)]
+pub enum CustomSubcommands {
+	/// Publish, claim and deliver tasks, and inspect or export DeWork state.
+	#[structopt(name = "dework")]
+	DeWork(DeWorkCmd),
+}
+
+impl GetSharedParams for CustomSubcommands {
+	fn shared_params(&self) -> Option<&SharedParams> {
+		match self {
+			CustomSubcommands::DeWork(cmd) => cmd.shared_params(),
+		}
+	}
+}
+
+/// DeWork options of the node.
+#[derive(Debug, Clone, StructOpt)]
+pub struct DeWorkParams {
+	/// URL the DeWork offchain worker posts deadline and dispute notifications to.
+	#[structopt(long = "dework-webhook", value_name = "URL")]
+	pub dework_webhook: Option<String>,
+}
+
 /// Parse command line arguments into service configuration.
 pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()> where
 	I: IntoIterator<Item = T>,
@@ -16,9 +42,9 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 	E: IntoExit,
 {
 	type Config<T> = Configuration<(), T>;
-	match parse_and_prepare::<NoCustom, NoCustom, _>(&version, "substrate-node", args) {
+	match parse_and_prepare::<CustomSubcommands, DeWorkParams, _>(&version, "substrate-node", args) {
 		ParseAndPrepare::Run(cmd) => cmd.run(load_spec, exit,
-		|exit, _cli_args, _custom_args, config: Config<_>| {
+		|exit, _cli_args, custom_args: DeWorkParams, config: Config<_>| {
 			info!("{}", version.name);
 			info!("  version {}", config.full_version());
 			info!("  by {}, 2017, 2018", version.author);
@@ -34,7 +60,7 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 				),
 				_ => run_until_exit(
 					runtime,
-					service::new_full(config)?,
+					service::new_full(config, custom_args.dework_webhook)?,
 					exit
 				),
 			}
@@ -46,10 +72,16 @@ pub fn run<I, T, E>(args: I, exit: E, version: VersionInfo) -> error::Result<()>
 			Ok(new_full_start!(config).0), load_spec, exit),
 		ParseAndPrepare::CheckBlock(cmd) => cmd.run_with_builder(|config: Config<_>|
 			Ok(new_full_start!(config).0), load_spec, exit),
+		ParseAndPrepare::ExportState(cmd) => cmd.run_with_builder(|config: Config<_>|
+			Ok(new_full_start!(config).0), load_spec),
+		ParseAndPrepare::ImportState(cmd) => cmd.run_with_builder(|config: Config<_>|
+			Ok(new_full_start!(config).0), load_spec),
+		ParseAndPrepare::CheckDb(cmd) => cmd.run_with_builder(|config: Config<_>|
+			Ok(new_full_start!(config).0), load_spec),
 		ParseAndPrepare::PurgeChain(cmd) => cmd.run(load_spec),
 		ParseAndPrepare::RevertChain(cmd) => cmd.run_with_builder(|config: Config<_>|
 			Ok(new_full_start!(config).0), load_spec),
-		ParseAndPrepare::CustomCommand(_) => Ok(())
+		ParseAndPrepare::CustomCommand(CustomSubcommands::DeWork(cmd)) => cmd.run(load_spec, &version),
 	}?;
 
 	Ok(())
@@ -0,0 +1,786 @@
+//! `dework` subcommands: publish, claim and deliver tasks, and inspect or export DeWork state
+//! from a node database or a running node's RPC endpoint.
+
+use std::{collections::BTreeSet, fs::File, io::Write, path::{Path, PathBuf}, sync::Arc};
+use caliburn_runtime::{
+	AccountId, Balance, BlockNumber, Call, Hash, Runtime, SignedExtra, UncheckedExtrinsic,
+	dework, judge_pool, reputation, task_board,
+	judge_pool::{JudgeKind, ResultKind},
+	task_board::{Task, TaskKind},
+	opaque::Block,
+};
+use codec::{Decode, Encode, FullCodec, FullEncode};
+use frame_support::storage::{StorageMap, StorageValue};
+use jsonrpc_core_client::transports::http;
+use sc_cli::{error, SharedParams, VersionInfo};
+use sc_rpc_api::{author::AuthorClient, state::StateClient};
+use sc_service::Configuration;
+use serde_json::{json, Value};
+use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_core::{
+	Blake2Hasher, Bytes, Pair, sr25519,
+	crypto::Ss58Codec,
+	storage::StorageKey,
+};
+use sp_runtime::{
+	MultiSigner,
+	generic::{BlockId, Era, SignedPayload},
+	traits::IdentifyAccount,
+};
+use structopt::StructOpt;
+use tokio::runtime::Runtime as TokioRuntime;
+
+/// The `dework` subcommands.
+#[derive(Debug, Clone, StructOpt)]
+pub enum DeWorkCmd {
+	/// Publish a task, escrowing its pay and judge pay.
+	#[structopt(name = "publish")]
+	Publish(PublishCmd),
+	/// Claim a published task for a team of receivers.
+	#[structopt(name = "claim")]
+	Claim(ClaimCmd),
+	/// Deliver a claimed task with the hash of its delivery certificate.
+	#[structopt(name = "deliver")]
+	Deliver(DeliverCmd),
+	/// List the tasks on one board.
+	#[structopt(name = "list")]
+	List(ListCmd),
+	/// Show the judgement of a disputed task.
+	#[structopt(name = "judgement")]
+	Judgement(JudgementCmd),
+	/// Export every task and the reputation of every account taking part in one.
+	#[structopt(name = "export")]
+	Export(ExportCmd),
+}
+
+impl DeWorkCmd {
+	/// Shared params of the commands that may open the local database.
+	pub fn shared_params(&self) -> Option<&SharedParams> {
+		match self {
+			DeWorkCmd::Publish(_) | DeWorkCmd::Claim(_) | DeWorkCmd::Deliver(_) => None,
+			DeWorkCmd::List(cmd) => Some(&cmd.source.shared_params),
+			DeWorkCmd::Judgement(cmd) => Some(&cmd.source.shared_params),
+			DeWorkCmd::Export(cmd) => Some(&cmd.source.shared_params),
+		}
+	}
+
+	/// Run the command. `load_spec` resolves `--chain` when state is read from the database.
+	pub fn run<S>(self, load_spec: S, version: &VersionInfo) -> error::Result<()> where
+		S: FnOnce(&str) -> Result<Option<crate::chain_spec::ChainSpec>, String>,
+	{
+		match self {
+			DeWorkCmd::Publish(cmd) => cmd.sign.submit(Call::DeWork(dework::Call::publish_task(
+				cmd.description.into_bytes(), cmd.min_rep, cmd.pay, cmd.judge_pay, cmd.subjects,
+			))),
+			DeWorkCmd::Claim(cmd) => cmd.sign.submit(Call::DeWork(dework::Call::claim_task(
+				cmd.task, cmd.members,
+			))),
+			DeWorkCmd::Deliver(cmd) => cmd.sign.submit(Call::DeWork(dework::Call::deliver_task(
+				cmd.task, cmd.certificate,
+			))),
+			DeWorkCmd::List(cmd) => {
+				let mut state = cmd.source.open(load_spec, version)?;
+				let board = state.map::<task_board::BoardManager<Runtime>, _, _>(&(cmd.board as u8))?
+					.unwrap_or_default();
+				let mut table = Table::new(TASK_COLUMNS);
+				for hash in board.inner_board {
+					if let Some(task) = state.task(&hash)? {
+						table.push(task_row(&task));
+					}
+				}
+				table.write(cmd.format, &mut std::io::stdout())?;
+				Ok(())
+			},
+			DeWorkCmd::Judgement(cmd) => {
+				let mut state = cmd.source.open(load_spec, version)?;
+				let mut table = Table::new(JUDGEMENT_COLUMNS);
+				table.push(judgement_row(&mut state, &cmd.task)?);
+				table.write(cmd.format, &mut std::io::stdout())?;
+				Ok(())
+			},
+			DeWorkCmd::Export(cmd) => {
+				let mut state = cmd.source.open(load_spec, version)?;
+				export(&mut state, cmd.format, &cmd.output)
+			},
+		}
+	}
+}
+
+/// Options of the commands that sign and submit an extrinsic.
+#[derive(Debug, Clone, StructOpt)]
+pub struct SignParams {
+	/// HTTP RPC endpoint of the node the extrinsic is submitted to.
+	#[structopt(long = "url", value_name = "URL", default_value = "http://localhost:9933")]
+	pub url: String,
+
+	/// Secret URI of the sr25519 key signing the extrinsic, e.g. `//Alice` or a mnemonic.
+	#[structopt(long = "suri", value_name = "SECRET_URI")]
+	pub suri: String,
+}
+
+/// Options of the commands that read DeWork state.
+#[derive(Debug, Clone, StructOpt)]
+pub struct SourceParams {
+	/// Read through the HTTP RPC endpoint of a running node instead of the local database.
+	#[structopt(long = "url", value_name = "URL")]
+	pub url: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+/// The `dework publish` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct PublishCmd {
+	/// What is to be done, and when it counts as done.
+	#[structopt(long = "description")]
+	pub description: String,
+
+	/// Minimum reputation score a receiver needs to claim the task.
+	#[structopt(long = "min-rep", default_value = "0")]
+	pub min_rep: u32,
+
+	/// Pay of the receivers.
+	#[structopt(long = "pay")]
+	pub pay: Balance,
+
+	/// Pay of the judges if the delivery is disputed.
+	#[structopt(long = "judge-pay")]
+	pub judge_pay: Balance,
+
+	/// Identity subjects a receiver needs a credential for.
+	#[structopt(long = "subject", value_name = "SUBJECT")]
+	pub subjects: Vec<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub sign: SignParams,
+}
+
+/// The `dework claim` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct ClaimCmd {
+	/// Hash of the task.
+	#[structopt(parse(try_from_str = parse_hash))]
+	pub task: Hash,
+
+	/// SS58 addresses of the other team members; the signer leads the team.
+	#[structopt(long = "member", value_name = "ADDRESS", parse(try_from_str = parse_account))]
+	pub members: Vec<AccountId>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub sign: SignParams,
+}
+
+/// The `dework deliver` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct DeliverCmd {
+	/// Hash of the task.
+	#[structopt(parse(try_from_str = parse_hash))]
+	pub task: Hash,
+
+	/// Hash of the delivery certificate.
+	#[structopt(long = "certificate", parse(try_from_str = parse_hash))]
+	pub certificate: Hash,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub sign: SignParams,
+}
+
+/// The `dework list` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct ListCmd {
+	/// Board to list: published, in-delivery, delivered, arbitration, failure or done.
+	#[structopt(parse(try_from_str = parse_board))]
+	pub board: TaskKind,
+
+	/// Output format: json or csv.
+	#[structopt(long = "format", default_value = "json", parse(try_from_str = parse_format))]
+	pub format: Format,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub source: SourceParams,
+}
+
+/// The `dework judgement` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct JudgementCmd {
+	/// Hash of the disputed task.
+	#[structopt(parse(try_from_str = parse_hash))]
+	pub task: Hash,
+
+	/// Output format: json or csv.
+	#[structopt(long = "format", default_value = "json", parse(try_from_str = parse_format))]
+	pub format: Format,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub source: SourceParams,
+}
+
+/// The `dework export` command.
+#[derive(Debug, Clone, StructOpt)]
+pub struct ExportCmd {
+	/// Output format: json or csv.
+	#[structopt(long = "format", default_value = "json", parse(try_from_str = parse_format))]
+	pub format: Format,
+
+	/// Directory `tasks.<format>` and `reputations.<format>` are written to.
+	#[structopt(long = "output", value_name = "DIR", default_value = ".", parse(from_os_str))]
+	pub output: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub source: SourceParams,
+}
+
+/// Output format of the read commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Json,
+	Csv,
+}
+
+impl Format {
+	fn extension(self) -> &'static str {
+		match self {
+			Format::Json => "json",
+			Format::Csv => "csv",
+		}
+	}
+}
+
+fn parse_format(s: &str) -> Result<Format, String> {
+	match s {
+		"json" => Ok(Format::Json),
+		"csv" => Ok(Format::Csv),
+		_ => Err(format!("Unknown format `{}`, expected json or csv", s)),
+	}
+}
+
+fn parse_board(s: &str) -> Result<TaskKind, String> {
+	match s {
+		"published" => Ok(TaskKind::Published),
+		"in-delivery" => Ok(TaskKind::InDelivery),
+		"delivered" => Ok(TaskKind::Deliveryed),
+		"arbitration" => Ok(TaskKind::Arbitration),
+		"failure" => Ok(TaskKind::Failure),
+		"done" => Ok(TaskKind::Done),
+		_ => Err(format!(
+			"Unknown board `{}`, expected published, in-delivery, delivered, arbitration, failure or done", s,
+		)),
+	}
+}
+
+fn board_name(kind: &TaskKind) -> &'static str {
+	match kind {
+		TaskKind::Published => "published",
+		TaskKind::InDelivery => "in-delivery",
+		TaskKind::Deliveryed => "delivered",
+		TaskKind::Arbitration => "arbitration",
+		TaskKind::Failure => "failure",
+		TaskKind::Done => "done",
+	}
+}
+
+fn parse_hash(s: &str) -> Result<Hash, String> {
+	s.trim_start_matches("0x").parse().map_err(|e| format!("Invalid hash `{}`: {:?}", s, e))
+}
+
+fn parse_account(s: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(s).map_err(|e| format!("Invalid address `{}`: {:?}", s, e))
+}
+
+/// A node extrinsics are signed for and submitted to.
+trait Node {
+	/// Raw storage value at `key` of the best block.
+	fn storage(&mut self, key: Vec<u8>) -> Result<Option<Vec<u8>>, String>;
+
+	/// Spec version of the runtime of the best block.
+	fn spec_version(&mut self) -> Result<u32, String>;
+
+	/// Submit a signed extrinsic, returning its hash.
+	fn submit_extrinsic(&mut self, xt: Bytes) -> Result<Hash, String>;
+}
+
+/// Blocking access to the HTTP RPC endpoint of a node.
+struct Rpc {
+	runtime: TokioRuntime,
+	state: StateClient<Hash>,
+	author: AuthorClient<Hash, Hash>,
+}
+
+impl Rpc {
+	fn connect(url: &str) -> Result<Self, String> {
+		let mut runtime = TokioRuntime::new().map_err(|e| format!("{:?}", e))?;
+		let state = runtime.block_on(http::connect(url))
+			.map_err(|e| format!("Error connecting to `{}`: {:?}", url, e))?;
+		let author = runtime.block_on(http::connect(url))
+			.map_err(|e| format!("Error connecting to `{}`: {:?}", url, e))?;
+		Ok(Rpc { runtime, state, author })
+	}
+}
+
+impl Node for Rpc {
+	fn storage(&mut self, key: Vec<u8>) -> Result<Option<Vec<u8>>, String> {
+		self.runtime.block_on(self.state.storage(StorageKey(key), None))
+			.map(|data| data.map(|data| data.0))
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	fn spec_version(&mut self) -> Result<u32, String> {
+		self.runtime.block_on(self.state.runtime_version(None))
+			.map(|version| version.spec_version)
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	fn submit_extrinsic(&mut self, xt: Bytes) -> Result<Hash, String> {
+		self.runtime.block_on(self.author.submit_extrinsic(xt))
+			.map_err(|e| format!("Error submitting extrinsic: {:?}", e))
+	}
+}
+
+/// Reads raw storage values of the chain's best block.
+type Reader = Box<dyn FnMut(Vec<u8>) -> Result<Option<Vec<u8>>, String>>;
+
+/// DeWork storage, decoded.
+struct State {
+	read: Reader,
+}
+
+impl SourceParams {
+	fn open<S>(self, load_spec: S, version: &VersionInfo) -> error::Result<State> where
+		S: FnOnce(&str) -> Result<Option<crate::chain_spec::ChainSpec>, String>,
+	{
+		Ok(match self.url {
+			Some(url) => {
+				let mut rpc = Rpc::connect(&url)?;
+				State { read: Box::new(move |key| rpc.storage(key)) }
+			},
+			None => {
+				let config: Configuration<(), _> = sc_cli::create_config_with_db_path(
+					load_spec, &self.shared_params, version,
+				)?;
+				State::from_client(new_full_start!(config).0.client().clone())
+			},
+		})
+	}
+}
+
+impl State {
+	/// Read the best block of `client`.
+	fn from_client<B, E, RA>(client: Arc<sc_client::Client<B, E, Block, RA>>) -> Self where
+		B: sc_client_api::backend::Backend<Block, Blake2Hasher> + 'static,
+		E: sc_client::CallExecutor<Block, Blake2Hasher> + 'static,
+		RA: 'static,
+	{
+		let at = BlockId::Hash(client.chain_info().best_hash);
+		State {
+			read: Box::new(move |key| client.storage(&at, &StorageKey(key))
+				.map(|data| data.map(|data| data.0))
+				.map_err(|e| format!("{:?}", e))),
+		}
+	}
+
+	fn get<T: Decode>(&mut self, key: Vec<u8>) -> Result<Option<T>, String> {
+		decode((self.read)(key)?)
+	}
+
+	/// The plain storage item `S`.
+	fn value<S: StorageValue<V>, V: FullCodec>(&mut self) -> Result<Option<V>, String> {
+		self.get(S::hashed_key().to_vec())
+	}
+
+	/// The value at `key` of the storage map `S`.
+	fn map<S: StorageMap<K, V>, K: FullEncode, V: FullCodec>(&mut self, key: &K) -> Result<Option<V>, String> {
+		self.get(S::hashed_key_for(key))
+	}
+
+	fn task(&mut self, hash: &Hash) -> Result<Option<Task<Runtime>>, String> {
+		match self.map::<task_board::TaskIndex<Runtime>, _, _>(hash)? {
+			Some(index) => self.map::<task_board::Tasks<Runtime>, _, _>(&index),
+			None => Ok(None),
+		}
+	}
+}
+
+impl SignParams {
+	/// Sign `call` with the key of `suri` and submit it to `url`.
+	fn submit(&self, call: Call) -> error::Result<()> {
+		let mut rpc = Rpc::connect(&self.url)?;
+		let hash = submit(&mut rpc, &self.suri, call)?;
+		println!("{:?}", hash);
+		Ok(())
+	}
+}
+
+/// Sign `call` with the key of `suri` and submit it to `node`.
+fn submit(node: &mut dyn Node, suri: &str, call: Call) -> Result<Hash, String> {
+	let pair = sr25519::Pair::from_string(suri, None)
+		.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+	let signer: AccountId = MultiSigner::from(pair.public()).into_account();
+
+	let nonce = decode(node.storage(system::AccountNonce::<Runtime>::hashed_key_for(&signer))?)?
+		.unwrap_or_default();
+	let genesis: Hash = decode(node.storage(system::BlockHash::<Runtime>::hashed_key_for(0 as BlockNumber))?)?
+		.ok_or_else(|| "The node has no genesis block hash".to_string())?;
+	let spec_version = node.spec_version()?;
+
+	let extra: SignedExtra = (
+		system::CheckVersion::new(),
+		system::CheckGenesis::new(),
+		system::CheckEra::from(Era::Immortal),
+		system::CheckNonce::from(nonce),
+		system::CheckWeight::new(),
+		transaction_payment::ChargeTransactionPayment::from(0),
+		dework::CheckLegacyCall::new(),
+	);
+	let payload = SignedPayload::from_raw(call, extra, (spec_version, genesis, genesis, (), (), (), ()));
+	let signature = payload.using_encoded(|payload| pair.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	let xt = UncheckedExtrinsic::new_signed(call, signer.into(), signature.into(), extra);
+
+	node.submit_extrinsic(Bytes(xt.encode()))
+}
+
+fn decode<T: Decode>(raw: Option<Vec<u8>>) -> Result<Option<T>, String> {
+	raw.map(|raw| T::decode(&mut &raw[..]).map_err(|e| format!("Undecodable storage value: {:?}", e)))
+		.transpose()
+}
+
+/// Rows of named fields, written as a JSON array of objects or as CSV with a header line.
+struct Table {
+	columns: &'static [&'static str],
+	rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+	fn new(columns: &'static [&'static str]) -> Self {
+		Table { columns, rows: Vec::new() }
+	}
+
+	fn push(&mut self, row: Vec<Value>) {
+		self.rows.push(row);
+	}
+
+	fn write(&self, format: Format, out: &mut dyn Write) -> Result<(), String> {
+		let io = |e: std::io::Error| e.to_string();
+		match format {
+			Format::Json => {
+				let rows: Vec<Value> = self.rows.iter()
+					.map(|row| Value::Object(
+						self.columns.iter().map(|c| c.to_string()).zip(row.iter().cloned()).collect()
+					))
+					.collect();
+				let json = serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())?;
+				writeln!(out, "{}", json).map_err(io)
+			},
+			Format::Csv => {
+				writeln!(out, "{}", self.columns.join(",")).map_err(io)?;
+				for row in &self.rows {
+					let fields: Vec<String> = row.iter().map(csv_field).collect();
+					writeln!(out, "{}", fields.join(",")).map_err(io)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+/// A JSON value as one CSV field; arrays are joined with `;`.
+fn csv_field(value: &Value) -> String {
+	fn plain(value: &Value) -> String {
+		match value {
+			Value::String(s) => s.clone(),
+			Value::Null => String::new(),
+			Value::Array(items) => items.iter().map(plain).collect::<Vec<_>>().join(";"),
+			other => other.to_string(),
+		}
+	}
+	let field = plain(value);
+	if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field
+	}
+}
+
+fn account(who: &AccountId) -> Value {
+	Value::String(who.to_ss58check())
+}
+
+fn hash(hash: &Hash) -> Value {
+	Value::String(format!("{:?}", hash))
+}
+
+/// Balances are strings: JSON numbers cannot hold every `u128`.
+fn balance(balance: Balance) -> Value {
+	Value::String(balance.to_string())
+}
+
+const TASK_COLUMNS: &[&str] = &[
+	"hash", "status", "issuer", "receivers", "description", "pay", "judge_pay", "min_rep",
+	"subjects", "delivery_certificate", "parent", "children", "blocked_by",
+];
+
+fn task_row(task: &Task<Runtime>) -> Vec<Value> {
+	vec![
+		hash(&task.hash),
+		json!(board_name(&task.kind)),
+		account(&task.issuer),
+		Value::Array(task.receivers.iter().map(account).collect()),
+		json!(String::from_utf8_lossy(&task.description)),
+		balance(task.pay),
+		balance(task.judge_pay),
+		json!(task.min_rep),
+		json!(task.req_subjects),
+		hash(&task.delivery_certificate),
+		task.parent.as_ref().map_or(Value::Null, hash),
+		Value::Array(task.children.iter().map(hash).collect()),
+		Value::Array(task.blocked_by.iter().map(hash).collect()),
+	]
+}
+
+const JUDGEMENT_COLUMNS: &[&str] = &[
+	"task", "status", "sender", "pool_size", "threshold", "approvals", "rejections",
+	"parties", "evidence", "started", "voting_deadline",
+];
+
+fn judgement_row(state: &mut State, task: &Hash) -> Result<Vec<Value>, String> {
+	let judgement = state.map::<judge_pool::Judges<Runtime>, _, _>(task)?
+		.ok_or_else(|| format!("Task {:?} was never disputed", task))?;
+	let parties = state.map::<judge_pool::Parties<Runtime>, _, _>(task)?.unwrap_or_default();
+	let evidence = state.map::<judge_pool::Evidences<Runtime>, _, _>(task)?.unwrap_or_default();
+	let started = state.map::<judge_pool::JudgementStarted<Runtime>, _, _>(task)?;
+	let voting_period = state.value::<judge_pool::VotingPeriod<Runtime>, _>()?.unwrap_or_default();
+
+	let votes = |result: ResultKind| Value::Array(judgement.judges.iter()
+		.filter(|(_, vote)| *vote == result)
+		.map(|(judge, _)| account(judge))
+		.collect());
+	// Mirrors `judge_pool::Module::voting_deadline`.
+	let deadline = match (judgement.kind, started) {
+		(JudgeKind::Processing, Some(started)) if voting_period > 0 => json!(started.saturating_add(voting_period)),
+		_ => Value::Null,
+	};
+	Ok(vec![
+		hash(task),
+		json!(match judgement.kind {
+			JudgeKind::UnCreated => "uncreated",
+			JudgeKind::Processing => "processing",
+			JudgeKind::Done => "done",
+		}),
+		account(&judgement.sender),
+		json!(judgement.pool_size),
+		json!(judgement.threshold),
+		votes(ResultKind::ResultTrue),
+		votes(ResultKind::ResultFalse),
+		Value::Array(parties.iter().map(account).collect()),
+		Value::Array(evidence.iter().map(|e| hash(&e.content)).collect()),
+		started.map_or(Value::Null, |started| json!(started)),
+		deadline,
+	])
+}
+
+const REPUTATION_COLUMNS: &[&str] = &[
+	"account", "linked", "score", "issuer_ratings", "issuer_rating_total", "worker_ratings", "worker_rating_total",
+];
+
+/// Write every task and the reputation of every issuer, receiver and judge to `dir`.
+///
+/// Linked accounts share the score of their primary account, so each primary account gets one
+/// row listing its linked accounts, with the ratings of all of them added up. Reputations are
+/// keyed by hashed account ids that cannot be enumerated, so accounts that never took part in a
+/// task, and are not linked to one that did, are not exported.
+fn export(state: &mut State, format: Format, dir: &Path) -> error::Result<()> {
+	let count = state.value::<task_board::TaskCount<Runtime>, _>()?.unwrap_or_default();
+	let mut tasks = Table::new(TASK_COLUMNS);
+	let mut accounts = Vec::new();
+	for index in 0..count {
+		let task = match state.map::<task_board::Tasks<Runtime>, _, _>(&index)? {
+			Some(task) => task,
+			None => continue,
+		};
+		accounts.push(task.issuer.clone());
+		accounts.extend(task.receivers.iter().cloned());
+		if let Some(judgement) = state.map::<judge_pool::Judges<Runtime>, _, _>(&task.hash)? {
+			accounts.extend(judgement.judges.into_iter().map(|(judge, _)| judge));
+		}
+		tasks.push(task_row(&task));
+	}
+
+	let mut primaries = BTreeSet::new();
+	for who in accounts {
+		primaries.insert(state.map::<reputation::PrimaryOf<Runtime>, _, _>(&who)?.unwrap_or(who));
+	}
+
+	let mut reputations = Table::new(REPUTATION_COLUMNS);
+	for primary in &primaries {
+		let linked = state.map::<reputation::Linked<Runtime>, _, _>(primary)?.unwrap_or_default();
+		let level = state.map::<reputation::Reputation<Runtime>, _, _>(primary)?.unwrap_or_default();
+		let (mut issuer_count, mut issuer_total, mut worker_count, mut worker_total) = (0u32, 0u32, 0u32, 0u32);
+		for who in Some(primary).into_iter().chain(linked.iter()) {
+			let issuer = state.map::<reputation::IssuerRatings<Runtime>, _, _>(who)?.unwrap_or_default();
+			let worker = state.map::<reputation::WorkerRatings<Runtime>, _, _>(who)?.unwrap_or_default();
+			issuer_count = issuer_count.saturating_add(issuer.count);
+			issuer_total = issuer_total.saturating_add(issuer.total);
+			worker_count = worker_count.saturating_add(worker.count);
+			worker_total = worker_total.saturating_add(worker.total);
+		}
+		reputations.push(vec![
+			account(primary),
+			Value::Array(linked.iter().map(account).collect()),
+			json!(level.score),
+			json!(issuer_count),
+			json!(issuer_total),
+			json!(worker_count),
+			json!(worker_total),
+		]);
+	}
+
+	for (name, table) in &[("tasks", &tasks), ("reputations", &reputations)] {
+		let path = dir.join(format!("{}.{}", name, format.extension()));
+		let mut file = File::create(&path).map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+		table.write(format, &mut file)?;
+		println!("{} rows written to {}", table.rows.len(), path.display());
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use caliburn_runtime::{BalancesCall, RuntimeApi, SLOT_DURATION, TimestampCall};
+	use sp_consensus::BlockOrigin;
+	use sp_runtime::{OpaqueExtrinsic, Storage, BuildStorage};
+	use substrate_test_client::{
+		Backend, ClientExt, GenesisInit, LocalCallExecutor, NativeExecutor, TestClientBuilder,
+	};
+
+	type TestClient = sc_client::Client<
+		Backend<Block>,
+		LocalCallExecutor<Backend<Block>, NativeExecutor<crate::service::Executor>>,
+		Block,
+		RuntimeApi,
+	>;
+
+	/// Genesis of the development chain.
+	#[derive(Default)]
+	struct DevGenesis;
+
+	impl GenesisInit for DevGenesis {
+		fn genesis_storage(&self) -> Storage {
+			crate::chain_spec::Alternative::Development.load()
+				.and_then(|spec| spec.build_storage())
+				.expect("The development chain spec builds")
+		}
+	}
+
+	/// Seals every submitted extrinsic in a block of its own.
+	struct TestNode {
+		client: Arc<TestClient>,
+	}
+
+	impl TestNode {
+		fn new() -> Self {
+			let (client, _) = TestClientBuilder::<_, _, DevGenesis>::default()
+				.build_with_native_executor::<Block, RuntimeApi, _>(None);
+			let mut node = TestNode { client: Arc::new(client) };
+			// `System::BlockHash(0)` only holds the genesis hash once block 1 is imported.
+			node.seal(None);
+			node
+		}
+
+		fn seal(&mut self, xt: Option<OpaqueExtrinsic>) {
+			let now = (self.client.chain_info().best_number as u64 + 1) * SLOT_DURATION;
+			let mut builder = self.client.new_block(Default::default()).unwrap();
+			builder.push(opaque(UncheckedExtrinsic::new_unsigned(Call::Timestamp(TimestampCall::set(now))))).unwrap();
+			if let Some(xt) = xt {
+				builder.push(xt).unwrap();
+			}
+			let block = builder.bake().unwrap();
+			self.client.import(BlockOrigin::Own, block).unwrap();
+		}
+
+		fn submit(&mut self, suri: &str, call: Call) {
+			submit(self, suri, call).unwrap();
+		}
+
+		fn state(&self) -> State {
+			State::from_client(self.client.clone())
+		}
+	}
+
+	impl Node for TestNode {
+		fn storage(&mut self, key: Vec<u8>) -> Result<Option<Vec<u8>>, String> {
+			(self.state().read)(key)
+		}
+
+		fn spec_version(&mut self) -> Result<u32, String> {
+			let at = BlockId::Hash(self.client.chain_info().best_hash);
+			self.client.runtime_version_at(&at)
+				.map(|version| version.spec_version)
+				.map_err(|e| format!("{:?}", e))
+		}
+
+		fn submit_extrinsic(&mut self, xt: Bytes) -> Result<Hash, String> {
+			let hash = sp_core::hashing::blake2_256(&xt.0).into();
+			let xt = Decode::decode(&mut &xt.0[..]).map_err(|e| format!("{:?}", e))?;
+			self.seal(Some(xt));
+			Ok(hash)
+		}
+	}
+
+	fn opaque(xt: UncheckedExtrinsic) -> OpaqueExtrinsic {
+		Decode::decode(&mut &xt.encode()[..]).expect("Extrinsics encode as opaque extrinsics")
+	}
+
+	fn account_of(suri: &str) -> AccountId {
+		MultiSigner::from(sr25519::Pair::from_string(suri, None).unwrap().public()).into_account()
+	}
+
+	#[test]
+	fn submitted_claims_are_exported_with_linked_reputations() {
+		let mut node = TestNode::new();
+		let (alice, bob, charlie) = (account_of("//Alice"), account_of("//Bob"), account_of("//Charlie"));
+
+		node.submit("//Alice", Call::Balances(BalancesCall::transfer(charlie.clone().into(), 1 << 50)));
+		node.submit("//Alice", Call::DeWork(dework::Call::link_account(charlie.clone())));
+		node.submit("//Charlie", Call::DeWork(dework::Call::accept_link(alice.clone())));
+
+		let mut state = node.state();
+		let published = state.map::<task_board::BoardManager<Runtime>, _, _>(&(TaskKind::Published as u8))
+			.unwrap().unwrap_or_default().inner_board;
+		let mut bobs = None;
+		for hash in published {
+			let task = state.task(&hash).unwrap().unwrap();
+			if task.issuer == bob {
+				bobs = Some(hash);
+			}
+		}
+		let task = bobs.expect("Bob publishes a demo task at genesis");
+		node.submit("//Charlie", Call::DeWork(dework::Call::claim_task(task, vec![])));
+
+		let claimed = node.state().task(&task).unwrap().unwrap();
+		assert_eq!(claimed.kind, TaskKind::InDelivery);
+		assert_eq!(claimed.receivers, vec![charlie.clone()]);
+
+		let dir = tempfile::tempdir().unwrap();
+		export(&mut node.state(), Format::Json, dir.path()).unwrap();
+		let read = |name: &str| -> Vec<Value> {
+			let file = File::open(dir.path().join(name)).unwrap();
+			serde_json::from_reader(file).unwrap()
+		};
+
+		assert_eq!(read("tasks.json").len(), 2);
+		let reputations = read("reputations.json");
+		let alice_row = reputations.iter().find(|row| row["account"] == account(&alice)).unwrap();
+		assert_eq!(alice_row["linked"], json!([charlie.to_ss58check()]));
+		assert_eq!(alice_row["score"], json!(80));
+		assert!(reputations.iter().all(|row| row["account"] != account(&charlie)));
+	}
+}
@@ -7,6 +7,8 @@ mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
+mod dework;
+mod rpc;
 
 pub use sc_cli::{VersionInfo, IntoExit, error};
 
@@ -0,0 +1,188 @@
+//! DeWork RPC methods: the `Published` tasks an account can claim, on request or as they appear.
+
+use std::{collections::HashSet, sync::Arc};
+use caliburn_runtime::{AccountId, Balance, BlockNumber, DeWorkApi as DeWorkRuntimeApi, Hash, opaque::Block};
+use futures::{future, StreamExt, TryStreamExt};
+use futures01::{Future, Sink, Stream, stream};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
+use log::warn;
+use sc_client_api::BlockchainEvents;
+use sc_rpc_api::Subscriptions;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::U256;
+use sp_runtime::{generic::BlockId, traits::ProvideRuntimeApi};
+
+pub use self::gen_client::Client as DeWorkClient;
+
+/// Tasks returned when no `limit` is given.
+const DEFAULT_LIMIT: u32 = 20;
+
+/// A `Published` task an account can claim.
+///
+/// Balances are `U256` so that every `u128` survives JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EligibleTask {
+	pub hash: Hash,
+	pub pay: U256,
+	pub judge_pay: U256,
+	pub min_rep: u32,
+	pub req_subjects: Vec<u32>,
+	/// Timestamp, in milliseconds, of the block the task was published in.
+	pub published_at: u64,
+	/// Last block the task can be claimed in, if its issuer set one.
+	pub claim_deadline: Option<BlockNumber>,
+}
+
+impl From<caliburn_runtime::task_board::EligibleTask<Hash, Balance, u64, BlockNumber>> for EligibleTask {
+	fn from(task: caliburn_runtime::task_board::EligibleTask<Hash, Balance, u64, BlockNumber>) -> Self {
+		EligibleTask {
+			hash: task.hash,
+			pay: task.pay.into(),
+			judge_pay: task.judge_pay.into(),
+			min_rep: task.min_rep,
+			req_subjects: task.req_subjects,
+			published_at: task.published_at,
+			claim_deadline: task.claim_deadline,
+		}
+	}
+}
+
+/// DeWork RPC methods.
+#[rpc]
+pub trait DeWorkApi<BlockHash> {
+	/// RPC metadata
+	type Metadata;
+
+	/// The `Published` tasks `who` can claim, best paid first, skipping `offset` and returning
+	/// at most `limit` of them.
+	#[rpc(name = "dework_eligibleTasks")]
+	fn eligible_tasks(
+		&self,
+		who: AccountId,
+		offset: Option<u32>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<EligibleTask>>;
+
+	/// Every task `who` can claim at the best block, then the ones it becomes able to claim
+	/// as new best blocks are imported.
+	#[pubsub(subscription = "dework_eligibleTasks", subscribe, name = "dework_subscribeEligibleTasks")]
+	fn subscribe_eligible_tasks(&self, metadata: Self::Metadata, subscriber: Subscriber<Vec<EligibleTask>>, who: AccountId);
+
+	/// Unsubscribe from eligible task notifications.
+	#[pubsub(subscription = "dework_eligibleTasks", unsubscribe, name = "dework_unsubscribeEligibleTasks")]
+	fn unsubscribe_eligible_tasks(&self, metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+/// Implements `DeWorkApi` with the runtime's `DeWorkApi`.
+pub struct DeWork<C> {
+	client: Arc<C>,
+	subscriptions: Subscriptions,
+}
+
+impl<C> DeWork<C> {
+	/// Create new `DeWork` over `client`.
+	///
+	/// Subscriptions run on the executor of the RPC server thread they are made on.
+	pub fn new(client: Arc<C>) -> Self {
+		DeWork {
+			client,
+			subscriptions: Subscriptions::new(Arc::new(tokio::executor::DefaultExecutor::current())),
+		}
+	}
+}
+
+/// Query the runtime for the tasks `who` can claim at `at`.
+fn query<C>(client: &C, who: &AccountId, offset: u32, limit: u32, at: Hash) -> Result<Vec<EligibleTask>> where
+	C: ProvideRuntimeApi,
+	C::Api: DeWorkRuntimeApi<Block, AccountId, Hash, Balance, u64, BlockNumber>,
+{
+	client.runtime_api()
+		.eligible_tasks(&BlockId::hash(at), who.clone(), offset, limit)
+		.map(|tasks| tasks.into_iter().map(Into::into).collect())
+		.map_err(query_error)
+}
+
+/// Every task `who` can claim at `at`, from a single scan of the published tasks.
+fn query_all<C>(client: &C, who: &AccountId, at: Hash) -> Result<Vec<EligibleTask>> where
+	C: ProvideRuntimeApi,
+	C::Api: DeWorkRuntimeApi<Block, AccountId, Hash, Balance, u64, BlockNumber>,
+{
+	client.runtime_api()
+		.all_eligible_tasks(&BlockId::hash(at), who.clone())
+		.map(|tasks| tasks.into_iter().map(Into::into).collect())
+		.map_err(query_error)
+}
+
+fn query_error<E: std::fmt::Debug>(e: E) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query eligible tasks.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C> DeWorkApi<Hash> for DeWork<C> where
+	C: ProvideRuntimeApi + HeaderBackend<Block> + BlockchainEvents<Block> + Send + Sync + 'static,
+	C::Api: DeWorkRuntimeApi<Block, AccountId, Hash, Balance, u64, BlockNumber>,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn eligible_tasks(
+		&self,
+		who: AccountId,
+		offset: Option<u32>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> Result<Vec<EligibleTask>> {
+		// If the block hash is not supplied assume the best block.
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		query(&*self.client, &who, offset.unwrap_or(0), limit.unwrap_or(DEFAULT_LIMIT), at)
+	}
+
+	fn subscribe_eligible_tasks(&self, _metadata: Self::Metadata, subscriber: Subscriber<Vec<EligibleTask>>, who: AccountId) {
+		let client = self.client.clone();
+		let initial = query_all(&*client, &who, client.info().best_hash);
+		let mut known: HashSet<Hash> = initial.iter().flatten().map(|task| task.hash).collect();
+
+		let stream = client.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| Ok::<_, ()>(notification.hash))
+			.compat()
+			.filter_map(move |hash| match query_all(&*client, &who, hash) {
+				Ok(tasks) => {
+					// Only push the tasks that were not eligible at the previous best block.
+					let new: Vec<EligibleTask> = tasks.iter().filter(|task| !known.contains(&task.hash)).cloned().collect();
+					known = tasks.iter().map(|task| task.hash).collect();
+					if new.is_empty() { None } else { Some(Ok(new)) }
+				},
+				Err(e) => Some(Err(e)),
+			});
+
+		self.subscriptions.add(subscriber, |sink| {
+			sink
+				.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream::iter_result(vec![Ok(initial)]).chain(stream))
+				// we ignore the resulting Stream (if the first stream is over we are unsubscribed)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_eligible_tasks(&self, _metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+}
+
+/// Instantiate the RPC extensions of a full node.
+pub fn create<C>(client: Arc<C>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
+	C: ProvideRuntimeApi + HeaderBackend<Block> + BlockchainEvents<Block> + Send + Sync + 'static,
+	C::Api: DeWorkRuntimeApi<Block, AccountId, Hash, Balance, u64, BlockNumber>,
+{
+	let mut io = jsonrpc_core::IoHandler::default();
+	io.extend_with(DeWorkApi::to_delegate(DeWork::new(client)));
+	io
+}
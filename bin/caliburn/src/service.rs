@@ -2,11 +2,13 @@
 
 use std::sync::Arc;
 use std::time::Duration;
+use futures::{FutureExt, TryFutureExt};
 use sc_client::LongestChain;
 use caliburn_runtime::{self, GenesisConfig, opaque::Block, RuntimeApi};
 use sc_service::{error::{Error as ServiceError}, AbstractService, Configuration, ServiceBuilder};
 use sp_inherents::InherentDataProviders;
 use sc_network::{construct_simple_protocol};
+use sp_core::offchain::OffchainStorage;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
@@ -20,6 +22,18 @@ native_executor_instance!(
 	caliburn_runtime::native_version,
 );
 
+/// The account sending a transaction, from the `(AccountId, Index)` tag `CheckNonce` gives it.
+pub fn sender_of(provides: &[Vec<u8>]) -> Option<&[u8]> {
+	use codec::Decode;
+
+	provides.iter()
+		.find(|tag| {
+			let mut input = &tag[..];
+			<(caliburn_runtime::AccountId, caliburn_runtime::Index)>::decode(&mut input).is_ok() && input.is_empty()
+		})
+		.map(|tag| &tag[..tag.len() - std::mem::size_of::<caliburn_runtime::Index>()])
+}
+
 construct_simple_protocol! {
 	/// Demo protocol attachment for substrate.
 	pub struct NodeProtocol where Block = Block { }
@@ -41,9 +55,11 @@ macro_rules! new_full_start {
 				Ok(sc_client::LongestChain::new(backend.clone()))
 			})?
 			.with_transaction_pool(|config, client, _fetcher| {
-				let pool_api = sc_transaction_pool::FullChainApi::new(client.clone());
+				let pool_api = sc_transaction_pool::FullChainApi::new(client.clone())
+					.with_sender_of(crate::service::sender_of);
 				let pool = sc_transaction_pool::BasicPool::new(config, pool_api);
-				let maintainer = sc_transaction_pool::FullBasicPoolMaintainer::new(pool.pool().clone(), client);
+				let maintainer = sc_transaction_pool::FullBasicPoolMaintainer::new(pool.pool().clone(), client)
+					.with_journal(pool.journal().cloned());
 				let maintainable_pool = sp_transaction_pool::MaintainableTransactionPool::new(pool, maintainer);
 				Ok(maintainable_pool)
 			})?
@@ -73,6 +89,11 @@ macro_rules! new_full_start {
 				import_setup = Some((grandpa_block_import, grandpa_link));
 
 				Ok(import_queue)
+			})?
+			.with_rpc_extensions(|client, _pool, _backend, _fetcher, _remote_blockchain|
+				-> Result<jsonrpc_core::IoHandler<sc_rpc::Metadata>, sc_service::Error>
+			{
+				Ok(crate::rpc::create(client))
 			})?;
 
 		(builder, import_setup, inherent_data_providers)
@@ -80,7 +101,9 @@ macro_rules! new_full_start {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full<C: Send + Default + 'static>(config: Configuration<C, GenesisConfig>)
+///
+/// `dework_webhook` is stored for the DeWork offchain worker to post its notifications to.
+pub fn new_full<C: Send + Default + 'static>(config: Configuration<C, GenesisConfig>, dework_webhook: Option<String>)
 	-> Result<impl AbstractService, ServiceError>
 {
 	let is_authority = config.roles.is_authority();
@@ -105,6 +128,25 @@ pub fn new_full<C: Send + Default + 'static>(config: Configuration<C, GenesisCon
 		)?
 		.build()?;
 
+	// Resubmit what was pending at the last shutdown; stale transactions are dropped.
+	let best = sp_runtime::generic::BlockId::hash(service.client().chain_info().best_hash);
+	let restore_journal = service.transaction_pool().pool().restore_journal(&best)
+		.map(|result| if let Err(e) = result {
+			log::warn!("Unable to restore the transaction pool journal: {:?}", e);
+		});
+	service.spawn_task(restore_journal.unit_error().boxed().compat());
+
+	if let Some(url) = dework_webhook {
+		match service.client().offchain_storage() {
+			Some(mut storage) => storage.set(
+				sp_offchain::STORAGE_PREFIX,
+				caliburn_runtime::DEWORK_WEBHOOK_KEY,
+				url.as_bytes(),
+			),
+			None => log::warn!("No offchain storage, DeWork notifications are disabled."),
+		}
+	}
+
 	if participates_in_consensus {
 		let proposer = sc_basic_authority::ProposerFactory {
 			client: service.client(),
@@ -29,7 +29,7 @@ use crate::{
 		Backend as BlockchainBackend, well_known_cache_keys
 	},
 	light::RemoteBlockchain,
-	UsageInfo,
+	UsageInfo, StateRetention,
 };
 use sp_blockchain;
 use sp_consensus::BlockOrigin;
@@ -265,6 +265,11 @@ pub trait Backend<Block, H>: AuxStore + Send + Sync where
 	/// Returns current usage statistics.
 	fn usage_info(&self) -> Option<UsageInfo>;
 
+	/// Returns the historical states this backend keeps, if it prunes them in a known way.
+	fn state_retention(&self) -> Option<StateRetention> {
+		None
+	}
+
 	/// Returns reference to changes trie storage.
 	fn changes_trie_storage(&self) -> Option<&Self::ChangesTrieStorage>;
 
@@ -150,6 +150,16 @@ impl fmt::Display for UsageInfo {
 	}
 }
 
+/// Historical states a backend is able to serve.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StateRetention {
+	/// The state of the finalized blocks below this number is pruned, checkpoints excepted.
+	/// `None` if no state is ever pruned.
+	pub pruned_below: Option<u64>,
+	/// The state of the finalized blocks whose number is a multiple of this is never pruned.
+	pub checkpoint_interval: Option<u32>,
+}
+
 /// Summary of an imported block
 #[derive(Clone, Debug)]
 pub struct BlockImportNotification<Block: BlockT> {
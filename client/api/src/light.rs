@@ -73,6 +73,23 @@ pub struct RemoteReadRequest<Header: HeaderT> {
 	pub retry_count: Option<usize>,
 }
 
+/// Remote storage keys read request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RemoteReadKeysRequest<Header: HeaderT> {
+	/// Read at state of given block.
+	pub block: Header::Hash,
+	/// Header of block at which read is performed.
+	pub header: Header,
+	/// Prefix of the keys to read.
+	pub prefix: Vec<u8>,
+	/// Only read keys strictly after this one.
+	pub start_key: Option<Vec<u8>>,
+	/// Maximal number of keys to read.
+	pub count: u32,
+	/// Number of times to retry request. None means that default RETRY_COUNT is used.
+	pub retry_count: Option<usize>,
+}
+
 /// Remote storage read child request.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RemoteReadChildRequest<Header: HeaderT> {
@@ -154,6 +171,11 @@ pub trait Fetcher<Block: BlockT>: Send + Sync {
 		HashMap<Vec<u8>, Option<Vec<u8>>>,
 		ClientError,
 	>> + Unpin + Send + 'static;
+	/// Remote storage keys read future.
+	type RemoteReadKeysResult: Future<Output = Result<
+		Vec<(Vec<u8>, Vec<u8>)>,
+		ClientError,
+	>> + Unpin + Send + 'static;
 	/// Remote call result future.
 	type RemoteCallResult: Future<Output = Result<
 		Vec<u8>,
@@ -182,6 +204,11 @@ pub trait Fetcher<Block: BlockT>: Send + Sync {
 		&self,
 		request: RemoteReadChildRequest<Block::Header>
 	) -> Self::RemoteReadResult;
+	/// Fetch remote storage key/value pairs with given prefix, in lexicographic order.
+	fn remote_read_keys(
+		&self,
+		request: RemoteReadKeysRequest<Block::Header>
+	) -> Self::RemoteReadKeysResult;
 	/// Fetch remote call result.
 	fn remote_call(&self, request: RemoteCallRequest<Block::Header>) -> Self::RemoteCallResult;
 	/// Fetch remote changes ((block number, extrinsic index)) where given key has been changed
@@ -215,6 +242,12 @@ pub trait FetchChecker<Block: BlockT>: Send + Sync {
 		request: &RemoteReadChildRequest<Block::Header>,
 		remote_proof: StorageProof,
 	) -> ClientResult<HashMap<Vec<u8>, Option<Vec<u8>>>>;
+	/// Check remote storage keys read proof.
+	fn check_read_keys_proof(
+		&self,
+		request: &RemoteReadKeysRequest<Block::Header>,
+		remote_proof: StorageProof,
+	) -> ClientResult<Vec<(Vec<u8>, Vec<u8>)>>;
 	/// Check remote method execution proof.
 	fn check_execution_proof(
 		&self,
@@ -323,6 +356,7 @@ pub mod tests {
 	impl Fetcher<Block> for OkCallFetcher {
 		type RemoteHeaderResult = Ready<Result<Header, ClientError>>;
 		type RemoteReadResult = Ready<Result<HashMap<Vec<u8>, Option<Vec<u8>>>, ClientError>>;
+		type RemoteReadKeysResult = Ready<Result<Vec<(Vec<u8>, Vec<u8>)>, ClientError>>;
 		type RemoteCallResult = Ready<Result<Vec<u8>, ClientError>>;
 		type RemoteChangesResult = Ready<Result<Vec<(NumberFor<Block>, u32)>, ClientError>>;
 		type RemoteBodyResult = Ready<Result<Vec<Extrinsic>, ClientError>>;
@@ -339,6 +373,10 @@ pub mod tests {
 			not_implemented_in_tests()
 		}
 
+		fn remote_read_keys(&self, _request: RemoteReadKeysRequest<Header>) -> Self::RemoteReadKeysResult {
+			not_implemented_in_tests()
+		}
+
 		fn remote_call(&self, _request: RemoteCallRequest<Header>) -> Self::RemoteCallResult {
 			futures::future::ready(Ok((*self.lock()).clone()))
 		}
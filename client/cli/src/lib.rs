@@ -30,7 +30,7 @@ use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::{
 	config::{Configuration, DatabaseConfig, KeystoreConfig},
 	ServiceBuilderCommand,
-	RuntimeGenesis, ChainSpecExtension, PruningMode, ChainSpec,
+	RuntimeGenesis, ChainSpecExtension, PruningMode, ChainSpec, TransactionPoolLimit,
 };
 use sc_network::{
 	self,
@@ -54,9 +54,9 @@ pub use structopt::clap::App;
 use params::{
 	RunCmd, PurgeChainCmd, RevertCmd, ImportBlocksCmd, ExportBlocksCmd, BuildSpecCmd,
 	NetworkConfigurationParams, MergeParameters, TransactionPoolParams,
-	NodeKeyParams, NodeKeyType, Cors, CheckBlockCmd,
+	NodeKeyParams, NodeKeyType, Cors, CheckBlockCmd, ExportStateCmd, ImportStateCmd, CheckDbCmd,
 };
-pub use params::{NoCustom, CoreParams, SharedParams, ImportParams, ExecutionStrategy};
+pub use params::{NoCustom, CoreParams, SharedParams, ImportParams, ExecutionStrategy, Database};
 pub use traits::GetSharedParams;
 use app_dirs::{AppInfo, AppDataType};
 use log::info;
@@ -70,8 +70,12 @@ use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 const DEFAULT_NETWORK_CONFIG_PATH : &'static str = "network";
 /// default sub directory to store database
 const DEFAULT_DB_CONFIG_PATH : &'static str = "db";
+/// default sub directory to store the sled database
+const DEFAULT_SLED_DB_CONFIG_PATH : &'static str = "sled";
 /// default sub directory for the key store
 const DEFAULT_KEYSTORE_CONFIG_PATH : &'static str =  "keystore";
+/// default file for the transaction pool journal
+const DEFAULT_POOL_JOURNAL_PATH : &'static str = "txpool-journal";
 
 /// The maximum number of characters for a node name.
 const NODE_NAME_MAX_LENGTH: usize = 32;
@@ -235,6 +239,15 @@ where
 		params::CoreParams::CheckBlock(params) => ParseAndPrepare::CheckBlock(
 			CheckBlock { params, version }
 		),
+		params::CoreParams::ExportState(params) => ParseAndPrepare::ExportState(
+			ParseAndPrepareExportState { params, version }
+		),
+		params::CoreParams::ImportState(params) => ParseAndPrepare::ImportState(
+			ParseAndPrepareImportState { params, version }
+		),
+		params::CoreParams::CheckDb(params) => ParseAndPrepare::CheckDb(
+			ParseAndPrepareCheckDb { params, version }
+		),
 		params::CoreParams::PurgeChain(params) => ParseAndPrepare::PurgeChain(
 			ParseAndPreparePurge { params, version }
 		),
@@ -271,6 +284,12 @@ pub enum ParseAndPrepare<'a, CC, RP> {
 	ImportBlocks(ParseAndPrepareImport<'a>),
 	/// Command to check a block.
 	CheckBlock(CheckBlock<'a>),
+	/// Command ready to export the state of the last finalized block.
+	ExportState(ParseAndPrepareExportState<'a>),
+	/// Command ready to import a state snapshot.
+	ImportState(ParseAndPrepareImportState<'a>),
+	/// Command ready to check the database.
+	CheckDb(ParseAndPrepareCheckDb<'a>),
 	/// Command ready to purge the chain.
 	PurgeChain(ParseAndPreparePurge<'a>),
 	/// Command ready to revert the chain.
@@ -288,6 +307,9 @@ impl<'a, CC, RP> ParseAndPrepare<'a, CC, RP> where CC: GetSharedParams {
 			ParseAndPrepare::ExportBlocks(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::ImportBlocks(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::CheckBlock(c) => Some(&c.params.shared_params),
+			ParseAndPrepare::ExportState(c) => Some(&c.params.shared_params),
+			ParseAndPrepare::ImportState(c) => Some(&c.params.shared_params),
+			ParseAndPrepare::CheckDb(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::PurgeChain(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::RevertChain(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::CustomCommand(c) => c.shared_params(),
@@ -523,14 +545,7 @@ impl<'a> CheckBlock<'a> {
 		let mut config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
 		fill_import_params(&mut config, &self.params.import_params, sc_service::Roles::FULL)?;
 
-		let input = if self.params.input.starts_with("0x") { &self.params.input[2..] } else { &self.params.input[..] };
-		let block_id = match FromStr::from_str(input) {
-			Ok(hash) => BlockId::hash(hash),
-			Err(_) => match self.params.input.parse::<u32>() {
-				Ok(n) => BlockId::number((n as u32).into()),
-				Err(_) => return Err(error::Error::Input("Invalid hash or number specified".into())),
-			}
-		};
+		let block_id = parse_block_id(&self.params.input)?;
 
 		let start = std::time::Instant::now();
 		let check = builder(config)?
@@ -543,6 +558,122 @@ impl<'a> CheckBlock<'a> {
 	}
 }
 
+/// Command ready to export the state of the last finalized block.
+pub struct ParseAndPrepareExportState<'a> {
+	params: ExportStateCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareExportState<'a> {
+	/// Runs the command and exports the state.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S,
+	) -> error::Result<()>
+		where S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+			F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+			B: ServiceBuilderCommand,
+			C: Default,
+			G: RuntimeGenesis,
+			E: ChainSpecExtension,
+	{
+		let mut config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
+		fill_import_params(&mut config, &self.params.import_params, sc_service::Roles::FULL)?;
+
+		if let DatabaseConfig::Path { ref path, .. } = &config.database {
+			info!("DB path: {}", path.display());
+		}
+
+		let file: Box<dyn Write> = match self.params.output {
+			Some(filename) => Box::new(File::create(filename)?),
+			None => Box::new(stdout()),
+		};
+
+		builder(config)?.export_state(file)?;
+		Ok(())
+	}
+}
+
+/// Command ready to import a state snapshot.
+pub struct ParseAndPrepareImportState<'a> {
+	params: ImportStateCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareImportState<'a> {
+	/// Runs the command and seeds the database with the snapshot.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S,
+	) -> error::Result<()>
+		where S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+			F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+			B: ServiceBuilderCommand,
+			C: Default,
+			G: RuntimeGenesis,
+			E: ChainSpecExtension,
+	{
+		let mut config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
+		fill_import_params(&mut config, &self.params.import_params, sc_service::Roles::FULL)?;
+
+		let file: Box<dyn Read> = match self.params.input {
+			Some(filename) => Box::new(File::open(filename)?),
+			None => Box::new(stdin()),
+		};
+
+		builder(config)?.import_state(file)?;
+		Ok(())
+	}
+}
+
+/// Command ready to check the database.
+pub struct ParseAndPrepareCheckDb<'a> {
+	params: CheckDbCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareCheckDb<'a> {
+	/// Runs the command and checks the database.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S,
+	) -> error::Result<()>
+		where S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+			F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+			B: ServiceBuilderCommand,
+			C: Default,
+			G: RuntimeGenesis,
+			E: ChainSpecExtension,
+	{
+		let mut config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
+		fill_import_params(&mut config, &self.params.import_params, sc_service::Roles::FULL)?;
+
+		if let DatabaseConfig::Path { ref path, .. } = &config.database {
+			info!("DB path: {}", path.display());
+		}
+
+		builder(config)?.check_db(self.params.repair)?;
+		Ok(())
+	}
+}
+
+/// Parse a block hash, with or without `0x` prefix, or a block number.
+fn parse_block_id<Block: BlockT>(input: &str) -> error::Result<BlockId<Block>> where
+	Block::Hash: FromStr,
+{
+	let hash = if input.starts_with("0x") { &input[2..] } else { input };
+	match FromStr::from_str(hash) {
+		Ok(hash) => Ok(BlockId::hash(hash)),
+		Err(_) => match input.parse::<u32>() {
+			Ok(n) => Ok(BlockId::number(n.into())),
+			Err(_) => Err(error::Error::Input("Invalid hash or number specified".into())),
+		}
+	}
+}
+
 /// Command ready to purge the chain.
 pub struct ParseAndPreparePurge<'a> {
 	params: PurgeChainCmd,
@@ -562,6 +693,9 @@ impl<'a> ParseAndPreparePurge<'a> {
 		let config = create_config_with_db_path::<(), _, _, _>(
 			spec_factory, &self.params.shared_params, self.version
 		)?;
+		// a sled database keeps its own directory next to the RocksDB one.
+		let sled_path = config.in_chain_config_dir(DEFAULT_SLED_DB_CONFIG_PATH)
+			.expect("We provided a base_path.");
 		let db_path = match config.database {
 			DatabaseConfig::Path { path, .. } => path,
 			_ => {
@@ -569,9 +703,14 @@ impl<'a> ParseAndPreparePurge<'a> {
 				return Ok(());
 			}
 		};
+		let mut db_paths = vec![db_path];
+		if sled_path.exists() {
+			db_paths.push(sled_path);
+		}
 
 		if !self.params.yes {
-			print!("Are you sure to remove {:?}? [y/N]: ", &db_path);
+			let names = db_paths.iter().map(|path| format!("{:?}", path)).collect::<Vec<_>>();
+			print!("Are you sure to remove {}? [y/N]: ", names.join(" and "));
 			stdout().flush().expect("failed to flush stdout");
 
 			let mut input = String::new();
@@ -587,17 +726,15 @@ impl<'a> ParseAndPreparePurge<'a> {
 			}
 		}
 
-		match fs::remove_dir_all(&db_path) {
-			Result::Ok(_) => {
-				println!("{:?} removed.", &db_path);
-				Ok(())
-			},
-			Result::Err(ref err) if err.kind() == ErrorKind::NotFound => {
-				eprintln!("{:?} did not exist.", &db_path);
-				Ok(())
-			},
-			Result::Err(err) => Result::Err(err.into())
+		for db_path in db_paths {
+			match fs::remove_dir_all(&db_path) {
+				Result::Ok(_) => println!("{:?} removed.", &db_path),
+				Result::Err(ref err) if err.kind() == ErrorKind::NotFound =>
+					eprintln!("{:?} did not exist.", &db_path),
+				Result::Err(err) => return Result::Err(err.into()),
+			}
 		}
+		Ok(())
 	}
 }
 
@@ -684,6 +821,29 @@ fn fill_transaction_pool_configuration<C, G, E>(
 	options.transaction_pool.future.count = params.pool_limit / factor;
 	options.transaction_pool.future.total_bytes = params.pool_kbytes * 1024 / factor;
 
+	// per sender, the other bound defaulting to the whole ready queue
+	options.transaction_pool.sender = match (params.pool_sender_limit, params.pool_sender_kbytes) {
+		(None, None) => None,
+		(count, kbytes) => Some(TransactionPoolLimit {
+			count: count.unwrap_or(params.pool_limit),
+			total_bytes: kbytes.unwrap_or(params.pool_kbytes) * 1024,
+		}),
+	};
+
+	// replacements
+	options.transaction_pool.replace_bump = sp_runtime::Percent::from_percent(params.pool_replace_bump);
+
+	// journal
+	options.transaction_pool.journal = match params.pool_journal_path {
+		Some(path) => Some(path),
+		None if params.pool_journal => options.in_chain_config_dir(DEFAULT_POOL_JOURNAL_PATH),
+		None => None,
+	};
+
+	// lifetimes
+	options.transaction_pool.future_lifetime = params.pool_future_lifetime;
+	options.transaction_pool.revalidation_budget = params.pool_revalidation_budget;
+
 	Ok(())
 }
 
@@ -785,8 +945,16 @@ pub fn fill_import_params<C, G, E>(
 		G: RuntimeGenesis,
 		E: ChainSpecExtension,
 {
+	// sled keeps its own directory, so that it never opens the files of RocksDB
+	if let (Database::Sled, DatabaseConfig::Path { .. }) = (cli.database, &config.database) {
+		let path = config.in_chain_config_dir(DEFAULT_SLED_DB_CONFIG_PATH)
+			.ok_or_else(|| error::Error::Input("The sled database requires a base path".to_string()))?;
+		config.database = DatabaseConfig::Sled { path, cache_size: None };
+	}
+
 	match config.database {
-		DatabaseConfig::Path { ref mut cache_size, .. } =>
+		DatabaseConfig::Path { ref mut cache_size, .. } |
+		DatabaseConfig::Sled { ref mut cache_size, .. } =>
 			*cache_size = Some(cli.database_cache_size),
 		DatabaseConfig::Custom(_) => {},
 	}
@@ -809,9 +977,17 @@ pub fn fill_import_params<C, G, E>(
 				));
 			}
 
-			PruningMode::keep_blocks(s.parse()
-				.map_err(|_| error::Error::Input("Invalid pruning mode specified".to_string()))?
-			)
+			// either `<blocks>`, or `<blocks>:<interval>` to also keep every `interval`-th block.
+			let invalid = || error::Error::Input("Invalid pruning mode specified".to_string());
+			let mut parts = s.splitn(2, ':');
+			let keep_blocks = parts.next().and_then(|n| n.parse().ok()).ok_or_else(invalid)?;
+			match parts.next() {
+				Some(interval) => {
+					let interval = interval.parse().ok().filter(|i| *i > 0).ok_or_else(invalid)?;
+					PruningMode::keep_blocks_and_checkpoints(keep_blocks, interval)
+				},
+				None => PruningMode::keep_blocks(keep_blocks),
+			}
 		},
 	};
 
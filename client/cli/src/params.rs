@@ -80,6 +80,18 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// Embedded database backend.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum Database {
+		// RocksDB, the default.
+		RocksDb,
+		// sled, if the node is built with its `sled` feature.
+		Sled,
+	}
+}
+
 /// Shared parameters used by all `CoreParams`.
 #[derive(Debug, StructOpt, Clone)]
 pub struct SharedParams {
@@ -105,6 +117,9 @@ pub struct SharedParams {
 pub struct ImportParams {
 	/// Specify the state pruning mode, a number of blocks to keep or 'archive'.
 	///
+	/// '<blocks>:<interval>' additionally keeps the state of every finalized
+	/// block whose number is a multiple of '<interval>'.
+	///
 	/// Default is to keep all block states if the node is running as a
 	/// validator (i.e. 'archive'), otherwise state is only kept for the last
 	/// 256 blocks.
@@ -133,6 +148,16 @@ pub struct ImportParams {
 	#[structopt(flatten)]
 	pub execution_strategies: ExecutionStrategies,
 
+	/// Select the database backend to use.
+	#[structopt(
+		long = "database",
+		value_name = "DB",
+		possible_values = &Database::variants(),
+		case_insensitive = true,
+		default_value = "RocksDb"
+	)]
+	pub database: Database,
+
 	/// Limit the memory the database cache can use.
 	#[structopt(long = "db-cache", value_name = "MiB", default_value = "1024")]
 	pub database_cache_size: u32,
@@ -290,6 +315,33 @@ pub struct TransactionPoolParams {
 	/// Maximum number of kilobytes of all transactions stored in the pool.
 	#[structopt(long = "pool-kbytes", value_name = "COUNT", default_value = "10240")]
 	pub pool_kbytes: usize,
+	/// Maximum number of transactions a single sender may have in the pool.
+	///
+	/// Only applies when the node tells senders apart.
+	#[structopt(long = "pool-sender-limit", value_name = "COUNT")]
+	pub pool_sender_limit: Option<usize>,
+	/// Maximum number of kilobytes of the transactions of a single sender stored in the pool.
+	///
+	/// Only applies when the node tells senders apart.
+	#[structopt(long = "pool-sender-kbytes", value_name = "COUNT")]
+	pub pool_sender_kbytes: Option<usize>,
+	/// Minimal priority increase, in percent, for a transaction to replace another one.
+	#[structopt(long = "pool-replace-bump", value_name = "PERCENT", default_value = "0")]
+	pub pool_replace_bump: u8,
+	/// Keep a journal of the pending transactions on disk, to resubmit them after a restart.
+	#[structopt(long = "pool-journal")]
+	pub pool_journal: bool,
+	/// Specify the journal file, instead of the one in the chain's directory.
+	///
+	/// Implies `--pool-journal`.
+	#[structopt(long = "pool-journal-path", value_name = "PATH", parse(from_os_str))]
+	pub pool_journal_path: Option<PathBuf>,
+	/// Drop transactions that have waited in the future queue for more than this many blocks.
+	#[structopt(long = "pool-future-lifetime", value_name = "BLOCKS")]
+	pub pool_future_lifetime: Option<u64>,
+	/// Number of ready transactions revalidated after each imported block.
+	#[structopt(long = "pool-revalidation-budget", value_name = "COUNT", default_value = "16")]
+	pub pool_revalidation_budget: usize,
 }
 
 arg_enum! {
@@ -807,6 +859,54 @@ pub struct ImportBlocksCmd {
 	pub import_params: ImportParams,
 }
 
+/// The `export-state` command used to export the state of the last finalized block.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ExportStateCmd {
+	/// Output file name or stdout if unspecified.
+	#[structopt(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+/// The `import-state` command used to start a new database from a state snapshot.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ImportStateCmd {
+	/// Input file or stdin if unspecified.
+	#[structopt(parse(from_os_str))]
+	pub input: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+/// The `check-db` command used to check the integrity of the database.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CheckDbCmd {
+	/// Revert the chain to the last consistent finalized block if corruption is found.
+	#[structopt(long = "repair")]
+	pub repair: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
 /// The `check-block` command used to validate blocks.
 #[derive(Debug, StructOpt, Clone)]
 pub struct CheckBlockCmd {
@@ -875,6 +975,15 @@ pub enum CoreParams<CC, RP> {
 	/// Validte a single block.
 	CheckBlock(CheckBlockCmd),
 
+	/// Export the state of a finalized block to a file.
+	ExportState(ExportStateCmd),
+
+	/// Start a new database from a state snapshot.
+	ImportState(ImportStateCmd),
+
+	/// Check the integrity of the database.
+	CheckDb(CheckDbCmd),
+
 	/// Revert chain to the previous state.
 	Revert(RevertCmd),
 
@@ -912,6 +1021,18 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			CheckBlockCmd::augment_clap(SubCommand::with_name("check-block"))
 				.about("Re-validate a known block.")
 		)
+		.subcommand(
+			ExportStateCmd::augment_clap(SubCommand::with_name("export-state"))
+				.about("Export the state of the last finalized block, which must be the best block, to a compressed snapshot file.")
+		)
+		.subcommand(
+			ImportStateCmd::augment_clap(SubCommand::with_name("import-state"))
+				.about("Start a new database from a state snapshot, syncing from its block.")
+		)
+		.subcommand(
+			CheckDbCmd::augment_clap(SubCommand::with_name("check-db"))
+				.about("Check the integrity of the database, optionally reverting corrupted blocks.")
+		)
 		.subcommand(
 			RevertCmd::augment_clap(SubCommand::with_name("revert"))
 				.about("Revert chain to the previous state.")
@@ -932,6 +1053,12 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 				CoreParams::ImportBlocks(ImportBlocksCmd::from_clap(matches)),
 			("check-block", Some(matches)) =>
 				CoreParams::CheckBlock(CheckBlockCmd::from_clap(matches)),
+			("export-state", Some(matches)) =>
+				CoreParams::ExportState(ExportStateCmd::from_clap(matches)),
+			("import-state", Some(matches)) =>
+				CoreParams::ImportState(ImportStateCmd::from_clap(matches)),
+			("check-db", Some(matches)) =>
+				CoreParams::CheckDb(CheckDbCmd::from_clap(matches)),
 			("revert", Some(matches)) => CoreParams::Revert(RevertCmd::from_clap(matches)),
 			("purge-chain", Some(matches)) =>
 				CoreParams::PurgeChain(PurgeChainCmd::from_clap(matches)),
@@ -0,0 +1,634 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database integrity checks.
+//!
+//! The canonical chain is walked from genesis to the best block, checking that each block has a
+//! decodable header, body and justification, linked to its parent and rooted in its changes trie.
+//! Then the state-db journals are re-read and all the trie nodes reachable from the state roots
+//! the database retains are visited.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use codec::{Decode, Encode};
+use hash_db::{HashDBRef, Prefix};
+use sc_client_api::backend::{Backend as _, PrunableStateChangesTrieStorage};
+use sp_blockchain::{HeaderBackend, Result as ClientResult};
+use sp_core::{H256, Blake2Hasher};
+use sp_runtime::{Justification, generic::{BlockId, DigestItem}};
+use sp_runtime::traits::{
+	Block as BlockT, Header as HeaderT, Hash as HashT, NumberFor, One, Zero, SaturatedConversion,
+};
+use sp_state_machine::DBValue;
+use sp_trie::{KeySpacedDB, Layout, TrieConfiguration, TrieLayout, prefixed_key};
+use trie_db::{NodeCodec as _, node::{NodeHandlePlan, NodePlan}};
+
+use crate::{Backend, StateMetaDb, StorageDb, columns};
+use crate::snapshot::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+use crate::utils::{self, meta_keys};
+
+/// A problem found in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Corruption<Number, Hash> {
+	/// The best or finalized block recorded in the meta data is not canonical.
+	Meta(String),
+	/// No canonical block is recorded at this number.
+	MissingCanonicalBlock(Number),
+	/// The header of a canonical block is missing.
+	MissingHeader(Number, Hash),
+	/// The header of a canonical block can't be decoded.
+	InvalidHeader(Number, Hash),
+	/// The header of a canonical block has another hash or number than it is stored under.
+	HeaderMismatch(Number, Hash),
+	/// The parent of a canonical block is not the canonical block before it.
+	ParentMismatch(Number, Hash),
+	/// The body of a canonical block is missing.
+	MissingBody(Number, Hash),
+	/// The body of a canonical block can't be decoded.
+	InvalidBody(Number, Hash),
+	/// The body of a canonical block doesn't match the extrinsics root of its header.
+	ExtrinsicsRootMismatch(Number, Hash),
+	/// The justification of a canonical block can't be decoded.
+	InvalidJustification(Number, Hash),
+	/// The root node of the changes trie of a canonical block is missing.
+	MissingChangesTrie(Number, Hash),
+	/// The state-db journals can't be read, or disagree with the canonical chain.
+	StateJournal(String),
+	/// A node of the retained state of a canonical block is missing.
+	MissingTrieNode {
+		/// Block number.
+		number: Number,
+		/// Block hash.
+		hash: Hash,
+		/// Storage key of the child trie the node belongs to, if any.
+		child_storage_key: Option<Vec<u8>>,
+		/// The trie error.
+		error: String,
+	},
+}
+
+impl<Number: Copy, Hash> Corruption<Number, Hash> {
+	/// Number of the corrupted block, if the corruption is about a block.
+	pub fn block_number(&self) -> Option<Number> {
+		match *self {
+			Corruption::Meta(_) | Corruption::StateJournal(_) => None,
+			Corruption::MissingCanonicalBlock(number) |
+			Corruption::MissingHeader(number, _) |
+			Corruption::InvalidHeader(number, _) |
+			Corruption::HeaderMismatch(number, _) |
+			Corruption::ParentMismatch(number, _) |
+			Corruption::MissingBody(number, _) |
+			Corruption::InvalidBody(number, _) |
+			Corruption::ExtrinsicsRootMismatch(number, _) |
+			Corruption::InvalidJustification(number, _) |
+			Corruption::MissingChangesTrie(number, _) |
+			Corruption::MissingTrieNode { number, .. } => Some(number),
+		}
+	}
+}
+
+impl<Number: fmt::Display, Hash: fmt::Debug> fmt::Display for Corruption<Number, Hash> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Corruption::Meta(e) => write!(f, "Invalid meta data: {}", e),
+			Corruption::MissingCanonicalBlock(n) => write!(f, "No canonical block #{}", n),
+			Corruption::MissingHeader(n, h) => write!(f, "Missing header of #{} ({:?})", n, h),
+			Corruption::InvalidHeader(n, h) => write!(f, "Error decoding header of #{} ({:?})", n, h),
+			Corruption::HeaderMismatch(n, h) =>
+				write!(f, "Header stored as #{} ({:?}) belongs to another block", n, h),
+			Corruption::ParentMismatch(n, h) =>
+				write!(f, "Parent of #{} ({:?}) is not the canonical block before it", n, h),
+			Corruption::MissingBody(n, h) => write!(f, "Missing body of #{} ({:?})", n, h),
+			Corruption::InvalidBody(n, h) => write!(f, "Error decoding body of #{} ({:?})", n, h),
+			Corruption::ExtrinsicsRootMismatch(n, h) =>
+				write!(f, "Body of #{} ({:?}) does not match its extrinsics root", n, h),
+			Corruption::InvalidJustification(n, h) =>
+				write!(f, "Error decoding justification of #{} ({:?})", n, h),
+			Corruption::MissingChangesTrie(n, h) =>
+				write!(f, "Missing changes trie root of #{} ({:?})", n, h),
+			Corruption::StateJournal(e) => write!(f, "Invalid state journal: {}", e),
+			Corruption::MissingTrieNode { number, hash, child_storage_key: None, error } =>
+				write!(f, "Incomplete state of #{} ({:?}): {}", number, hash, error),
+			Corruption::MissingTrieNode { number, hash, child_storage_key: Some(key), error } =>
+				write!(f, "Incomplete child trie {:?} of #{} ({:?}): {}", key, number, hash, error),
+		}
+	}
+}
+
+/// Outcome of `Backend::check_integrity`.
+#[derive(Debug, Clone)]
+pub struct IntegrityReport<Number, Hash> {
+	/// Number of canonical blocks checked.
+	pub checked_blocks: u64,
+	/// Number of distinct state roots walked.
+	pub checked_states: u64,
+	/// The problems found.
+	pub corruptions: Vec<Corruption<Number, Hash>>,
+}
+
+impl<Number: Copy + Ord, Hash> IntegrityReport<Number, Hash> {
+	/// Number of the first corrupted canonical block.
+	pub fn first_corrupted_block(&self) -> Option<Number> {
+		self.corruptions.iter().filter_map(Corruption::block_number).min()
+	}
+
+	/// Whether all the corruptions are about blocks, so that reverting below them repairs the
+	/// database.
+	pub fn is_repairable_by_revert(&self) -> bool {
+		self.corruptions.iter().all(|c| c.block_number().is_some())
+	}
+}
+
+/// Trie nodes of the state, as seen by the tries.
+struct StateNodes<'a, Block: BlockT>(&'a StorageDb<Block>);
+
+impl<'a, Block: BlockT> HashDBRef<Blake2Hasher, DBValue> for StateNodes<'a, Block> {
+	fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+		sp_state_machine::Storage::get(self.0, key, prefix).unwrap_or(None)
+	}
+
+	fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+		HashDBRef::get(self, key, prefix).is_some()
+	}
+}
+
+/// Trie nodes already visited, by keyspace and key in the database.
+type VisitedNodes = HashSet<(Option<Vec<u8>>, Vec<u8>)>;
+
+/// Packs nibbles into bytes; an odd last nibble is returned apart, in the high half of a byte.
+fn pack_nibbles(nibbles: &[u8]) -> (Vec<u8>, Option<u8>) {
+	let bytes = nibbles.chunks(2)
+		.filter(|pair| pair.len() == 2)
+		.map(|pair| pair[0] << 4 | pair[1])
+		.collect();
+	let last = if nibbles.len() % 2 == 1 { nibbles.last().map(|nibble| nibble << 4) } else { None };
+	(bytes, last)
+}
+
+/// A step of `walk_trie`.
+enum WalkStep {
+	/// Visit the node with this hash, reached through these nibbles.
+	Node(H256, Vec<u8>),
+	/// The whole subtrie under this node has been walked.
+	Walked((Option<Vec<u8>>, Vec<u8>)),
+}
+
+/// Visit the nodes of a trie, calling `f` for each of its entries.
+///
+/// Subtries whose root node is in `visited` are skipped: tries of consecutive blocks share most
+/// of their nodes, which have been checked, with their entries, under an earlier root. A node is
+/// only marked visited once its whole subtrie has been walked, so that a missing node is reported
+/// under every root it belongs to.
+fn walk_trie(
+	db: &dyn HashDBRef<Blake2Hasher, DBValue>,
+	keyspace: Option<&[u8]>,
+	root: &H256,
+	visited: &mut VisitedNodes,
+	mut f: impl FnMut(&[u8], &[u8]),
+) -> Result<(), String> {
+	if *root == <Layout<Blake2Hasher> as TrieLayout>::Codec::hashed_null_node() {
+		return Ok(())
+	}
+
+	let mut pending = vec![WalkStep::Node(*root, Vec::new())];
+	let mut children = Vec::new();
+	while let Some(step) = pending.pop() {
+		let (hash, path) = match step {
+			WalkStep::Node(hash, path) => (hash, path),
+			WalkStep::Walked(visited_key) => {
+				visited.insert(visited_key);
+				continue
+			},
+		};
+		let (prefix, last) = pack_nibbles(&path);
+		let key = prefixed_key::<Blake2Hasher>(&hash, (&prefix, last));
+		let visited_key = (keyspace.map(<[u8]>::to_vec), key);
+		if visited.contains(&visited_key) {
+			continue
+		}
+		let node = db.get(&hash, (&prefix, last))
+			.ok_or_else(|| format!("Missing trie node {:?} at {:?}", hash, prefix))?;
+		walk_node(&node, path, &mut children, &mut f)?;
+		// popped once the children, pushed above it, have all been walked.
+		pending.push(WalkStep::Walked(visited_key));
+		pending.extend(children.drain(..).map(|(hash, path)| WalkStep::Node(hash, path)));
+	}
+	Ok(())
+}
+
+/// Visit the entries of an encoded node and the nodes inlined in it, queueing its other children.
+fn walk_node(
+	node: &[u8],
+	mut path: Vec<u8>,
+	pending: &mut Vec<(H256, Vec<u8>)>,
+	f: &mut impl FnMut(&[u8], &[u8]),
+) -> Result<(), String> {
+	let plan = <Layout<Blake2Hasher> as TrieLayout>::Codec::decode_plan(node)
+		.map_err(|e| format!("Undecodable trie node: {:?}", e))?;
+	let (partial, value, children) = match plan {
+		NodePlan::Empty => return Ok(()),
+		NodePlan::Leaf { partial, value } => (partial, Some(value), None),
+		NodePlan::NibbledBranch { partial, value, children } => (partial, value, Some(children)),
+		_ => return Err("Unexpected trie node kind".into()),
+	};
+	let partial = partial.build(node);
+	path.extend((0..partial.len()).map(|i| partial.at(i)));
+	if let Some(value) = value {
+		match pack_nibbles(&path) {
+			(key, None) => f(&key, &node[value]),
+			(_, Some(_)) => return Err(format!("Trie value at an odd number of nibbles {:?}", path)),
+		}
+	}
+	for (nibble, child) in children.iter().flatten().enumerate() {
+		let child = match child {
+			Some(child) => child,
+			None => continue,
+		};
+		let mut child_path = path.clone();
+		child_path.push(nibble as u8);
+		match child {
+			NodeHandlePlan::Hash(range) => pending.push((H256::from_slice(&node[range.clone()]), child_path)),
+			NodeHandlePlan::Inline(range) => walk_node(&node[range.clone()], child_path, pending, f)?,
+		}
+	}
+	Ok(())
+}
+
+impl<Block: BlockT<Hash=H256>> Backend<Block> {
+	/// Check the integrity of the database. The chain is not modified.
+	pub fn check_integrity(&self) -> ClientResult<IntegrityReport<NumberFor<Block>, Block::Hash>> {
+		let db = &*self.storage.db;
+		let (best_number, best_hash, finalized_number, finalized_hash) = {
+			let meta = self.blockchain.meta.read();
+			(meta.best_number, meta.best_hash, meta.finalized_number, meta.finalized_hash)
+		};
+		let mut report = IntegrityReport {
+			checked_blocks: 0,
+			checked_states: 0,
+			corruptions: Vec::new(),
+		};
+
+		// the blocks before an imported snapshot are not in the database.
+		let snapshot_number = match db.get(columns::META, meta_keys::SNAPSHOT_BLOCK).map_err(utils::db_err)? {
+			Some(lookup_key) => Some(utils::lookup_key_to_number::<NumberFor<Block>>(&lookup_key)?),
+			None => None,
+		};
+		let changes_trie_config = self.changes_trie_config(finalized_hash).unwrap_or(None);
+		let oldest_changes_trie = changes_trie_config.as_ref().map(|config| {
+			self.changes_tries_storage.oldest_changes_trie_block(config, finalized_number)
+		});
+		let empty_root = Layout::<Blake2Hasher>::trie_root(Vec::<(Vec<u8>, Vec<u8>)>::new());
+
+		let mut canonical = Vec::new();
+		let mut parent_hash = None;
+		let mut number: NumberFor<Block> = Zero::zero();
+		while number <= best_number {
+			if !number.is_zero() && snapshot_number.map_or(false, |snapshot| number < snapshot) {
+				number = snapshot_number.expect("checked above; qed");
+				parent_hash = None;
+				continue;
+			}
+			report.checked_blocks += 1;
+			let hash = self.check_block(number, parent_hash, snapshot_number, &mut report.corruptions)?;
+			if let (Some(hash), Some(oldest)) = (hash, oldest_changes_trie) {
+				if number >= oldest {
+					let header = self.blockchain.expect_header(BlockId::Hash(hash))?;
+					let root = header.digest().log(DigestItem::as_changes_trie_root).cloned();
+					if let Some(root) = root.filter(|root| *root != empty_root) {
+						if db.get(columns::CHANGES_TRIE, root.as_ref()).map_err(utils::db_err)?.is_none() {
+							report.corruptions.push(Corruption::MissingChangesTrie(number, hash));
+						}
+					}
+				}
+			}
+			if let Some(hash) = hash {
+				canonical.push((number, hash));
+			}
+			parent_hash = hash;
+			number += One::one();
+		}
+
+		for &(number, hash, name) in &[(best_number, best_hash, "best"), (finalized_number, finalized_hash, "finalized")] {
+			if !canonical.contains(&(number, hash)) {
+				report.corruptions.push(Corruption::Meta(
+					format!("the {} block #{} ({:?}) is not canonical", name, number, hash),
+				));
+			}
+		}
+
+		match self.storage.state_db.check_journals(&StateMetaDb(db)) {
+			Ok(Some((hash, number))) => {
+				let number = number.saturated_into::<NumberFor<Block>>();
+				if !canonical.contains(&(number, hash)) {
+					report.corruptions.push(Corruption::StateJournal(
+						format!("the last canonicalized block #{} ({:?}) is not canonical", number, hash),
+					));
+				}
+			},
+			Ok(None) => {},
+			Err(e) => report.corruptions.push(Corruption::StateJournal(format!("{:?}", e))),
+		}
+
+		let mut checked_roots = HashSet::new();
+		let mut visited = VisitedNodes::new();
+		for (number, hash) in canonical {
+			if !self.have_state_at(&hash, number) && !self.is_archive {
+				continue;
+			}
+			let header = self.blockchain.expect_header(BlockId::Hash(hash))?;
+			if checked_roots.insert(*header.state_root()) {
+				report.checked_states += 1;
+				self.check_state(number, hash, header.state_root(), &mut visited, &mut report.corruptions);
+			}
+		}
+
+		Ok(report)
+	}
+
+	/// Check a canonical block, returning its hash if it can be followed by its children.
+	fn check_block(
+		&self,
+		number: NumberFor<Block>,
+		parent_hash: Option<Block::Hash>,
+		snapshot_number: Option<NumberFor<Block>>,
+		corruptions: &mut Vec<Corruption<NumberFor<Block>, Block::Hash>>,
+	) -> ClientResult<Option<Block::Hash>> {
+		let db = &*self.storage.db;
+		let lookup_key = match utils::block_id_to_lookup_key::<Block>(db, columns::KEY_LOOKUP, BlockId::Number(number))? {
+			Some(lookup_key) if lookup_key.len() == 4 + H256::len_bytes() => lookup_key,
+			_ => {
+				corruptions.push(Corruption::MissingCanonicalBlock(number));
+				return Ok(None);
+			},
+		};
+		let hash = H256::from_slice(&lookup_key[4..]);
+
+		let header = match db.get(columns::HEADER, &lookup_key).map_err(utils::db_err)? {
+			Some(header) => match Block::Header::decode(&mut &header[..]) {
+				Ok(header) => header,
+				Err(_) => {
+					corruptions.push(Corruption::InvalidHeader(number, hash));
+					return Ok(None);
+				},
+			},
+			None => {
+				corruptions.push(Corruption::MissingHeader(number, hash));
+				return Ok(None);
+			},
+		};
+		if header.hash() != hash || *header.number() != number {
+			corruptions.push(Corruption::HeaderMismatch(number, hash));
+			return Ok(None);
+		}
+		if parent_hash.map_or(false, |parent_hash| *header.parent_hash() != parent_hash) {
+			corruptions.push(Corruption::ParentMismatch(number, hash));
+		}
+
+		match db.get(columns::BODY, &lookup_key).map_err(utils::db_err)? {
+			Some(body) => match Vec::<Block::Extrinsic>::decode(&mut &body[..]) {
+				Ok(body) => {
+					let extrinsics_root = <<Block::Header as HeaderT>::Hashing as HashT>::ordered_trie_root(
+						body.iter().map(Encode::encode).collect(),
+					);
+					if extrinsics_root != *header.extrinsics_root() {
+						corruptions.push(Corruption::ExtrinsicsRootMismatch(number, hash));
+					}
+				},
+				Err(_) => corruptions.push(Corruption::InvalidBody(number, hash)),
+			},
+			// the block a snapshot was imported at comes without its body.
+			None if snapshot_number == Some(number) => {},
+			None => corruptions.push(Corruption::MissingBody(number, hash)),
+		}
+
+		if let Some(justification) = db.get(columns::JUSTIFICATION, &lookup_key).map_err(utils::db_err)? {
+			if Justification::decode(&mut &justification[..]).is_err() {
+				corruptions.push(Corruption::InvalidJustification(number, hash));
+			}
+		}
+
+		Ok(Some(hash))
+	}
+
+	/// Visit all the trie nodes of the state of a canonical block, including its child tries.
+	fn check_state(
+		&self,
+		number: NumberFor<Block>,
+		hash: Block::Hash,
+		state_root: &H256,
+		visited: &mut VisitedNodes,
+		corruptions: &mut Vec<Corruption<NumberFor<Block>, Block::Hash>>,
+	) {
+		let nodes = StateNodes(&*self.storage);
+		let mut child_roots = Vec::new();
+		let top = walk_trie(&nodes, None, state_root, visited, |key, value| {
+			if key.starts_with(DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+				child_roots.push((key.to_vec(), value.to_vec()));
+			}
+		});
+		if let Err(error) = top {
+			corruptions.push(Corruption::MissingTrieNode { number, hash, child_storage_key: None, error });
+		}
+
+		for (storage_key, root) in child_roots {
+			let error = if root.len() == H256::len_bytes() {
+				let unique_id = &storage_key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..];
+				let child_nodes = KeySpacedDB::new(&nodes, unique_id);
+				match walk_trie(&child_nodes, Some(unique_id), &H256::from_slice(&root), visited, |_, _| ()) {
+					Ok(()) => continue,
+					Err(error) => error,
+				}
+			} else {
+				format!("invalid child trie root {:?}", root)
+			};
+			corruptions.push(Corruption::MissingTrieNode {
+				number,
+				hash,
+				child_storage_key: Some(storage_key),
+				error,
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use kvdb::DBTransaction;
+	use sc_client_api::backend::{Backend as _, BlockImportOperation as _, NewBlockState};
+	use sp_runtime::Storage;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+	use sp_runtime::traits::BlakeTwo256;
+	use sp_trie::{EMPTY_PREFIX, PrefixedMemoryDB, TrieMut, trie_types::TrieDBMut};
+	use super::*;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	fn commit_block(backend: &Backend<Block>, number: u64, parent_hash: H256, value: &[u8]) -> Header {
+		let state_at = if number == 0 {
+			Default::default()
+		} else {
+			parent_hash
+		};
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(state_at)).unwrap();
+		let state_root = op.reset_storage(Storage {
+			top: vec![(b"key".to_vec(), value.to_vec())].into_iter().collect(),
+			children: Default::default(),
+		}).unwrap();
+		let header = Header {
+			number,
+			parent_hash,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: BlakeTwo256::ordered_trie_root(Vec::new()),
+		};
+		op.set_block_data(header.clone(), Some(vec![]), None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+		header
+	}
+
+	fn build_chain(backend: &Backend<Block>) -> Vec<Header> {
+		let mut headers: Vec<Header> = Vec::new();
+		for number in 0..4 {
+			let parent_hash = headers.last().map(|h| h.hash()).unwrap_or_default();
+			headers.push(commit_block(backend, number, parent_hash, &[number as u8]));
+		}
+		headers
+	}
+
+	/// Trie nodes counting how many are read.
+	struct CountingNodes(PrefixedMemoryDB<Blake2Hasher>, std::cell::Cell<usize>);
+
+	impl HashDBRef<Blake2Hasher, DBValue> for CountingNodes {
+		fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+			self.1.set(self.1.get() + 1);
+			HashDBRef::get(&self.0, key, prefix)
+		}
+
+		fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+			HashDBRef::contains(&self.0, key, prefix)
+		}
+	}
+
+	#[test]
+	fn shared_subtries_are_walked_once() {
+		let mut db = PrefixedMemoryDB::<Blake2Hasher>::default();
+		let mut root = H256::default();
+		{
+			let mut trie = TrieDBMut::<Blake2Hasher>::new(&mut db, &mut root);
+			// values long enough for the leaves not to be inlined in their parents.
+			for i in 0..64u8 {
+				trie.insert(&[i, i], &[i; 32]).unwrap();
+			}
+		}
+		let first_root = root;
+		{
+			let mut trie = TrieDBMut::<Blake2Hasher>::from_existing(&mut db, &mut root).unwrap();
+			trie.insert(&[7, 7], b"changed").unwrap();
+		}
+		let nodes = CountingNodes(db, Default::default());
+		let mut visited = VisitedNodes::new();
+
+		let mut entries = Vec::new();
+		walk_trie(&nodes, None, &first_root, &mut visited, |key, _| entries.push(key.to_vec())).unwrap();
+		assert_eq!(entries.len(), 64);
+		let first_reads = nodes.1.replace(0);
+
+		let mut entries = Vec::new();
+		walk_trie(&nodes, None, &root, &mut visited, |key, value| entries.push((key.to_vec(), value.to_vec())))
+			.unwrap();
+		assert_eq!(entries, vec![(vec![7, 7], b"changed".to_vec())]);
+		assert!(nodes.1.get() < first_reads);
+	}
+
+	/// Trie nodes missing the ones under a path.
+	struct PrunedNodes(PrefixedMemoryDB<Blake2Hasher>, Vec<u8>);
+
+	impl HashDBRef<Blake2Hasher, DBValue> for PrunedNodes {
+		fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+			if prefix == (&self.1[..], None) {
+				return None
+			}
+			HashDBRef::get(&self.0, key, prefix)
+		}
+
+		fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+			HashDBRef::get(self, key, prefix).is_some()
+		}
+	}
+
+	#[test]
+	fn missing_nodes_are_reported_under_every_root_sharing_them() {
+		let mut db = PrefixedMemoryDB::<Blake2Hasher>::default();
+		let mut root = H256::default();
+		{
+			let mut trie = TrieDBMut::<Blake2Hasher>::new(&mut db, &mut root);
+			for i in 0..64u8 {
+				trie.insert(&[i, i], &[i; 32]).unwrap();
+			}
+		}
+		let first_root = root;
+		{
+			// leaves the subtrie of the keys starting with nibble 0 shared.
+			let mut trie = TrieDBMut::<Blake2Hasher>::from_existing(&mut db, &mut root).unwrap();
+			trie.insert(&[63, 63], b"changed").unwrap();
+		}
+		// the leaf of key `[1, 1]`, at nibbles `[0, 1]`.
+		let nodes = PrunedNodes(db, vec![0x01]);
+		let mut visited = VisitedNodes::new();
+
+		assert!(walk_trie(&nodes, None, &first_root, &mut visited, |_, _| ()).is_err());
+		assert!(walk_trie(&nodes, None, &root, &mut visited, |_, _| ()).is_err());
+	}
+
+	#[test]
+	fn healthy_database_passes() {
+		let backend = Backend::<Block>::new_test(16, 0);
+		build_chain(&backend);
+
+		let report = backend.check_integrity().unwrap();
+		assert_eq!(report.corruptions, Vec::new());
+		assert_eq!(report.checked_blocks, 4);
+		assert_eq!(report.checked_states, 4);
+		assert_eq!(report.first_corrupted_block(), None);
+	}
+
+	#[test]
+	fn reports_missing_body_and_state_node() {
+		let backend = Backend::<Block>::new_test(16, 0);
+		let headers = build_chain(&backend);
+
+		let mut transaction = DBTransaction::new();
+		let lookup_key = utils::number_and_hash_to_lookup_key(2, headers[2].hash()).unwrap();
+		transaction.delete(columns::BODY, &lookup_key);
+		let root_key = prefixed_key::<Blake2Hasher>(&headers[3].state_root, EMPTY_PREFIX);
+		transaction.delete(columns::STATE, &root_key);
+		backend.storage.db.write(transaction).unwrap();
+
+		let report = backend.check_integrity().unwrap();
+		assert_eq!(report.corruptions.len(), 2);
+		assert_eq!(report.corruptions[0], Corruption::MissingBody(2, headers[2].hash()));
+		match &report.corruptions[1] {
+			Corruption::MissingTrieNode { number: 3, hash, child_storage_key: None, .. } =>
+				assert_eq!(*hash, headers[3].hash()),
+			c => panic!("Unexpected corruption {:?}", c),
+		}
+		assert_eq!(report.first_corrupted_block(), Some(2));
+		assert!(report.is_repairable_by_revert());
+		assert_eq!(backend.blockchain().info().best_number, 3);
+	}
+}
@@ -33,13 +33,17 @@ mod children;
 mod cache;
 mod storage_cache;
 mod utils;
+mod snapshot;
+mod check;
+#[cfg(feature = "sled")]
+mod sled_db;
 
 use std::sync::Arc;
 use std::path::PathBuf;
 use std::io;
 use std::collections::{HashMap, HashSet};
 
-use sc_client_api::{execution_extensions::ExecutionExtensions, ForkBlocks, UsageInfo, MemoryInfo, BadBlocks, IoInfo};
+use sc_client_api::{execution_extensions::ExecutionExtensions, ForkBlocks, UsageInfo, MemoryInfo, BadBlocks, IoInfo, StateRetention};
 use sc_client_api::backend::NewBlockState;
 use sc_client_api::backend::{StorageCollection, ChildStorageCollection};
 use sp_blockchain::{
@@ -72,6 +76,8 @@ use sp_blockchain::{CachedHeaderMetadata, HeaderMetadata, HeaderMetadataCache};
 use crate::storage_cache::{CachingState, SharedCache, new_shared_cache};
 use log::{trace, debug, warn};
 pub use sc_state_db::PruningMode;
+pub use snapshot::SnapshotManifest;
+pub use check::{Corruption, IntegrityReport};
 
 #[cfg(feature = "test-helpers")]
 use sc_client::in_mem::Backend as InMemoryBackend;
@@ -266,6 +272,14 @@ pub enum DatabaseSettingsSrc {
 		cache_size: Option<usize>,
 	},
 
+	/// Load a sled database from a given path.
+	Sled {
+		/// Path to the database.
+		path: PathBuf,
+		/// Cache size in MiB. If `None` default is used.
+		cache_size: Option<usize>,
+	},
+
 	/// Use a custom already-open database.
 	Custom(Arc<dyn KeyValueDB>),
 }
@@ -898,10 +912,10 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 		Self::from_kvdb(db as Arc<_>, canonicalization_delay, &config)
 	}
 
-	/// Create new memory-backed client backend for tests.
+	/// Create new client backend for tests, see `utils::new_test_database`.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test(keep_blocks: u32, canonicalization_delay: u64) -> Self {
-		let db = Arc::new(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let db = crate::utils::new_test_database();
 		let db_setting = DatabaseSettings {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
@@ -1007,6 +1021,19 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 		self.blockchain.db.iter(columns::HEADER).count() as u64
 	}
 
+	/// Returns true if `hash` is a checkpoint of the canonical chain below the pruning window,
+	/// whose state is never discarded.
+	fn is_checkpoint_state(&self, hash: &Block::Hash, number: NumberFor<Block>) -> bool {
+		use sc_client::blockchain::HeaderBackend as BcHeaderBackend;
+
+		let number_u64 = number.saturated_into::<u64>();
+		let state_db = &self.storage.state_db;
+		state_db.checkpoint_interval().is_some()
+			&& state_db.pruning_window_start().map_or(false, |start| number_u64 < start)
+			&& !state_db.is_pruned(hash, number_u64)
+			&& self.blockchain.hash(number).ok().and_then(|h| h) == Some(*hash)
+	}
+
 	/// Read (from storage or cache) changes trie config.
 	///
 	/// Currently changes tries configuration is set up once (at genesis) and could not
@@ -1166,7 +1193,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			};
 
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
-			let commit = self.storage.state_db.canonicalize_block(&hash)
+			let commit = self.storage.state_db.canonicalize_block(&hash, &*self.storage)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
 		};
@@ -1386,7 +1413,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			let lookup_key = utils::number_and_hash_to_lookup_key(f_num, f_hash.clone())?;
 			transaction.put(columns::META, meta_keys::FINALIZED_BLOCK, &lookup_key);
 
-			let commit = self.storage.state_db.canonicalize_block(&f_hash)
+			let commit = self.storage.state_db.canonicalize_block(&f_hash, &*self.storage)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
 
@@ -1556,6 +1583,13 @@ impl<Block> sc_client_api::backend::Backend<Block, Blake2Hasher> for Backend<Blo
 		})
 	}
 
+	fn state_retention(&self) -> Option<StateRetention> {
+		Some(StateRetention {
+			pruned_below: self.storage.state_db.pruning_window_start(),
+			checkpoint_interval: self.storage.state_db.checkpoint_interval(),
+		})
+	}
+
 	fn revert(&self, n: NumberFor<Block>, revert_finalized: bool) -> ClientResult<NumberFor<Block>> {
 		let mut best_number = self.blockchain.info().best_number;
 		let mut best_hash = self.blockchain.info().best_hash;
@@ -1654,6 +1688,13 @@ impl<Block> sc_client_api::backend::Backend<Block, Blake2Hasher> for Backend<Blo
 					let db_state = DbState::new(self.storage.clone(), root);
 					let state = RefTrackingState::new(db_state, self.storage.clone(), Some(hash.clone()));
 					Ok(CachingState::new(state, self.shared_cache.clone(), Some(hash)))
+				} else if self.is_checkpoint_state(&hash, *hdr.number()) {
+					// checkpoint states are out of the pruning window and never discarded,
+					// so they need no pinning.
+					let root = H256::from_slice(hdr.state_root().as_ref());
+					let db_state = DbState::new(self.storage.clone(), root);
+					let state = RefTrackingState::new(db_state, self.storage.clone(), None);
+					Ok(CachingState::new(state, self.shared_cache.clone(), None))
 				} else {
 					Err(sp_blockchain::Error::UnknownBlock(format!("State already discarded for {:?}", block)))
 				}
@@ -1672,7 +1713,18 @@ impl<Block> sc_client_api::backend::Backend<Block, Blake2Hasher> for Backend<Blo
 				_ => false,
 			}
 		} else {
-			!self.storage.state_db.is_pruned(hash, number.saturated_into::<u64>())
+			use sc_client::blockchain::HeaderBackend as BcHeaderBackend;
+
+			let number_u64 = number.saturated_into::<u64>();
+			if self.storage.state_db.is_pruned(hash, number_u64) {
+				return false;
+			}
+			// below the pruning window, only the checkpoints of the canonical chain are kept.
+			match self.storage.state_db.pruning_window_start() {
+				Some(start) if number_u64 < start =>
+					self.blockchain.hash(number).ok().and_then(|h| h) == Some(*hash),
+				_ => true,
+			}
 		}
 	}
 
@@ -77,14 +77,12 @@ impl<Block> LightStorage<Block>
 		Self::from_kvdb(db as Arc<_>)
 	}
 
-	/// Create new memory-backed `LightStorage` for tests.
+	/// Create new `LightStorage` for tests, see `utils::new_test_database`.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test() -> Self {
-		use utils::NUM_COLUMNS;
+		let db = utils::new_test_database();
 
-		let db = Arc::new(::kvdb_memorydb::create(NUM_COLUMNS));
-
-		Self::from_kvdb(db as Arc<_>).expect("failed to create test-db")
+		Self::from_kvdb(db).expect("failed to create test-db")
 	}
 
 	fn from_kvdb(db: Arc<dyn KeyValueDB>) -> ClientResult<Self> {
@@ -0,0 +1,158 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `KeyValueDB` implementation on top of sled.
+//!
+//! All the columns are kept in a single sled tree, each key being prefixed with the big-endian
+//! index of its column. This way a transaction spanning several columns is applied as one
+//! atomic batch, and iterating a column is a prefix scan.
+
+use std::{io, path::Path};
+
+use kvdb::{DBOp, DBTransaction, DBValue, KeyValueDB};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+
+/// Key-value database stored by sled.
+pub struct SledDb {
+	db: sled::Db,
+}
+
+impl SledDb {
+	/// Open or create the database at `path`, with a page cache of `cache_size` MiB.
+	pub fn open(path: &Path, cache_size: Option<usize>) -> io::Result<Self> {
+		let mut config = sled::Config::new().path(path);
+		if let Some(cache_size) = cache_size {
+			config = config.cache_capacity(cache_size as u64 * 1024 * 1024);
+		}
+		Ok(SledDb { db: config.open().map_err(sled_err)? })
+	}
+
+	/// Create a database that is removed when dropped.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn temporary() -> io::Result<Self> {
+		Ok(SledDb { db: sled::Config::new().temporary(true).open().map_err(sled_err)? })
+	}
+}
+
+fn sled_err(err: sled::Error) -> io::Error {
+	io::Error::new(io::ErrorKind::Other, format!("{}", err))
+}
+
+/// Fail on an error that `KeyValueDB` leaves no way to report.
+///
+/// Going on without the write, or with part of an iteration, would leave the node working on
+/// an inconsistent database.
+fn unreportable(err: sled::Error) -> ! {
+	log::error!(target: "db", "Sled database error: {}", err);
+	panic!("Sled database error: {}", err)
+}
+
+fn column_key(col: u32, key: &[u8]) -> Vec<u8> {
+	let mut column_key = Vec::with_capacity(4 + key.len());
+	column_key.extend_from_slice(&col.to_be_bytes());
+	column_key.extend_from_slice(key);
+	column_key
+}
+
+fn batch(transaction: DBTransaction) -> sled::Batch {
+	let mut batch = sled::Batch::default();
+	for op in transaction.ops {
+		match op {
+			DBOp::Insert { col, key, value } => batch.insert(column_key(col, &key), value),
+			DBOp::Delete { col, key } => batch.remove(column_key(col, &key)),
+		}
+	}
+	batch
+}
+
+impl KeyValueDB for SledDb {
+	fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> {
+		self.db.get(column_key(col, key))
+			.map(|value| value.map(|value| value.to_vec()))
+			.map_err(sled_err)
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.iter_from_prefix(col, prefix).next().map(|(_, value)| value)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		self.db.apply_batch(batch(transaction)).unwrap_or_else(unreportable)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+		self.db.apply_batch(batch(transaction)).map_err(sled_err)
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		self.db.flush().map(|_| ()).map_err(sled_err)
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.iter_from_prefix(col, &[])
+	}
+
+	fn iter_from_prefix<'a>(
+		&'a self,
+		col: u32,
+		prefix: &'a [u8],
+	) -> Box<dyn Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(self.db.scan_prefix(column_key(col, prefix))
+			.map(|entry| match entry {
+				Ok((key, value)) => (key[4..].into(), value[..].into()),
+				Err(e) => unreportable(e),
+			}))
+	}
+
+	fn restore(&self, _new_db: &str) -> io::Result<()> {
+		Err(io::Error::new(io::ErrorKind::Other, "Restoring is not supported by the sled database"))
+	}
+}
+
+impl MallocSizeOf for SledDb {
+	fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+		// sled bounds its own memory through the page cache capacity.
+		0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_keep_columns_apart() {
+		let db = SledDb::temporary().unwrap();
+		let mut transaction = db.transaction();
+		transaction.put(0, b"key", b"zero");
+		transaction.put(1, b"key", b"one");
+		transaction.put(1, b"key2", b"two");
+		db.write(transaction).unwrap();
+
+		assert_eq!(db.get(0, b"key").unwrap(), Some(b"zero".to_vec()));
+		assert_eq!(db.iter(0).count(), 1);
+		assert_eq!(
+			db.iter(1).map(|(key, _)| key.to_vec()).collect::<Vec<_>>(),
+			vec![b"key".to_vec(), b"key2".to_vec()],
+		);
+
+		let mut transaction = db.transaction();
+		transaction.delete(1, b"key");
+		db.write(transaction).unwrap();
+		assert_eq!(db.get(1, b"key").unwrap(), None);
+		assert_eq!(db.get_by_prefix(1, b"k"), Some(b"two".to_vec().into_boxed_slice()));
+	}
+}
@@ -0,0 +1,390 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State snapshots.
+//!
+//! A snapshot holds the state of the last finalized block, so that a new node can start from that block
+//! instead of replaying the chain from genesis. It is a gzip stream of the SCALE-encoded
+//! `SnapshotManifest`, followed by the storage entries and the auxiliary data of the consensus
+//! engines, up to an end marker.
+
+use std::io::{self, Read, Write};
+
+use codec::{Decode, Encode, IoReader};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use kvdb::DBTransaction;
+use sc_client_api::backend::{Backend as _, BlockImportOperation as _};
+use sp_blockchain::{
+	Backend as _, HeaderBackend, HeaderMetadata, Result as ClientResult, Error as ClientError,
+};
+use sp_core::H256;
+use sp_core::storage::{well_known_keys, ChildInfo, OwnedChildInfo, StorageChild};
+use sp_runtime::{Justification, Storage, generic::BlockId};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero, SaturatedConversion};
+use sp_state_machine::backend::Backend as StateBackend;
+
+use crate::{Backend, CachedHeaderMetadata, apply_state_commit, columns};
+use crate::utils::{self, db_err, meta_keys};
+
+/// Version of the snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Prefix of the storage key of default child tries, followed by their unique id.
+pub(crate) const DEFAULT_CHILD_STORAGE_KEY_PREFIX: &[u8] = b":child_storage:default:";
+
+/// Description of the block a snapshot restores.
+#[derive(Debug, Encode, Decode)]
+pub struct SnapshotManifest<Hash, Header> {
+	/// Version of the snapshot format.
+	pub version: u32,
+	/// Genesis hash of the chain.
+	pub genesis_hash: Hash,
+	/// Header of the block.
+	pub header: Header,
+	/// Justification of the block, if any.
+	pub justification: Option<Justification>,
+	/// State root of the block, which the imported state must match.
+	pub state_root: Hash,
+}
+
+#[derive(Encode, Decode)]
+enum SnapshotEntry {
+	/// Top-level storage key and value.
+	Top(Vec<u8>, Vec<u8>),
+	/// Storage key and unique id of a child trie, then the key and value.
+	Child(Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>),
+	/// Auxiliary data key and value.
+	Aux(Vec<u8>, Vec<u8>),
+	/// End of the snapshot.
+	End,
+}
+
+fn write_entry(output: &mut impl Write, entry: SnapshotEntry) -> ClientResult<()> {
+	output.write_all(&entry.encode()).map_err(db_err)
+}
+
+fn snapshot_err(msg: impl Into<String>) -> ClientError {
+	ClientError::Backend(format!("Invalid snapshot: {}", msg.into()))
+}
+
+impl<Block: BlockT<Hash=H256>> Backend<Block> {
+	/// Write a snapshot of the state of the last finalized block, which must be the best block.
+	///
+	/// The auxiliary data is only kept as of the latest imported block, so the snapshot can't be
+	/// of an older one: the consensus engines would restart from data about blocks the snapshot
+	/// doesn't have.
+	///
+	/// Default child tries are read with the unique id that follows `:child_storage:default:` in
+	/// their storage key, the way the runtime modules derive it.
+	pub fn export_snapshot(&self, output: impl Write) -> ClientResult<Block::Header> {
+		let (genesis_hash, hash) = {
+			let meta = self.blockchain.meta.read();
+			if meta.best_hash != meta.finalized_hash {
+				return Err(ClientError::Backend(format!(
+					"Best block #{} is not finalized; the last finalized block is #{}",
+					meta.best_number, meta.finalized_number,
+				)));
+			}
+			(meta.genesis_hash, meta.finalized_hash)
+		};
+		let header = self.blockchain.expect_header(BlockId::Hash(hash))?;
+
+		let manifest = SnapshotManifest {
+			version: SNAPSHOT_VERSION,
+			genesis_hash,
+			header: header.clone(),
+			justification: self.blockchain.justification(BlockId::Hash(hash))?,
+			state_root: *header.state_root(),
+		};
+		let mut output = GzEncoder::new(output, Compression::default());
+		output.write_all(&manifest.encode()).map_err(db_err)?;
+
+		let state = self.state_at(BlockId::Hash(hash))?;
+		for (key, value) in state.pairs() {
+			if !well_known_keys::is_child_storage_key(&key) {
+				write_entry(&mut output, SnapshotEntry::Top(key, value))?;
+				continue
+			}
+			if !key.starts_with(DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+				return Err(ClientError::Backend(format!("Unsupported child trie {:?}", key)));
+			}
+
+			let unique_id = key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..].to_vec();
+			let child_info = ChildInfo::new_default(&unique_id);
+			let mut child_keys = Vec::new();
+			state.for_keys_in_child_storage(&key, child_info, |child_key| child_keys.push(child_key.to_vec()));
+			for child_key in child_keys {
+				let child_value = state.child_storage(&key, child_info, &child_key)
+					.map_err(|e| ClientError::Backend(format!("Error reading state: {:?}", e)))?
+					.ok_or_else(|| ClientError::Backend(format!("Missing child value of {:?}", child_key)))?;
+				write_entry(
+					&mut output,
+					SnapshotEntry::Child(key.clone(), unique_id.clone(), child_key, child_value),
+				)?;
+			}
+		}
+
+		for (key, value) in self.storage.db.iter(columns::AUX) {
+			write_entry(&mut output, SnapshotEntry::Aux(key.to_vec(), value.to_vec()))?;
+		}
+		write_entry(&mut output, SnapshotEntry::End)?;
+		output.finish().map_err(db_err)?;
+
+		Ok(header)
+	}
+
+	/// Seed a new database with a snapshot.
+	///
+	/// The database must not have any block beyond the genesis, which must be the one of the
+	/// snapshot. The snapshot block becomes the best and finalized block; the blocks between the
+	/// genesis and it stay unknown.
+	pub fn import_snapshot(&self, input: impl Read) -> ClientResult<Block::Header> {
+		let mut input = IoReader(GzDecoder::new(input));
+		let manifest = SnapshotManifest::<Block::Hash, Block::Header>::decode(&mut input)
+			.map_err(|e| snapshot_err(e.what()))?;
+		if manifest.version != SNAPSHOT_VERSION {
+			return Err(snapshot_err(format!("unsupported version {}", manifest.version)));
+		}
+
+		{
+			let meta = self.blockchain.meta.read();
+			if manifest.genesis_hash != meta.genesis_hash {
+				return Err(snapshot_err("it belongs to another chain"));
+			}
+			if !meta.best_number.is_zero() || !meta.finalized_number.is_zero() {
+				return Err(ClientError::Backend("Snapshots can only be imported into a new database".into()));
+			}
+		}
+
+		let header = manifest.header;
+		let hash = header.hash();
+		let number = *header.number();
+		let parent_hash = *header.parent_hash();
+		if number.is_zero() {
+			return Err(snapshot_err("it is the genesis block"));
+		}
+
+		let mut storage = Storage::default();
+		let mut aux = Vec::new();
+		loop {
+			match SnapshotEntry::decode(&mut input).map_err(|e| snapshot_err(e.what()))? {
+				SnapshotEntry::Top(key, value) => {
+					storage.top.insert(key, value);
+				},
+				SnapshotEntry::Child(storage_key, unique_id, key, value) => {
+					storage.children.entry(storage_key)
+						.or_insert_with(|| StorageChild {
+							data: Default::default(),
+							child_info: OwnedChildInfo::new_default(unique_id),
+						})
+						.data.insert(key, value);
+				},
+				SnapshotEntry::Aux(key, value) => aux.push((key, value)),
+				SnapshotEntry::End => break,
+			}
+		}
+
+		let mut operation = self.begin_operation()?;
+		self.begin_state_operation(&mut operation, BlockId::Hash(Default::default()))?;
+		let root = operation.reset_storage(storage)?;
+		if root != manifest.state_root || root != *header.state_root() {
+			return Err(snapshot_err("its state does not match the state root"));
+		}
+
+		let mut changeset = sc_state_db::ChangeSet::default();
+		for (key, (value, rc)) in operation.db_updates.drain() {
+			if rc > 0 {
+				changeset.inserted.push((key, value.to_vec()));
+			}
+		}
+
+		// the state is committed as the child of a canonical parent without history.
+		let map_e = |e: sc_state_db::Error<io::Error>| ClientError::from(format!("State database error: {:?}", e));
+		let number_u64 = number.saturated_into::<u64>();
+		let state_db = &self.storage.state_db;
+		let mut transaction = DBTransaction::new();
+		apply_state_commit(&mut transaction, state_db.rebase(&parent_hash, number_u64 - 1).map_err(map_e)?);
+		apply_state_commit(&mut transaction, state_db.insert_block(&hash, number_u64, &parent_hash, changeset).map_err(map_e)?);
+		apply_state_commit(&mut transaction, state_db.canonicalize_block(&hash, &*self.storage).map_err(map_e)?);
+
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+		utils::insert_hash_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		utils::insert_number_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		transaction.put(columns::HEADER, &lookup_key, &header.encode());
+		if let Some(justification) = manifest.justification {
+			transaction.put(columns::JUSTIFICATION, &lookup_key, &justification.encode());
+		}
+		transaction.put(columns::META, meta_keys::BEST_BLOCK, &lookup_key);
+		transaction.put(columns::META, meta_keys::FINALIZED_BLOCK, &lookup_key);
+		transaction.put(columns::META, meta_keys::SNAPSHOT_BLOCK, &lookup_key);
+		for (key, value) in &aux {
+			transaction.put(columns::AUX, key, value);
+		}
+
+		{
+			// the genesis is no longer a leaf.
+			let mut leaves = self.blockchain.leaves.write();
+			leaves.import(hash, number, parent_hash);
+			leaves.finalize_height(number);
+			leaves.prepare_transaction(&mut transaction, columns::META, meta_keys::LEAF_PREFIX);
+		}
+
+		if let Err(e) = self.storage.db.write(transaction) {
+			state_db.revert_pending();
+			return Err(db_err(e));
+		}
+		state_db.apply_pending();
+
+		self.blockchain.insert_header_metadata(hash, CachedHeaderMetadata::from(&header));
+		self.blockchain.update_meta(hash, number, true, true);
+
+		Ok(header)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sc_client_api::backend::NewBlockState;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+	use super::*;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	fn commit_block(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		storage: Storage,
+	) -> H256 {
+		let state_at = if number == 0 {
+			Default::default()
+		} else {
+			parent_hash
+		};
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(state_at)).unwrap();
+		let state_root = op.reset_storage(storage).unwrap();
+		let header = Header {
+			number,
+			parent_hash,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, Some(vec![]), None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+		hash
+	}
+
+	fn top_storage(value: &[u8]) -> Storage {
+		Storage {
+			top: vec![(b"key".to_vec(), value.to_vec())].into_iter().collect(),
+			children: Default::default(),
+		}
+	}
+
+	#[test]
+	fn snapshot_restores_state_and_finalized_block() {
+		let backend = Backend::<Block>::new_test(16, 0);
+		let genesis_hash = commit_block(&backend, 0, Default::default(), top_storage(b"genesis"));
+
+		// block #1 sets the state exported by the snapshot.
+		let mut storage = top_storage(b"value");
+		storage.children.insert(
+			b":child_storage:default:child".to_vec(),
+			StorageChild {
+				data: vec![(b"child_key".to_vec(), b"child_value".to_vec())].into_iter().collect(),
+				child_info: OwnedChildInfo::new_default(b"child".to_vec()),
+			},
+		);
+		let hash = commit_block(&backend, 1, genesis_hash, storage);
+		backend.insert_aux(&[(&b"aux"[..], &b"data"[..])], &[]).unwrap();
+
+		let mut snapshot = Vec::new();
+		backend.export_snapshot(&mut snapshot).unwrap();
+
+		// the same chain, without block #1.
+		let restored = Backend::<Block>::new_test(16, 0);
+		commit_block(&restored, 0, Default::default(), top_storage(b"genesis"));
+		restored.import_snapshot(&snapshot[..]).unwrap();
+
+		let info = restored.blockchain().info();
+		assert_eq!((info.best_number, info.best_hash), (1, hash));
+		assert_eq!((info.finalized_number, info.finalized_hash), (1, hash));
+		assert_eq!(info.genesis_hash, genesis_hash);
+		assert_eq!(restored.get_aux(b"aux").unwrap(), Some(b"data".to_vec()));
+		let state = restored.state_at(BlockId::Hash(hash)).unwrap();
+		assert_eq!(state.storage(b"key").unwrap(), Some(b"value".to_vec()));
+		assert_eq!(
+			state.child_storage(
+				b":child_storage:default:child",
+				ChildInfo::new_default(b"child"),
+				b"child_key",
+			).unwrap(),
+			Some(b"child_value".to_vec()),
+		);
+
+		// the chain goes on from the snapshot block.
+		commit_block(&restored, 2, hash, top_storage(b"next"));
+		assert_eq!(restored.blockchain().info().finalized_number, 2);
+
+		// a snapshot can only seed a new database.
+		assert!(restored.import_snapshot(&snapshot[..]).is_err());
+	}
+
+	#[test]
+	fn snapshot_is_of_the_finalized_best_block() {
+		let backend = Backend::<Block>::new_test(16, 0);
+		let genesis_hash = commit_block(&backend, 0, Default::default(), top_storage(b"genesis"));
+		let hash_1 = commit_block(&backend, 1, genesis_hash, top_storage(b"one"));
+		backend.insert_aux(&[(&b"aux"[..], &b"one"[..])], &[]).unwrap();
+		let hash_2 = commit_block(&backend, 2, hash_1, top_storage(b"two"));
+
+		// block #3 is the best block, but isn't finalized, and the aux data already is about it.
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(hash_2)).unwrap();
+		let state_root = op.reset_storage(top_storage(b"three")).unwrap();
+		let header = Header {
+			number: 3,
+			parent_hash: hash_2,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash_3 = header.hash();
+		op.set_block_data(header, Some(vec![]), None, NewBlockState::Best).unwrap();
+		backend.commit_operation(op).unwrap();
+		backend.insert_aux(&[(&b"aux"[..], &b"three"[..])], &[]).unwrap();
+
+		let mut snapshot = Vec::new();
+		assert!(backend.export_snapshot(&mut snapshot).is_err());
+
+		backend.finalize_block(BlockId::Hash(hash_3), None).unwrap();
+		let mut snapshot = Vec::new();
+		assert_eq!(backend.export_snapshot(&mut snapshot).unwrap().hash(), hash_3);
+
+		// the aux data matches the state of the snapshot block, not of an older one.
+		let restored = Backend::<Block>::new_test(16, 0);
+		commit_block(&restored, 0, Default::default(), top_storage(b"genesis"));
+		restored.import_snapshot(&snapshot[..]).unwrap();
+		assert_eq!(restored.blockchain().info().finalized_hash, hash_3);
+		assert_eq!(restored.get_aux(b"aux").unwrap(), Some(b"three".to_vec()));
+		let state = restored.state_at(BlockId::Hash(hash_3)).unwrap();
+		assert_eq!(state.storage(b"key").unwrap(), Some(b"three".to_vec()));
+		assert!(restored.state_at(BlockId::Hash(hash_2)).is_err());
+	}
+}
@@ -56,6 +56,8 @@ pub mod meta_keys {
 	pub const LEAF_PREFIX: &[u8; 4] = b"leaf";
 	/// Children prefix list key.
 	pub const CHILDREN_PREFIX: &[u8; 8] = b"children";
+	/// Lookup key of the block a state snapshot was imported at.
+	pub const SNAPSHOT_BLOCK: &[u8; 8] = b"snapshot";
 }
 
 /// Database metadata.
@@ -197,12 +199,27 @@ pub fn block_id_to_lookup_key<Block>(
 	res.map_err(db_err)
 }
 
+/// Create an empty database for tests.
+///
+/// With the `sled` feature the tests run against a temporary sled database, otherwise in memory.
+#[cfg(any(test, feature = "test-helpers"))]
+pub fn new_test_database() -> Arc<dyn KeyValueDB> {
+	#[cfg(feature = "sled")]
+	{
+		Arc::new(crate::sled_db::SledDb::temporary().expect("failed to create test-db"))
+	}
+	#[cfg(not(feature = "sled"))]
+	{
+		Arc::new(kvdb_memorydb::create(NUM_COLUMNS))
+	}
+}
+
 /// Maps database error to client error
 pub fn db_err(err: io::Error) -> sp_blockchain::Error {
 	sp_blockchain::Error::Backend(format!("{}", err))
 }
 
-/// Open RocksDB database.
+/// Open the database, RocksDB or sled depending on the settings.
 pub fn open_database(
 	config: &DatabaseSettings,
 	col_meta: u32,
@@ -237,6 +254,15 @@ pub fn open_database(
 			let msg = "Try to open RocksDB database with RocksDB disabled".into();
 			return Err(sp_blockchain::Error::Backend(msg));
 		},
+		#[cfg(feature = "sled")]
+		DatabaseSettingsSrc::Sled { path, cache_size } => {
+			Arc::new(crate::sled_db::SledDb::open(path, *cache_size).map_err(db_err)?)
+		},
+		#[cfg(not(feature = "sled"))]
+		DatabaseSettingsSrc::Sled { .. } => {
+			let msg = "Try to open sled database with sled disabled".into();
+			return Err(sp_blockchain::Error::Backend(msg));
+		},
 		DatabaseSettingsSrc::Custom(db) => db.clone(),
 	};
 
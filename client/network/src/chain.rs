@@ -62,6 +62,15 @@ pub trait Client<Block: BlockT>: Send + Sync {
 		keys: &[Vec<u8>],
 	) -> Result<StorageProof, Error>;
 
+	/// Get proof of a page of storage key/value pairs with given prefix.
+	fn read_keys_proof(
+		&self,
+		block: &Block::Hash,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Result<StorageProof, Error>;
+
 	/// Get method execution proof.
 	fn execution_proof(&self, block: &Block::Hash, method: &str, data: &[u8]) -> Result<(Vec<u8>, StorageProof), Error>;
 
@@ -144,6 +153,17 @@ impl<B, E, Block, RA> Client<Block> for SubstrateClient<B, E, Block, RA> where
 			.read_child_proof(&BlockId::Hash(block.clone()), storage_key, child_info, keys)
 	}
 
+	fn read_keys_proof(
+		&self,
+		block: &Block::Hash,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Result<StorageProof, Error> {
+		(self as &SubstrateClient<B, E, Block, RA>)
+			.read_keys_proof(&BlockId::Hash(block.clone()), prefix, start_key, count)
+	}
+
 	fn execution_proof(&self, block: &Block::Hash, method: &str, data: &[u8]) -> Result<(Vec<u8>, StorageProof), Error> {
 		(self as &SubstrateClient<B, E, Block, RA>).execution_proof(&BlockId::Hash(block.clone()), method, data)
 	}
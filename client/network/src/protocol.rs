@@ -75,14 +75,18 @@ const MAX_KNOWN_BLOCKS: usize = 1024; // ~32kb per peer + LruHashSet overhead
 const MAX_KNOWN_EXTRINSICS: usize = 4096; // ~128kb per peer + overhead
 
 /// Current protocol version.
-pub(crate) const CURRENT_VERSION: u32 = 5;
+pub(crate) const CURRENT_VERSION: u32 = 6;
 /// Lowest version we support
 pub(crate) const MIN_VERSION: u32 = 3;
+/// Lowest version that handles `RemoteReadKeysRequest`.
+pub(crate) const READ_KEYS_VERSION: u32 = 6;
 
 // Maximum allowed entries in `BlockResponse`
 const MAX_BLOCK_DATA_RESPONSE: u32 = 128;
 // Maximum allowed entries in `ConsensusBatch`
 const MAX_CONSENSUS_MESSAGES: usize = 256;
+// Maximum allowed keys in `RemoteReadKeysRequest`
+const MAX_READ_KEYS_COUNT: u32 = 1000;
 /// When light node connects to the full node and the full node is behind light node
 /// for at least `LIGHT_MAXIMAL_BLOCKS_DIFFERENCE` blocks, we consider it unuseful
 /// and disconnect to free connection slot.
@@ -254,6 +258,26 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 		self.behaviour.send_packet(who, message.encode())
 	}
 
+	fn send_read_keys_request(
+		&mut self,
+		who: &PeerId,
+		id: RequestId,
+		block: <B as BlockT>::Hash,
+		prefix: Vec<u8>,
+		start_key: Option<Vec<u8>>,
+		count: u32,
+	) {
+		let message: Message<B> = message::generic::Message::RemoteReadKeysRequest(message::RemoteReadKeysRequest {
+			id,
+			block,
+			prefix,
+			start_key,
+			count,
+		});
+
+		self.behaviour.send_packet(who, message.encode())
+	}
+
 	fn send_call_request(
 		&mut self,
 		who: &PeerId,
@@ -659,6 +683,8 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				return self.on_finality_proof_response(who, response),
 			GenericMessage::RemoteReadChildRequest(request) =>
 				self.on_remote_read_child_request(who, request),
+			GenericMessage::RemoteReadKeysRequest(request) =>
+				self.on_remote_read_keys_request(who, request),
 			GenericMessage::Consensus(msg) =>
 				return if self.registered_notif_protocols.contains(&msg.engine_id) {
 					CustomMessageOutcome::NotificationsReceived {
@@ -1058,7 +1084,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		self.light_dispatch.on_connect(LightDispatchIn {
 			behaviour: &mut self.behaviour,
 			peerset: self.peerset_handle.clone(),
-		}, who.clone(), status.roles, status.best_number);
+		}, who.clone(), status.roles, status.best_number, status.version);
 		if info.roles.is_full() {
 			match self.sync.new_peer(who.clone(), info.best_hash, info.best_number) {
 				Ok(None) => (),
@@ -1599,6 +1625,51 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		);
 	}
 
+	fn on_remote_read_keys_request(
+		&mut self,
+		who: PeerId,
+		request: message::RemoteReadKeysRequest<B::Hash>,
+	) {
+		trace!(target: "sync", "Remote read keys request {} from {} ({} after {:?} at {})",
+			request.id,
+			who,
+			request.prefix.to_hex::<String>(),
+			request.start_key.as_ref().map(|key| key.to_hex::<String>()),
+			request.block,
+		);
+		let proof = if request.count > MAX_READ_KEYS_COUNT {
+			trace!(target: "sync", "Remote read keys request {} from {} asks for {} keys, more than {}",
+				request.id, who, request.count, MAX_READ_KEYS_COUNT);
+			StorageProof::empty()
+		} else {
+			match self.context_data.chain.read_keys_proof(
+				&request.block,
+				&request.prefix,
+				request.start_key.as_ref().map(|key| &key[..]),
+				request.count as usize,
+			) {
+				Ok(proof) => proof,
+				Err(error) => {
+					trace!(target: "sync", "Remote read keys request {} from {} ({} at {}) failed with: {}",
+						request.id,
+						who,
+						request.prefix.to_hex::<String>(),
+						request.block,
+						error
+					);
+					StorageProof::empty()
+				}
+			}
+		};
+		self.send_message(
+			&who,
+			GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+				id: request.id,
+				proof,
+			}),
+		);
+	}
+
 	fn on_remote_read_response(
 		&mut self,
 		who: PeerId,
@@ -28,8 +28,9 @@ use linked_hash_map::{Entry, LinkedHashMap};
 use sp_blockchain::Error as ClientError;
 use sc_client_api::{FetchChecker, RemoteHeaderRequest,
 	RemoteCallRequest, RemoteReadRequest, RemoteChangesRequest, ChangesProof,
-	RemoteReadChildRequest, RemoteBodyRequest, StorageProof};
+	RemoteReadChildRequest, RemoteReadKeysRequest, RemoteBodyRequest, StorageProof};
 use crate::message::{self, BlockAttributes, Direction, FromBlock, RequestId};
+use crate::protocol::{MIN_VERSION, READ_KEYS_VERSION};
 use libp2p::PeerId;
 use crate::config::Roles;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
@@ -74,6 +75,17 @@ pub trait LightDispatchNetwork<B: BlockT> {
 		keys: Vec<Vec<u8>>,
 	);
 
+	/// Send to `who` a keys read request.
+	fn send_read_keys_request(
+		&mut self,
+		who: &PeerId,
+		id: RequestId,
+		block: <B as BlockT>::Hash,
+		prefix: Vec<u8>,
+		start_key: Option<Vec<u8>>,
+		count: u32,
+	);
+
 	/// Send to `who` a call request.
 	fn send_call_request(
 		&mut self,
@@ -126,6 +138,8 @@ pub struct LightDispatch<B: BlockT> {
 	idle_peers: VecDeque<PeerId>,
 	/// Best known block for each node in `active_peers` and `idle_peers`.
 	best_blocks: HashMap<PeerId, NumberFor<B>>,
+	/// Protocol version of each node in `active_peers` and `idle_peers`.
+	versions: HashMap<PeerId, u32>,
 }
 
 struct Request<Block: BlockT> {
@@ -152,6 +166,10 @@ pub(crate) enum RequestData<Block: BlockT> {
 		RemoteReadChildRequest<Block::Header>,
 		OneShotSender<Result<HashMap<Vec<u8>, Option<Vec<u8>>>, ClientError>>
 	),
+	RemoteReadKeys(
+		RemoteReadKeysRequest<Block::Header>,
+		OneShotSender<Result<Vec<(Vec<u8>, Vec<u8>)>, ClientError>>
+	),
 	RemoteCall(RemoteCallRequest<Block::Header>, OneShotSender<Result<Vec<u8>, ClientError>>),
 	RemoteChanges(
 		RemoteChangesRequest<Block::Header>,
@@ -198,6 +216,14 @@ impl<Block: BlockT> FetchChecker<Block> for AlwaysBadChecker {
 		Err(ClientError::Msg("AlwaysBadChecker".into()))
 	}
 
+	fn check_read_keys_proof(
+		&self,
+		_request: &RemoteReadKeysRequest<Block::Header>,
+		_remote_proof: StorageProof,
+	) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ClientError> {
+		Err(ClientError::Msg("AlwaysBadChecker".into()))
+	}
+
 	fn check_execution_proof(
 		&self,
 		_request: &RemoteCallRequest<Block::Header>,
@@ -235,6 +261,7 @@ impl<B: BlockT> LightDispatch<B> where
 			active_peers: LinkedHashMap::new(),
 			idle_peers: VecDeque::new(),
 			best_blocks: HashMap::new(),
+			versions: HashMap::new(),
 		}
 	}
 
@@ -317,14 +344,16 @@ impl<B: BlockT> LightDispatch<B> where
 		network: impl LightDispatchNetwork<B>,
 		peer: PeerId,
 		role: Roles,
-		best_number: NumberFor<B>
+		best_number: NumberFor<B>,
+		version: u32,
 	) {
 		if !role.is_full() {
 			return;
 		}
 
 		self.idle_peers.push_back(peer.clone());
-		self.best_blocks.insert(peer, best_number);
+		self.best_blocks.insert(peer.clone(), best_number);
+		self.versions.insert(peer, version);
 
 		self.dispatch(network);
 	}
@@ -416,6 +445,18 @@ impl<B: BlockT> LightDispatch<B> where
 						RequestData::RemoteReadChild(request, sender)
 					),
 			}},
+			RequestData::RemoteReadKeys(request, sender) => {
+				match checker.check_read_keys_proof(&request, response.proof) {
+					Ok(response) => {
+						// we do not bother if receiver has been dropped already
+						let _ = sender.send(Ok(response));
+						Accept::Ok
+					},
+					Err(error) => Accept::CheckFailed(
+						error,
+						RequestData::RemoteReadKeys(request, sender)
+					),
+			}},
 			data => Accept::Unexpected(data),
 		})
 	}
@@ -524,6 +565,7 @@ impl<B: BlockT> LightDispatch<B> where
 	/// Puts back the active request that this node was performing into `pending_requests`.
 	fn remove_peer(&mut self, peer: PeerId) {
 		self.best_blocks.remove(&peer);
+		self.versions.remove(&peer);
 
 		if let Some(request) = self.active_peers.remove(&peer) {
 			self.pending_requests.push_front(request);
@@ -535,8 +577,30 @@ impl<B: BlockT> LightDispatch<B> where
 		}
 	}
 
+	/// Fails the pending requests that none of the connected nodes understands.
+	///
+	/// They would otherwise wait for a node running a newer version to connect.
+	fn fail_unsupported_requests(&mut self) {
+		if self.versions.is_empty() {
+			return;
+		}
+		let best_version = self.versions.values().cloned().max().unwrap_or(0);
+		let (unsupported, supported) = self.pending_requests.drain(..)
+			.partition::<VecDeque<_>, _>(|request| request.required_version() > best_version);
+		self.pending_requests = supported;
+		for request in unsupported {
+			trace!(target: "sync", "No peer supports remote request {}", request.id);
+			request.data.fail(ClientError::Msg(format!(
+				"Remote request needs protocol version {}, which no peer supports",
+				request.required_version(),
+			)));
+		}
+	}
+
 	/// Dispatches pending requests.
 	fn dispatch(&mut self, mut network: impl LightDispatchNetwork<B>) {
+		self.fail_unsupported_requests();
+
 		let mut last_peer = self.idle_peers.back().cloned();
 		let mut unhandled_requests = VecDeque::new();
 
@@ -559,7 +623,11 @@ impl<B: BlockT> LightDispatch<B> where
 					.expect("entries are inserted into best_blocks when peer is connected;
 						entries are removed from best_blocks when peer is disconnected;
 						peer is in idle_peers and thus connected; qed");
-				request.required_block() <= *peer_best_block
+				let peer_version = self.versions.get(&peer)
+					.expect("entries are inserted into versions when peer is connected;
+						entries are removed from versions when peer is disconnected;
+						peer is in idle_peers and thus connected; qed");
+				request.required_block() <= *peer_best_block && request.required_version() <= *peer_version
 			};
 
 			if !can_be_processed_by_peer {
@@ -590,6 +658,15 @@ impl<B: BlockT> LightDispatch<B> where
 }
 
 impl<Block: BlockT> Request<Block> {
+	/// Returns the protocol version that the remote needs to advertise in order to understand
+	/// this request.
+	fn required_version(&self) -> u32 {
+		match self.data {
+			RequestData::RemoteReadKeys(..) => READ_KEYS_VERSION,
+			_ => MIN_VERSION,
+		}
+	}
+
 	/// Returns the block that the remote needs to have in order to be able to fulfill
 	/// this request.
 	fn required_block(&self) -> NumberFor<Block> {
@@ -597,6 +674,7 @@ impl<Block: BlockT> Request<Block> {
 			RequestData::RemoteHeader(ref data, _) => data.block,
 			RequestData::RemoteRead(ref data, _) => *data.header.number(),
 			RequestData::RemoteReadChild(ref data, _) => *data.header.number(),
+			RequestData::RemoteReadKeys(ref data, _) => *data.header.number(),
 			RequestData::RemoteCall(ref data, _) => *data.header.number(),
 			RequestData::RemoteChanges(ref data, _) => data.max_block.0,
 			RequestData::RemoteBody(ref data, _) => *data.header.number(),
@@ -628,6 +706,15 @@ impl<Block: BlockT> Request<Block> {
 					data.child_type,
 					data.keys.clone(),
 				),
+			RequestData::RemoteReadKeys(ref data, _) =>
+				out.send_read_keys_request(
+					peer,
+					self.id,
+					data.block,
+					data.prefix.clone(),
+					data.start_key.clone(),
+					data.count,
+				),
 			RequestData::RemoteCall(ref data, _) =>
 				out.send_call_request(
 					peer,
@@ -669,6 +756,7 @@ impl<Block: BlockT> RequestData<Block> {
 			RequestData::RemoteCall(_, sender) => { let _ = sender.send(Err(error)); },
 			RequestData::RemoteRead(_, sender) => { let _ = sender.send(Err(error)); },
 			RequestData::RemoteReadChild(_, sender) => { let _ = sender.send(Err(error)); },
+			RequestData::RemoteReadKeys(_, sender) => { let _ = sender.send(Err(error)); },
 			RequestData::RemoteChanges(_, sender) => { let _ = sender.send(Err(error)); },
 			RequestData::RemoteBody(_, sender) => { let _ = sender.send(Err(error)); },
 		}
@@ -686,10 +774,11 @@ pub mod tests {
 	use sp_blockchain::{Error as ClientError, Result as ClientResult};
 	use sc_client_api::{FetchChecker, RemoteHeaderRequest,
 		ChangesProof, RemoteCallRequest, RemoteReadRequest,
-		RemoteReadChildRequest, RemoteChangesRequest, RemoteBodyRequest};
+		RemoteReadChildRequest, RemoteReadKeysRequest, RemoteChangesRequest, RemoteBodyRequest};
 	use crate::config::Roles;
 	use crate::message::{self, BlockAttributes, Direction, FromBlock, RequestId};
 	use libp2p::PeerId;
+	use crate::protocol::{CURRENT_VERSION, READ_KEYS_VERSION};
 	use super::{REQUEST_TIMEOUT, LightDispatch, LightDispatchNetwork, RequestData, StorageProof};
 	use sp_test_primitives::{changes_trie_config, Block, Extrinsic, Header};
 
@@ -740,6 +829,17 @@ pub mod tests {
 			}
 		}
 
+		fn check_read_keys_proof(
+			&self,
+			request: &RemoteReadKeysRequest<Header>,
+			_: StorageProof,
+		) -> ClientResult<Vec<(Vec<u8>, Vec<u8>)>> {
+			match self.ok {
+				true => Ok(vec![(request.prefix.clone(), vec![42])]),
+				false => Err(ClientError::Backend("Test error".into())),
+			}
+		}
+
 		fn check_execution_proof(&self, _: &RemoteCallRequest<Header>, _: StorageProof) -> ClientResult<Vec<u8>> {
 			match self.ok {
 				true => Ok(vec![42]),
@@ -814,6 +914,8 @@ pub mod tests {
 		fn send_read_request(&mut self, _: &PeerId, _: RequestId, _: <B as BlockT>::Hash, _: Vec<Vec<u8>>) {}
 		fn send_read_child_request(&mut self, _: &PeerId, _: RequestId, _: <B as BlockT>::Hash, _: Vec<u8>,
 			_: Vec<u8>, _: u32, _: Vec<Vec<u8>>) {}
+		fn send_read_keys_request(&mut self, _: &PeerId, _: RequestId, _: <B as BlockT>::Hash, _: Vec<u8>,
+			_: Option<Vec<u8>>, _: u32) {}
 		fn send_call_request(&mut self, _: &PeerId, _: RequestId, _: <B as BlockT>::Hash, _: String, _: Vec<u8>) {}
 		fn send_changes_request(&mut self, _: &PeerId, _: RequestId, _: <B as BlockT>::Hash, _: <B as BlockT>::Hash,
 			_: <B as BlockT>::Hash, _: <B as BlockT>::Hash, _: Option<Vec<u8>>, _: Vec<u8>) {}
@@ -832,9 +934,9 @@ pub mod tests {
 		let peer0 = PeerId::random();
 		let peer1 = PeerId::random();
 		let peer2 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0, Roles::LIGHT, 1000);
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 2000);
-		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::AUTHORITY, 3000);
+		light_dispatch.on_connect(&mut network_interface, peer0, Roles::LIGHT, 1000, CURRENT_VERSION);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 2000, CURRENT_VERSION);
+		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::AUTHORITY, 3000, CURRENT_VERSION);
 		assert_eq!(vec![peer1.clone(), peer2.clone()], light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert_eq!(light_dispatch.best_blocks.get(&peer1), Some(&2000));
 		assert_eq!(light_dispatch.best_blocks.get(&peer2), Some(&3000));
@@ -846,7 +948,7 @@ pub mod tests {
 
 		let mut network_interface = DummyNetwork::default();
 		let mut light_dispatch = dummy(true);
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 100);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 100, CURRENT_VERSION);
 		assert_eq!(1, total_peers(&light_dispatch));
 		assert!(!light_dispatch.best_blocks.is_empty());
 
@@ -861,8 +963,8 @@ pub mod tests {
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
 		let peer1 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 		assert_eq!(vec![peer0.clone(), peer1.clone()], light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert!(light_dispatch.active_peers.is_empty());
 
@@ -888,7 +990,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let peer0 = PeerId::random();
 		let mut network_interface = DummyNetwork::default();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteCall(RemoteCallRequest {
 			block: Default::default(),
@@ -915,7 +1017,7 @@ pub mod tests {
 			retry_count: Some(1),
 		}, oneshot::channel().0));
 
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 		receive_call_response(&mut network_interface, &mut light_dispatch, peer0.clone(), 0);
 		assert_disconnected_peer(&network_interface);
 		assert_eq!(light_dispatch.pending_requests.len(), 1);
@@ -926,7 +1028,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		receive_call_response(&mut network_interface, &mut light_dispatch, peer0, 0);
 		assert_disconnected_peer(&network_interface);
@@ -937,7 +1039,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(false);
 		let peer0 = PeerId::random();
 		let mut network_interface = DummyNetwork::default();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteCall(RemoteCallRequest {
 			block: Default::default(),
@@ -962,7 +1064,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(false);
 		let mut network_interface = DummyNetwork::default();
 		for i in 0..retry_count+1 {
-			light_dispatch.on_connect(&mut network_interface, peer_ids[i].clone(), Roles::FULL, 1000);
+			light_dispatch.on_connect(&mut network_interface, peer_ids[i].clone(), Roles::FULL, 1000, CURRENT_VERSION);
 		}
 
 		let (tx, mut response) = oneshot::channel();
@@ -987,7 +1089,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		let (tx, response) = oneshot::channel();
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteCall(RemoteCallRequest {
@@ -1007,7 +1109,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		let (tx, response) = oneshot::channel();
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteRead(RemoteReadRequest {
@@ -1029,7 +1131,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		let (tx, response) = oneshot::channel();
 		let child_info = ChildInfo::new_default(b"unique_id_1");
@@ -1052,12 +1154,85 @@ pub mod tests {
 		assert_eq!(response.wait().unwrap().unwrap().remove(b":key".as_ref()).unwrap(), Some(vec![42]));
 	}
 
+	#[test]
+	fn receives_remote_read_keys_response() {
+		let mut light_dispatch = dummy(true);
+		let mut network_interface = DummyNetwork::default();
+		let peer0 = PeerId::random();
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
+
+		let (tx, response) = oneshot::channel();
+		light_dispatch.add_request(&mut network_interface, RequestData::RemoteReadKeys(RemoteReadKeysRequest {
+			header: dummy_header(),
+			block: Default::default(),
+			prefix: b":key".to_vec(),
+			start_key: None,
+			count: 10,
+			retry_count: None,
+		}, tx));
+
+		light_dispatch.on_remote_read_response(&mut network_interface, peer0.clone(), message::RemoteReadResponse {
+			id: 0,
+			proof: StorageProof::empty(),
+		});
+		assert_eq!(response.wait().unwrap().unwrap(), vec![(b":key".to_vec(), vec![42])]);
+	}
+
+	#[test]
+	fn remote_read_keys_request_is_only_sent_to_peers_supporting_it() {
+		let mut light_dispatch = dummy(true);
+		let mut network_interface = DummyNetwork::default();
+		let peer0 = PeerId::random();
+		let peer1 = PeerId::random();
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, READ_KEYS_VERSION - 1);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 1000, READ_KEYS_VERSION);
+
+		let (tx, response) = oneshot::channel();
+		light_dispatch.add_request(&mut network_interface, RequestData::RemoteReadKeys(RemoteReadKeysRequest {
+			header: dummy_header(),
+			block: Default::default(),
+			prefix: b":key".to_vec(),
+			start_key: None,
+			count: 10,
+			retry_count: None,
+		}, tx));
+		assert_eq!(vec![peer0.clone()], light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>());
+		assert_eq!(vec![peer1.clone()], light_dispatch.active_peers.keys().cloned().collect::<Vec<_>>());
+
+		light_dispatch.on_remote_read_response(&mut network_interface, peer1.clone(), message::RemoteReadResponse {
+			id: 0,
+			proof: StorageProof::empty(),
+		});
+		assert_eq!(response.wait().unwrap().unwrap(), vec![(b":key".to_vec(), vec![42])]);
+	}
+
+	#[test]
+	fn remote_read_keys_request_fails_when_no_peer_supports_it() {
+		let mut light_dispatch = dummy(true);
+		let mut network_interface = DummyNetwork::default();
+		let peer0 = PeerId::random();
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, READ_KEYS_VERSION - 1);
+
+		let (tx, response) = oneshot::channel();
+		light_dispatch.add_request(&mut network_interface, RequestData::RemoteReadKeys(RemoteReadKeysRequest {
+			header: dummy_header(),
+			block: Default::default(),
+			prefix: b":key".to_vec(),
+			start_key: None,
+			count: 10,
+			retry_count: None,
+		}, tx));
+		assert!(light_dispatch.pending_requests.is_empty());
+		assert!(light_dispatch.active_peers.is_empty());
+		assert!(response.wait().unwrap().is_err());
+	}
+
 	#[test]
 	fn receives_remote_header_response() {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		let (tx, response) = oneshot::channel();
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteHeader(RemoteHeaderRequest {
@@ -1088,7 +1263,7 @@ pub mod tests {
 		let mut light_dispatch = dummy(true);
 		let mut network_interface = DummyNetwork::default();
 		let peer0 = PeerId::random();
-		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000, CURRENT_VERSION);
 
 		let (tx, response) = oneshot::channel();
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteChanges(RemoteChangesRequest {
@@ -1119,7 +1294,7 @@ pub mod tests {
 		let peer1 = PeerId::random();
 		let peer2 = PeerId::random();
 
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 100);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 100, CURRENT_VERSION);
 
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteHeader(RemoteHeaderRequest {
 			cht_root: Default::default(),
@@ -1137,7 +1312,7 @@ pub mod tests {
 			retry_count: None,
 		}, oneshot::channel().0));
 
-		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::FULL, 150);
+		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::FULL, 150, CURRENT_VERSION);
 
 		assert_eq!(vec![peer1.clone(), peer2.clone()], light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert_eq!(light_dispatch.pending_requests.len(), 3);
@@ -1184,9 +1359,9 @@ pub mod tests {
 			retry_count: None,
 		}, oneshot::channel().0));
 
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 200);
-		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::FULL, 200);
-		light_dispatch.on_connect(&mut network_interface, peer3.clone(), Roles::FULL, 250);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 200, CURRENT_VERSION);
+		light_dispatch.on_connect(&mut network_interface, peer2.clone(), Roles::FULL, 200, CURRENT_VERSION);
+		light_dispatch.on_connect(&mut network_interface, peer3.clone(), Roles::FULL, 250, CURRENT_VERSION);
 
 		assert_eq!(vec![peer1.clone(), peer2.clone()], light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>());
 		assert_eq!(light_dispatch.pending_requests.len(), 1);
@@ -1209,7 +1384,7 @@ pub mod tests {
 			retry_count: None,
 		}, oneshot::channel().0));
 
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250, CURRENT_VERSION);
 
 		assert!(light_dispatch.idle_peers.iter().cloned().collect::<Vec<_>>().is_empty());
 		assert_eq!(light_dispatch.pending_requests.len(), 1);
@@ -1222,7 +1397,7 @@ pub mod tests {
 		let peer1 = PeerId::random();
 
 		let header = dummy_header();
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250, CURRENT_VERSION);
 
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteBody(RemoteBodyRequest {
 			header: header.clone(),
@@ -1259,7 +1434,7 @@ pub mod tests {
 		let peer1 = PeerId::random();
 
 		let header = dummy_header();
-		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 250, CURRENT_VERSION);
 
 		light_dispatch.add_request(&mut network_interface, RequestData::RemoteBody(RemoteBodyRequest {
 			header: header.clone(),
@@ -24,7 +24,7 @@ pub use self::generic::{
 	RemoteHeaderRequest, RemoteHeaderResponse,
 	RemoteChangesRequest, RemoteChangesResponse,
 	FinalityProofRequest, FinalityProofResponse,
-	FromBlock, RemoteReadChildRequest,
+	FromBlock, RemoteReadChildRequest, RemoteReadKeysRequest,
 };
 use sc_client_api::StorageProof;
 
@@ -219,6 +219,8 @@ pub mod generic {
 		FinalityProofResponse(FinalityProofResponse<Hash>),
 		/// Batch of consensus protocol messages.
 		ConsensusBatch(Vec<ConsensusMessage>),
+		/// Remote storage keys read request.
+		RemoteReadKeysRequest(RemoteReadKeysRequest<Hash>),
 		/// Chain-specific message.
 		#[codec(index = "255")]
 		ChainSpecific(Vec<u8>),
@@ -246,6 +248,7 @@ pub mod generic {
 				Message::FinalityProofRequest(_) => "FinalityProofRequest",
 				Message::FinalityProofResponse(_) => "FinalityProofResponse",
 				Message::ConsensusBatch(_) => "ConsensusBatch",
+				Message::RemoteReadKeysRequest(_) => "RemoteReadKeysRequest",
 				Message::ChainSpecific(_) => "ChainSpecific",
 			}
 		}
@@ -377,6 +380,21 @@ pub mod generic {
 		pub keys: Vec<Vec<u8>>,
 	}
 
+	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+	/// Remote storage keys read request.
+	pub struct RemoteReadKeysRequest<H> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// Block at which to perform call.
+		pub block: H,
+		/// Prefix of the keys to read.
+		pub prefix: Vec<u8>,
+		/// Only read keys strictly after this one.
+		pub start_key: Option<Vec<u8>>,
+		/// Maximal number of keys to read.
+		pub count: u32,
+	}
+
 	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
 	/// Remote header request.
 	pub struct RemoteHeaderRequest<N> {
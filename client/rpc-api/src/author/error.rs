@@ -90,6 +90,10 @@ const POOL_CYCLE_DETECTED: i64 = POOL_INVALID_TX + 5;
 const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
 /// The key type crypto is not known.
 const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
+/// The transaction to replace is not ready in the pool.
+const POOL_REPLACED_NOT_READY: i64 = POOL_INVALID_TX + 8;
+/// The replacement doesn't take the slot of the transaction it should replace.
+const POOL_NOT_A_REPLACEMENT: i64 = POOL_INVALID_TX + 9;
 
 impl From<Error> for rpc::Error {
 	fn from(e: Error) -> Self {
@@ -126,10 +130,20 @@ impl From<Error> for rpc::Error {
 				message: "Transaction Already Imported".into(),
 				data: Some(format!("{:?}", hash).into()),
 			},
-			Error::Pool(PoolError::TooLowPriority { old, new }) => rpc::Error {
+			Error::Pool(PoolError::TooLowPriority { old, new, required }) => rpc::Error {
 				code: rpc::ErrorCode::ServerError(POOL_TOO_LOW_PRIORITY),
-				message: format!("Priority is too low: ({} vs {})", old, new),
-				data: Some("The transaction has too low priority to replace another transaction already in the pool.".into()),
+				message: format!("Priority is too low: ({} vs {}, {} required)", old, new, required),
+				data: Some(serde_json::json!({ "old": old, "new": new, "required": required })),
+			},
+			Error::Pool(PoolError::ReplacedNotReady) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(POOL_REPLACED_NOT_READY),
+				message: "Replaced Transaction Not Ready".into(),
+				data: Some("The transaction to replace is not in the ready queue of the pool.".into()),
+			},
+			Error::Pool(PoolError::NotAReplacement) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(POOL_NOT_A_REPLACEMENT),
+				message: "Not A Replacement".into(),
+				data: Some("The transaction doesn't provide any tag of the one it should replace.".into()),
 			},
 			Error::Pool(PoolError::CycleDetected) => rpc::Error {
 				code: rpc::ErrorCode::ServerError(POOL_CYCLE_DETECTED),
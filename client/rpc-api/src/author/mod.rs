@@ -22,7 +22,7 @@ pub mod hash;
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use sp_core::Bytes;
-use sp_transaction_pool::TransactionStatus;
+use sp_transaction_pool::{PoolStatus, TransactionStatus};
 use self::error::{FutureResult, Result};
 
 pub use self::gen_client::Client as AuthorClient;
@@ -37,6 +37,13 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_submitExtrinsic")]
 	fn submit_extrinsic(&self, extrinsic: Bytes) -> FutureResult<Hash>;
 
+	/// Submit hex-encoded extrinsic in place of the ready extrinsic `old`, e.g. to speed it up.
+	///
+	/// The extrinsic has to take the place of `old`, e.g. by using the same nonce, and beat its
+	/// priority by the minimal bump of the pool.
+	#[rpc(name = "author_replaceExtrinsic")]
+	fn replace_extrinsic(&self, old: Hash, extrinsic: Bytes) -> FutureResult<Hash>;
+
 	/// Insert a key into the keystore.
 	#[rpc(name = "author_insertKey")]
 	fn insert_key(&self,
@@ -53,6 +60,10 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_pendingExtrinsics")]
 	fn pending_extrinsics(&self) -> Result<Vec<Bytes>>;
 
+	/// Returns the occupancy of the transaction pool, per queue and per sender.
+	#[rpc(name = "author_poolStatus")]
+	fn pool_status(&self) -> Result<PoolStatus>;
+
 	/// Remove given extrinsic from the pool and temporarily ban it to prevent reimporting.
 	#[rpc(name = "author_removeExtrinsic")]
 	fn remove_extrinsic(&self,
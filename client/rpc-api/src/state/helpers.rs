@@ -0,0 +1,43 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Substrate state API helpers.
+
+use serde::{Serialize, Deserialize};
+use sp_core::Bytes;
+
+/// Historical states the node is able to serve.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatePruning {
+	/// The state of the finalized blocks below this number is pruned, checkpoints excepted.
+	/// `None` if the node keeps all the states.
+	pub pruned_below: Option<u64>,
+	/// The state of the finalized blocks whose number is a multiple of this is kept.
+	pub checkpoint_interval: Option<u32>,
+}
+
+/// Proof of storage entries at a block, checkable against the block's state root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadProof<Hash> {
+	/// Block hash used to generate the proof.
+	pub at: Hash,
+	/// State root of the block, which is the root of the proof.
+	pub state_root: Hash,
+	/// Trie nodes proving the storage entries.
+	pub proof: Vec<Bytes>,
+}
@@ -25,7 +25,7 @@ use sp_core::{
 };
 use rpc::futures::Stream as _;
 use substrate_test_runtime_client::{
-	self, AccountKeyring, runtime::{Extrinsic, Transfer, SessionKeys, RuntimeApi, Block},
+	self, AccountKeyring, runtime::{AccountId, Extrinsic, Transfer, SessionKeys, RuntimeApi, Block},
 	DefaultTestClientBuilderExt, TestClientBuilderExt, Backend, Client, Executor,
 };
 use sc_transaction_pool::{BasicPool, FullChainApi};
@@ -117,7 +117,8 @@ fn should_watch_extrinsic() {
 	let (subscriber, id_rx, data) = jsonrpc_pubsub::typed::Subscriber::new_test("test");
 
 	// when
-	p.watch_extrinsic(Default::default(), subscriber, uxt(AccountKeyring::Alice, 0).encode().into());
+	let original = uxt(AccountKeyring::Alice, 0);
+	p.watch_extrinsic(Default::default(), subscriber, original.encode().into());
 
 	// then
 	assert_eq!(setup.runtime.block_on(id_rx), Ok(Ok(1.into())));
@@ -131,7 +132,9 @@ fn should_watch_extrinsic() {
 		};
 		tx.into_signed_tx()
 	};
-	AuthorApi::submit_extrinsic(&p, replacement.encode().into()).wait().unwrap();
+	let (subscriber, id_rx, replacement_data) = jsonrpc_pubsub::typed::Subscriber::new_test("test");
+	p.watch_extrinsic(Default::default(), subscriber, replacement.encode().into());
+	assert_eq!(setup.runtime.block_on(id_rx), Ok(Ok(2.into())));
 	let (res, data) = setup.runtime.block_on(data.into_future()).unwrap();
 	assert_eq!(
 		res,
@@ -142,6 +145,44 @@ fn should_watch_extrinsic() {
 		setup.runtime.block_on(data.into_future()).unwrap().0,
 		Some(format!(r#"{{"jsonrpc":"2.0","method":"test","params":{{"result":{{"usurped":"0x{}"}},"subscription":1}}}}"#, HexDisplay::from(&h)))
 	);
+	// the replacement learns what it replaced
+	let (res, replacement_data) = setup.runtime.block_on(replacement_data.into_future()).unwrap();
+	assert_eq!(
+		res,
+		Some(r#"{"jsonrpc":"2.0","method":"test","params":{"result":"ready","subscription":2}}"#.into())
+	);
+	assert_eq!(
+		setup.runtime.block_on(replacement_data.into_future()).unwrap().0,
+		Some(format!(
+			r#"{{"jsonrpc":"2.0","method":"test","params":{{"result":{{"replaced":{{"old":"0x{}","new":"0x{}"}}}},"subscription":2}}}}"#,
+			HexDisplay::from(&blake2_256(&original.encode())),
+			HexDisplay::from(&h),
+		))
+	);
+}
+
+#[test]
+fn should_replace_extrinsic() {
+	let setup = TestSetup::default();
+	let p = setup.author();
+
+	let old = p.submit_extrinsic(uxt(AccountKeyring::Alice, 0).encode().into()).wait().unwrap();
+	let replacement = Transfer {
+		amount: 5,
+		nonce: 0,
+		from: AccountKeyring::Alice.into(),
+		to: Default::default(),
+	}.into_signed_tx();
+
+	// not the transaction to replace
+	assert_matches!(
+		p.replace_extrinsic(old, uxt(AccountKeyring::Alice, 1).encode().into()).wait(),
+		Err(Error::Pool(sp_transaction_pool::error::Error::NotAReplacement))
+	);
+	let new = p.replace_extrinsic(old, replacement.encode().into()).wait().unwrap();
+
+	assert_eq!(new, blake2_256(&replacement.encode()).into());
+	assert_eq!(setup.pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![new]);
 }
 
 #[test]
@@ -172,6 +213,38 @@ fn should_return_pending_extrinsics() {
 	);
 }
 
+#[test]
+fn should_return_pool_status_per_sender() {
+	let mut setup = TestSetup::default();
+	let options = sc_transaction_pool::txpool::Options {
+		sender: Some(sc_transaction_pool::txpool::base_pool::Limit { count: 1, total_bytes: 1024 }),
+		..Default::default()
+	};
+	// test runtime tags are `(AccountId, u64)`
+	fn sender_of(provides: &[Vec<u8>]) -> Option<&[u8]> {
+		provides.first().map(|tag| &tag[..tag.len().saturating_sub(8)])
+	}
+	let pool_api = FullChainApi::new(setup.client.clone()).with_sender_of(sender_of);
+	setup.pool = Arc::new(BasicPool::new(options, pool_api));
+	let p = setup.author();
+
+	p.submit_extrinsic(uxt(AccountKeyring::Alice, 0).encode().into()).wait().unwrap();
+	// over Alice's quota
+	p.submit_extrinsic(uxt(AccountKeyring::Alice, 1).encode().into()).wait().unwrap_err();
+	p.submit_extrinsic(uxt(AccountKeyring::Bob, 0).encode().into()).wait().unwrap();
+
+	let status = p.pool_status().unwrap();
+	assert_eq!(status.ready, 2);
+	let mut senders = status.senders.iter().map(|s| (s.sender.clone(), s.ready)).collect::<Vec<_>>();
+	senders.sort();
+	let mut expected = vec![
+		(AccountId::from(AccountKeyring::Alice).encode(), 1),
+		(AccountId::from(AccountKeyring::Bob).encode(), 1),
+	];
+	expected.sort();
+	assert_eq!(senders, expected);
+}
+
 #[test]
 fn should_remove_extrinsics() {
 	let setup = TestSetup::default();
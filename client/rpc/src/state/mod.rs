@@ -24,7 +24,7 @@ mod tests;
 
 use std::sync::Arc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
-use rpc::{Result as RpcResult, futures::Future};
+use rpc::{Result as RpcResult, futures::{Future, future::result}};
 
 use sc_rpc_api::Subscriptions;
 use sc_client::{Client, CallExecutor, light::{blockchain::RemoteBlockchain, fetcher::Fetcher}};
@@ -43,6 +43,12 @@ use self::error::{Error, FutureResult};
 
 pub use sc_rpc_api::state::*;
 
+/// Maximal number of keys or pairs returned by a single paged storage request.
+const STORAGE_KEYS_PAGED_MAX_COUNT: u32 = 1000;
+
+/// Maximal number of keys a single read proof request may prove.
+const READ_PROOF_MAX_KEYS: u32 = 1000;
+
 /// State backend API.
 pub trait StateBackend<B, E, Block: BlockT, RA>: Send + Sync + 'static
 	where
@@ -66,6 +72,24 @@ pub trait StateBackend<B, E, Block: BlockT, RA>: Send + Sync + 'static
 		prefix: StorageKey,
 	) -> FutureResult<Vec<StorageKey>>;
 
+	/// Returns at most `count` keys with prefix, starting after `start_key` if given.
+	fn storage_keys_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<StorageKey>>;
+
+	/// Returns at most `count` key/value pairs with prefix, starting after `start_key` if given.
+	fn storage_pairs_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<(StorageKey, StorageData)>>;
+
 	/// Returns a storage entry at a specific block's state.
 	fn storage(
 		&self,
@@ -150,6 +174,26 @@ pub trait StateBackend<B, E, Block: BlockT, RA>: Send + Sync + 'static
 		keys: Vec<StorageKey>,
 	) -> FutureResult<Vec<StorageChangeSet<Block::Hash>>>;
 
+	/// Returns which historical states the node keeps.
+	fn pruning(&self) -> FutureResult<StatePruning>;
+
+	/// Returns proof of storage entries at a specific block's state.
+	fn read_proof(
+		&self,
+		block: Option<Block::Hash>,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>>;
+
+	/// Returns proof of child storage entries at a specific block's state.
+	fn read_child_proof(
+		&self,
+		block: Option<Block::Hash>,
+		child_storage_key: StorageKey,
+		child_info: StorageKey,
+		child_type: u32,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>>;
+
 	/// New runtime version subscription
 	fn subscribe_runtime_version(
 		&self,
@@ -249,6 +293,38 @@ impl<B, E, Block, RA> StateApi<Block::Hash> for State<B, E, Block, RA>
 		self.backend.storage_keys(block, key_prefix)
 	}
 
+	fn storage_keys_paged(
+		&self,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+		block: Option<Block::Hash>,
+	) -> FutureResult<Vec<StorageKey>> {
+		if count > STORAGE_KEYS_PAGED_MAX_COUNT {
+			return Box::new(result(Err(Error::InvalidCount {
+				value: count,
+				max: STORAGE_KEYS_PAGED_MAX_COUNT,
+			})));
+		}
+		self.backend.storage_keys_paged(block, prefix, count, start_key)
+	}
+
+	fn storage_pairs_paged(
+		&self,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+		block: Option<Block::Hash>,
+	) -> FutureResult<Vec<(StorageKey, StorageData)>> {
+		if count > STORAGE_KEYS_PAGED_MAX_COUNT {
+			return Box::new(result(Err(Error::InvalidCount {
+				value: count,
+				max: STORAGE_KEYS_PAGED_MAX_COUNT,
+			})));
+		}
+		self.backend.storage_pairs_paged(block, prefix, count, start_key)
+	}
+
 	fn storage(&self, key: StorageKey, block: Option<Block::Hash>) -> FutureResult<Option<StorageData>> {
 		self.backend.storage(block, key)
 	}
@@ -318,6 +394,37 @@ impl<B, E, Block, RA> StateApi<Block::Hash> for State<B, E, Block, RA>
 		self.backend.query_storage(from, to, keys)
 	}
 
+	fn pruning(&self) -> FutureResult<StatePruning> {
+		self.backend.pruning()
+	}
+
+	fn read_proof(&self, keys: Vec<StorageKey>, block: Option<Block::Hash>) -> FutureResult<ReadProof<Block::Hash>> {
+		if keys.len() > READ_PROOF_MAX_KEYS as usize {
+			return Box::new(result(Err(Error::InvalidCount {
+				value: keys.len().min(u32::max_value() as usize) as u32,
+				max: READ_PROOF_MAX_KEYS,
+			})));
+		}
+		self.backend.read_proof(block, keys)
+	}
+
+	fn read_child_proof(
+		&self,
+		child_storage_key: StorageKey,
+		child_info: StorageKey,
+		child_type: u32,
+		keys: Vec<StorageKey>,
+		block: Option<Block::Hash>
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		if keys.len() > READ_PROOF_MAX_KEYS as usize {
+			return Box::new(result(Err(Error::InvalidCount {
+				value: keys.len().min(u32::max_value() as usize) as u32,
+				max: READ_PROOF_MAX_KEYS,
+			})));
+		}
+		self.backend.read_child_proof(block, child_storage_key, child_info, child_type, keys)
+	}
+
 	fn subscribe_storage(
 		&self,
 		meta: Self::Metadata,
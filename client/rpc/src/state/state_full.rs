@@ -43,12 +43,12 @@ use sp_version::RuntimeVersion;
 use sp_state_machine::ExecutionStrategy;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, NumberFor, ProvideRuntimeApi, SaturatedConversion},
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, ProvideRuntimeApi, SaturatedConversion},
 };
 
 use sp_api::Metadata;
 
-use super::{StateBackend, error::{FutureResult, Error, Result}, client_err, child_resolution_error};
+use super::{StateBackend, StatePruning, ReadProof, error::{FutureResult, Error, Result}, client_err, child_resolution_error};
 
 /// Ranges to query in state_queryStorage.
 struct QueryStorageRange<Block: BlockT> {
@@ -86,6 +86,13 @@ impl<B, E, Block: BlockT, RA> FullState<B, E, Block, RA>
 		Ok(hash.unwrap_or_else(|| self.client.chain_info().best_hash))
 	}
 
+	/// Returns the state root of the given block.
+	fn state_root(&self, block: Block::Hash) -> ClientResult<Block::Hash> {
+		self.client.header(&BlockId::Hash(block))?
+			.map(|header| *header.state_root())
+			.ok_or_else(|| ClientError::UnknownBlock(format!("{}", block)))
+	}
+
 	/// Splits the `query_storage` block range into 'filtered' and 'unfiltered' subranges.
 	/// Blocks that contain changes within filtered subrange could be filtered using changes tries.
 	/// Blocks that contain changes within unfiltered subrange must be filtered manually.
@@ -262,6 +269,42 @@ impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, R
 				.map_err(client_err)))
 	}
 
+	fn storage_keys_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<StorageKey>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| self.client.storage_keys_paged(
+					&BlockId::Hash(block),
+					&prefix,
+					start_key.as_ref(),
+					count as usize,
+				))
+				.map_err(client_err)))
+	}
+
+	fn storage_pairs_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<(StorageKey, StorageData)>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| self.client.storage_pairs_paged(
+					&BlockId::Hash(block),
+					&prefix,
+					start_key.as_ref(),
+					count as usize,
+				))
+				.map_err(client_err)))
+	}
+
 	fn storage(
 		&self,
 		block: Option<Block::Hash>,
@@ -377,6 +420,64 @@ impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, R
 		Box::new(result(call_fn()))
 	}
 
+	fn pruning(&self) -> FutureResult<StatePruning> {
+		Box::new(result(
+			self.client.state_retention()
+				.map(|retention| StatePruning {
+					pruned_below: retention.pruned_below,
+					checkpoint_interval: retention.checkpoint_interval,
+				})
+				.ok_or_else(|| client_err(ClientError::Msg("State pruning is unknown to the backend".into())))
+		))
+	}
+
+	fn read_proof(
+		&self,
+		block: Option<Block::Hash>,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let state_root = self.state_root(block)?;
+					self.client.read_proof(&BlockId::Hash(block), keys.iter().map(|key| &key.0))
+						.map(|proof| ReadProof {
+							at: block,
+							state_root,
+							proof: proof.iter_nodes().map(Into::into).collect(),
+						})
+				})
+				.map_err(client_err)))
+	}
+
+	fn read_child_proof(
+		&self,
+		block: Option<Block::Hash>,
+		child_storage_key: StorageKey,
+		child_info: StorageKey,
+		child_type: u32,
+		keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(
+			self.block_or_best(block)
+				.and_then(|block| {
+					let state_root = self.state_root(block)?;
+					self.client.read_child_proof(
+						&BlockId::Hash(block),
+						&child_storage_key.0,
+						ChildInfo::resolve_child_info(child_type, &child_info.0[..])
+							.ok_or_else(child_resolution_error)?,
+						keys.iter().map(|key| &key.0),
+					)
+						.map(|proof| ReadProof {
+							at: block,
+							state_root,
+							proof: proof.iter_nodes().map(Into::into).collect(),
+						})
+				})
+				.map_err(client_err)))
+	}
+
 	fn subscribe_runtime_version(
 		&self,
 		_meta: crate::metadata::Metadata,
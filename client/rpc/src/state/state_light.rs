@@ -45,7 +45,10 @@ use sc_client::{
 	BlockchainEvents, Client, CallExecutor,
 	light::{
 		blockchain::{future_header, RemoteBlockchain},
-		fetcher::{Fetcher, RemoteCallRequest, RemoteReadRequest, RemoteReadChildRequest},
+		fetcher::{
+			Fetcher, RemoteCallRequest, RemoteReadRequest, RemoteReadChildRequest,
+			RemoteReadKeysRequest,
+		},
 	},
 };
 use sp_core::{
@@ -58,7 +61,7 @@ use sp_runtime::{
 	traits::Block as BlockT,
 };
 
-use super::{StateBackend, error::{FutureResult, Error}, client_err};
+use super::{StateBackend, StatePruning, ReadProof, error::{FutureResult, Error}, client_err};
 
 /// Storage data map of storage keys => (optional) storage value.
 type StorageMap = HashMap<StorageKey, Option<StorageData>>;
@@ -203,6 +206,40 @@ impl<Block, F, B, E, RA> StateBackend<B, E, Block, RA> for LightState<Block, F,
 		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
 	}
 
+	fn storage_keys_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<StorageKey>> {
+		Box::new(storage_pairs_paged(
+			&*self.remote_blockchain,
+			self.fetcher.clone(),
+			self.block_or_best(block),
+			prefix.0,
+			start_key.map(|key| key.0),
+			count,
+		).boxed().compat().map(|pairs| pairs.into_iter().map(|(key, _)| key).collect()))
+	}
+
+	fn storage_pairs_paged(
+		&self,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		count: u32,
+		start_key: Option<StorageKey>,
+	) -> FutureResult<Vec<(StorageKey, StorageData)>> {
+		Box::new(storage_pairs_paged(
+			&*self.remote_blockchain,
+			self.fetcher.clone(),
+			self.block_or_best(block),
+			prefix.0,
+			start_key.map(|key| key.0),
+			count,
+		).boxed().compat())
+	}
+
 	fn storage(
 		&self,
 		block: Option<Block::Hash>,
@@ -322,6 +359,29 @@ impl<Block, F, B, E, RA> StateBackend<B, E, Block, RA> for LightState<Block, F,
 		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
 	}
 
+	fn pruning(&self) -> FutureResult<StatePruning> {
+		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
+	}
+
+	fn read_proof(
+		&self,
+		_block: Option<Block::Hash>,
+		_keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
+	}
+
+	fn read_child_proof(
+		&self,
+		_block: Option<Block::Hash>,
+		_child_storage_key: StorageKey,
+		_child_info: StorageKey,
+		_child_type: u32,
+		_keys: Vec<StorageKey>,
+	) -> FutureResult<ReadProof<Block::Hash>> {
+		Box::new(result(Err(client_err(ClientError::NotAvailableOnLightClient))))
+	}
+
 	fn subscribe_storage(
 		&self,
 		_meta: crate::metadata::Metadata,
@@ -585,6 +645,35 @@ fn storage<Block: BlockT, F: Fetcher<Block>>(
 		})
 }
 
+/// Get at most `count` storage key/value pairs with given prefix at given block.
+fn storage_pairs_paged<Block: BlockT, F: Fetcher<Block>>(
+	remote_blockchain: &dyn RemoteBlockchain<Block>,
+	fetcher: Arc<F>,
+	block: Block::Hash,
+	prefix: Vec<u8>,
+	start_key: Option<Vec<u8>>,
+	count: u32,
+) -> impl std::future::Future<Output = Result<Vec<(StorageKey, StorageData)>, Error>> {
+	resolve_header(remote_blockchain, &*fetcher, block)
+		.then(move |result| match result {
+			Ok(header) => Either::Left(fetcher.remote_read_keys(RemoteReadKeysRequest {
+				block,
+				header,
+				prefix,
+				start_key,
+				count,
+				retry_count: Default::default(),
+			}).then(|result| ready(result
+				.map(|result| result
+					.into_iter()
+					.map(|(key, value)| (StorageKey(key), StorageData(value)))
+					.collect()
+				).map_err(client_err)
+			))),
+			Err(error) => Either::Right(ready(Err(error))),
+		})
+}
+
 /// Returns subscription stream that issues request on every imported block and
 /// if value has changed from previous block, emits (stream) item.
 fn subscription_stream<
@@ -24,6 +24,8 @@ use futures01::stream::Stream;
 use sp_core::storage::{well_known_keys, ChildInfo};
 use sp_core::hash::H256;
 use sp_io::hashing::blake2_256;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use sp_state_machine::Backend as _;
 use substrate_test_runtime_client::{
 	prelude::*,
 	sp_consensus::BlockOrigin,
@@ -75,6 +77,29 @@ fn should_return_storage() {
 
 }
 
+#[test]
+fn should_return_storage_keys_and_pairs_paged() {
+	let core = tokio::runtime::Runtime::new().unwrap();
+	let client = TestClientBuilder::new()
+		.add_extra_storage(b":mock1".to_vec(), b"one".to_vec())
+		.add_extra_storage(b":mock2".to_vec(), b"two".to_vec())
+		.add_extra_storage(b":mock3".to_vec(), b"three".to_vec())
+		.build();
+	let client = new_full(Arc::new(client), Subscriptions::new(Arc::new(core.executor())));
+	let prefix = StorageKey(b":mock".to_vec());
+
+	let first_page = client.storage_keys_paged(prefix.clone(), 2, None, None).wait().unwrap();
+	assert_eq!(first_page, vec![StorageKey(b":mock1".to_vec()), StorageKey(b":mock2".to_vec())]);
+	assert_eq!(
+		client.storage_pairs_paged(prefix.clone(), 2, first_page.last().cloned(), None).wait().unwrap(),
+		vec![(StorageKey(b":mock3".to_vec()), StorageData(b"three".to_vec()))],
+	);
+	assert_matches!(
+		client.storage_keys_paged(prefix, STORAGE_KEYS_PAGED_MAX_COUNT + 1, None, None).wait(),
+		Err(Error::InvalidCount { .. })
+	);
+}
+
 #[test]
 fn should_return_child_storage() {
 	let (child_info, child_type) = CHILD_INFO.info();
@@ -410,6 +435,60 @@ fn should_return_runtime_version() {
 	assert_eq!(deserialized, runtime_version);
 }
 
+#[test]
+fn should_return_pruning() {
+	let core = tokio::runtime::Runtime::new().unwrap();
+
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let api = new_full(client.clone(), Subscriptions::new(Arc::new(core.executor())));
+
+	let pruning = api.pruning().wait().unwrap();
+	assert_eq!(pruning, StatePruning { pruned_below: Some(0), checkpoint_interval: None });
+	assert_eq!(
+		serde_json::to_string(&pruning).unwrap(),
+		"{\"prunedBelow\":0,\"checkpointInterval\":null}",
+	);
+}
+
+#[test]
+fn should_return_read_proof() {
+	const KEY: &[u8] = b":mock";
+	const VALUE: &[u8] = b"hello world";
+
+	let core = tokio::runtime::Runtime::new().unwrap();
+	let client = Arc::new(TestClientBuilder::new()
+		.add_extra_storage(KEY.to_vec(), VALUE.to_vec())
+		.build());
+	let genesis_hash = client.genesis_hash();
+	let api = new_full(client.clone(), Subscriptions::new(Arc::new(core.executor())));
+
+	let read_proof = api.read_proof(vec![StorageKey(KEY.to_vec())], None).wait().unwrap();
+	assert_eq!(read_proof.at, genesis_hash);
+	assert_eq!(
+		read_proof.state_root,
+		*client.header(&BlockId::Hash(genesis_hash)).unwrap().unwrap().state_root(),
+	);
+
+	// the test genesis header doesn't commit to the extra storage
+	let state_root = client.state_at(&BlockId::Hash(genesis_hash)).unwrap()
+		.storage_root(std::iter::empty()).0;
+	let proof = sp_state_machine::StorageProof::new(
+		read_proof.proof.into_iter().map(|node| node.0).collect(),
+	);
+	let values = sp_state_machine::read_proof_check::<Blake2Hasher, _>(
+		state_root,
+		proof,
+		&[KEY],
+	).unwrap();
+	assert_eq!(values.get(KEY), Some(&Some(VALUE.to_vec())));
+
+	let too_many_keys = vec![StorageKey(KEY.to_vec()); READ_PROOF_MAX_KEYS as usize + 1];
+	assert_matches!(
+		api.read_proof(too_many_keys, None).wait(),
+		Err(Error::InvalidCount { .. })
+	);
+}
+
 #[test]
 fn should_notify_on_runtime_version_initially() {
 	let mut core = tokio::runtime::Runtime::new().unwrap();
@@ -79,7 +79,7 @@ pub struct ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImp
 {
 	config: Configuration<TCfg, TGen, TCSExt>,
 	pub (crate) client: Arc<TCl>,
-	backend: Arc<Backend>,
+	pub (crate) backend: Arc<Backend>,
 	keystore: Arc<RwLock<Keystore>>,
 	fetcher: Option<TFchr>,
 	select_chain: Option<TSc>,
@@ -202,6 +202,11 @@ fn new_full_parts<TBl, TRtApi, TExecDisp, TCfg, TGen, TCSExt>(
 						path: path.clone(),
 						cache_size: cache_size.clone().map(|u| u as usize),
 					},
+				DatabaseConfig::Sled { path, cache_size } =>
+					sc_client_db::DatabaseSettingsSrc::Sled {
+						path: path.clone(),
+						cache_size: cache_size.clone().map(|u| u as usize),
+					},
 				DatabaseConfig::Custom(db) =>
 					sc_client_db::DatabaseSettingsSrc::Custom(db.clone()),
 			},
@@ -314,6 +319,11 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 							path: path.clone(),
 							cache_size: cache_size.clone().map(|u| u as usize),
 						},
+					DatabaseConfig::Sled { path, cache_size } =>
+						sc_client_db::DatabaseSettingsSrc::Sled {
+							path: path.clone(),
+							cache_size: cache_size.clone().map(|u| u as usize),
+						},
 					DatabaseConfig::Custom(db) =>
 						sc_client_db::DatabaseSettingsSrc::Custom(db.clone()),
 				},
@@ -698,6 +708,25 @@ pub trait ServiceBuilderCommand {
 		self,
 		block: BlockId<Self::Block>
 	) -> Box<dyn Future<Item = (), Error = Error> + Send>;
+
+	/// Writes a snapshot of the state of the last finalized block, which must be the best block.
+	fn export_state(
+		self,
+		output: impl Write,
+	) -> Result<(), Error>;
+
+	/// Seeds a new database with a state snapshot.
+	fn import_state(
+		self,
+		input: impl Read,
+	) -> Result<(), Error>;
+
+	/// Checks the integrity of the database and, if `repair` is set, reverts the chain to the
+	/// last consistent finalized block.
+	fn check_db(
+		self,
+		repair: bool,
+	) -> Result<(), Error>;
 }
 
 impl<TBl, TRtApi, TCfg, TGen, TCSExt, TBackend, TExec, TSc, TImpQu, TNetP, TExPool, TRpc>
@@ -17,7 +17,7 @@
 //! Chain utilities.
 
 use crate::error;
-use crate::builder::{ServiceBuilderCommand, ServiceBuilder};
+use crate::builder::{ServiceBuilderCommand, ServiceBuilder, TFullBackend};
 use crate::error::Error;
 use sc_chain_spec::{ChainSpec, RuntimeGenesis, Extension};
 use log::{warn, info};
@@ -52,10 +52,10 @@ pub fn build_spec<G, E>(spec: ChainSpec<G, E>, raw: bool) -> error::Result<Strin
 impl<
 	TBl, TRtApi, TCfg, TGen, TCSExt, TBackend,
 	TExec, TFchr, TSc, TImpQu, TFprb, TFpp, TNetP,
-	TExPool, TRpc, Backend
+	TExPool, TRpc
 > ServiceBuilderCommand for ServiceBuilder<
 	TBl, TRtApi, TCfg, TGen, TCSExt, Client<TBackend, TExec, TBl, TRtApi>,
-	TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc, Backend
+	TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc, TFullBackend<TBl>
 > where
 	TBl: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
 	TBackend: 'static + sc_client_api::backend::Backend<TBl, Blake2Hasher> + Send,
@@ -282,15 +282,7 @@ impl<
 		&self,
 		blocks: NumberFor<TBl>
 	) -> Result<(), Error> {
-		let reverted = self.client.revert(blocks)?;
-		let info = self.client.chain_info();
-
-		if reverted.is_zero() {
-			info!("There aren't any non-finalized blocks to revert.");
-		} else {
-			info!("Reverted {} blocks. Best: #{} ({})", reverted, info.best_number, info.best_hash);
-		}
-		Ok(())
+		revert_blocks(&self.client, blocks, false).map(|_| ())
 	}
 
 	fn check_block(
@@ -309,5 +301,86 @@ impl<
 			Err(e) => Box::new(future::err(format!("Error reading block: {:?}", e).into())),
 		}
 	}
+
+	fn export_state(
+		self,
+		output: impl Write,
+	) -> Result<(), Error> {
+		let header = self.backend.export_snapshot(output)?;
+		info!("Exported the state of #{} ({})", header.number(), header.hash());
+		Ok(())
+	}
+
+	fn import_state(
+		self,
+		input: impl Read,
+	) -> Result<(), Error> {
+		let header = self.backend.import_snapshot(input)?;
+		info!("Imported the state of #{} ({}). The node will sync from there.", header.number(), header.hash());
+		Ok(())
+	}
+
+	fn check_db(
+		self,
+		repair: bool,
+	) -> Result<(), Error> {
+		let report = self.backend.check_integrity()?;
+		info!("Checked {} blocks and {} states", report.checked_blocks, report.checked_states);
+		if report.corruptions.is_empty() {
+			info!("No corruption found.");
+			return Ok(())
+		}
+		for corruption in &report.corruptions {
+			warn!("{}", corruption);
+		}
+		if !repair {
+			return Err(format!("Found {} corruptions", report.corruptions.len()).into());
+		}
+		if !report.is_repairable_by_revert() {
+			return Err("The database can't be repaired by reverting blocks".into());
+		}
+
+		let info = self.client.chain_info();
+		let first_corrupted = report.first_corrupted_block()
+			.expect("there are corruptions, all about blocks; qed");
+		if first_corrupted.is_zero() {
+			return Err("The genesis block is corrupted".into());
+		}
+		let target = std::cmp::min(info.finalized_number, first_corrupted - One::one());
+		let blocks = info.best_number - target;
+		let reverted = revert_blocks(&self.client, blocks, true)?;
+		if reverted < blocks {
+			return Err(format!(
+				"Could only revert {} of the {} blocks above #{}, whose state is already canonical",
+				reverted, blocks, target,
+			).into());
+		}
+		Ok(())
+	}
 }
 
+/// Reverts `blocks` blocks, the finalized ones included if `revert_finalized` is set. Returns the
+/// number of blocks reverted.
+fn revert_blocks<TBl, TBackend, TExec, TRtApi>(
+	client: &Client<TBackend, TExec, TBl, TRtApi>,
+	blocks: NumberFor<TBl>,
+	revert_finalized: bool,
+) -> Result<NumberFor<TBl>, Error> where
+	TBl: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
+	TBackend: sc_client_api::backend::Backend<TBl, Blake2Hasher>,
+	TExec: sc_client::CallExecutor<TBl, Blake2Hasher>,
+{
+	let reverted = if revert_finalized {
+		client.unsafe_revert(blocks)?
+	} else {
+		client.revert(blocks)?
+	};
+	let info = client.chain_info();
+
+	if reverted.is_zero() {
+		info!("There aren't any non-finalized blocks to revert.");
+	} else {
+		info!("Reverted {} blocks. Best: #{} ({})", reverted, info.best_number, info.best_hash);
+	}
+	Ok(reverted)
+}
@@ -129,6 +129,14 @@ pub enum DatabaseConfig {
 		cache_size: Option<u32>,
 	},
 
+	/// Sled database at a specific path.
+	Sled {
+		/// Path to the database.
+		path: PathBuf,
+		/// Cache Size for internal database in MiB
+		cache_size: Option<u32>,
+	},
+
 	/// A custom implementation of an already-open database.
 	Custom(Arc<dyn KeyValueDB>),
 }
@@ -42,7 +42,7 @@ use sp_runtime::{
 };
 use sp_state_machine::{
 	DBValue, Backend as StateBackend, ChangesTrieAnchorBlockId, ExecutionStrategy, ExecutionManager,
-	prove_read, prove_child_read, ChangesTrieRootsStorage, ChangesTrieStorage,
+	prove_read, prove_child_read, prove_read_keys, ChangesTrieRootsStorage, ChangesTrieStorage,
 	ChangesTrieTransaction, ChangesTrieConfigurationRange, key_changes, key_changes_proof,
 	OverlayedChanges, BackendTrustLevel, StorageProof, merge_storage_proofs,
 };
@@ -70,7 +70,7 @@ pub use sc_client_api::{
 	client::{
 		ImportNotifications, FinalityNotification, FinalityNotifications, BlockImportNotification,
 		ClientInfo, BlockchainEvents, BlockBody, ProvideUncles, BadBlocks, ForkBlocks,
-		BlockOf,
+		BlockOf, StateRetention,
 	},
 	execution_extensions::{ExecutionExtensions, ExecutionStrategies},
 	notifications::{StorageNotifications, StorageEventStream},
@@ -256,6 +256,42 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(keys)
 	}
 
+	/// Given a `BlockId` and a key prefix, return at most `count` matching storage keys in that
+	/// block, starting after `start_key` if given.
+	pub fn storage_keys_paged(
+		&self,
+		id: &BlockId<Block>,
+		key_prefix: &StorageKey,
+		start_key: Option<&StorageKey>,
+		count: usize,
+	) -> sp_blockchain::Result<Vec<StorageKey>> {
+		let keys = self.state_at(id)?
+			.keys_paged(&key_prefix.0, start_key.map(|key| &key.0[..]), count)
+			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))?
+			.into_iter()
+			.map(StorageKey)
+			.collect();
+		Ok(keys)
+	}
+
+	/// Given a `BlockId` and a key prefix, return at most `count` matching storage key/value
+	/// pairs in that block, starting after `start_key` if given.
+	pub fn storage_pairs_paged(
+		&self,
+		id: &BlockId<Block>,
+		key_prefix: &StorageKey,
+		start_key: Option<&StorageKey>,
+		count: usize,
+	) -> sp_blockchain::Result<Vec<(StorageKey, StorageData)>> {
+		let pairs = self.state_at(id)?
+			.pairs_paged(&key_prefix.0, start_key.map(|key| &key.0[..]), count)
+			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))?
+			.into_iter()
+			.map(|(key, value)| (StorageKey(key), StorageData(value)))
+			.collect();
+		Ok(pairs)
+	}
+
 	/// Given a `BlockId` and a key, return the value under the key in that block.
 	pub fn storage(&self, id: &BlockId<Block>, key: &StorageKey) -> sp_blockchain::Result<Option<StorageData>> {
 		Ok(self.state_at(id)?
@@ -360,6 +396,20 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 				.map_err(Into::into))
 	}
 
+	/// Reads a page of storage key/value pairs with given prefix at a given block,
+	/// returning read proof.
+	pub fn read_keys_proof(
+		&self,
+		id: &BlockId<Block>,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> sp_blockchain::Result<StorageProof> {
+		self.state_at(id)
+			.and_then(|state| prove_read_keys(state, prefix, start_key, count)
+				.map_err(Into::into))
+	}
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
@@ -1185,6 +1235,16 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		self.backend.blockchain().info()
 	}
 
+	/// Get the historical states the backend keeps, if known.
+	pub fn state_retention(&self) -> Option<StateRetention> {
+		self.backend.state_retention()
+	}
+
+	/// Get a handle to the offchain storage of the backend, if it has one.
+	pub fn offchain_storage(&self) -> Option<B::OffchainStorage> {
+		self.backend.offchain_storage()
+	}
+
 	/// Get block status.
 	pub fn block_status(&self, id: &BlockId<Block>) -> sp_blockchain::Result<BlockStatus> {
 		// this can probably be implemented more efficiently
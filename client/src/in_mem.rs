@@ -690,6 +690,10 @@ where
 		None
 	}
 
+	fn state_retention(&self) -> Option<sc_client_api::StateRetention> {
+		Some(Default::default())
+	}
+
 	fn changes_trie_storage(&self) -> Option<&Self::ChangesTrieStorage> {
 		Some(&self.changes_trie_storage)
 	}
@@ -30,7 +30,7 @@ use sp_runtime::traits::{
 use sp_state_machine::{
 	ChangesTrieRootsStorage, ChangesTrieAnchorBlockId, ChangesTrieConfigurationRange,
 	TrieBackend, read_proof_check, key_changes_proof_check, create_proof_check_backend_storage,
-	read_child_proof_check,
+	read_child_proof_check, read_keys_proof_check,
 };
 pub use sp_state_machine::StorageProof;
 use sp_blockchain::{Error as ClientError, Result as ClientResult};
@@ -39,6 +39,7 @@ use crate::cht;
 pub use sc_client_api::{
 	light::{
 		RemoteCallRequest, RemoteHeaderRequest, RemoteReadRequest, RemoteReadChildRequest,
+		RemoteReadKeysRequest,
 		RemoteChangesRequest, ChangesProof, RemoteBodyRequest, Fetcher, FetchChecker,
 		Storage as BlockchainStorage,
 	},
@@ -242,6 +243,20 @@ impl<E, Block, H, S> FetchChecker<Block> for LightDataChecker<E, H, Block, S>
 		).map_err(Into::into)
 	}
 
+	fn check_read_keys_proof(
+		&self,
+		request: &RemoteReadKeysRequest<Block::Header>,
+		remote_proof: StorageProof,
+	) -> ClientResult<Vec<(Vec<u8>, Vec<u8>)>> {
+		read_keys_proof_check::<H>(
+			convert_hash(request.header.state_root()),
+			remote_proof,
+			&request.prefix,
+			request.start_key.as_ref().map(|key| &key[..]),
+			request.count as usize,
+		).map_err(Into::into)
+	}
+
 	fn check_execution_proof(
 		&self,
 		request: &RemoteCallRequest<Block::Header>,
@@ -510,6 +525,51 @@ pub mod tests {
 		).unwrap().remove(b"key1".as_ref()).unwrap().unwrap(), result);
 	}
 
+	#[test]
+	fn storage_keys_read_proof_is_generated_and_checked() {
+		let remote_client = substrate_test_runtime_client::new();
+		let remote_block_id = BlockId::Number(0);
+		let mut remote_block_header = remote_client.header(&remote_block_id).unwrap().unwrap();
+		remote_block_header.state_root = remote_client.state_at(&remote_block_id).unwrap()
+			.storage_root(::std::iter::empty()).0.into();
+
+		let prefix = StorageKey(b":".to_vec());
+		let first_page = remote_client.storage_pairs_paged(&remote_block_id, &prefix, None, 1).unwrap();
+		let start_key = first_page[0].0.clone();
+		let remote_pairs = remote_client.storage_pairs_paged(
+			&remote_block_id,
+			&prefix,
+			Some(&start_key),
+			2,
+		).unwrap();
+		let remote_read_proof = remote_client.read_keys_proof(
+			&remote_block_id,
+			&prefix.0,
+			Some(&start_key.0),
+			2,
+		).unwrap();
+
+		let local_checker = TestChecker::new(
+			Arc::new(DummyBlockchain::new(DummyStorage::new())),
+			local_executor()
+		);
+		let local_pairs = (&local_checker as &dyn FetchChecker<Block>).check_read_keys_proof(
+			&RemoteReadKeysRequest::<Header> {
+				block: remote_block_header.hash(),
+				header: remote_block_header,
+				prefix: prefix.0.clone(),
+				start_key: Some(start_key.0.clone()),
+				count: 2,
+				retry_count: None,
+			},
+			remote_read_proof,
+		).unwrap();
+		assert_eq!(
+			local_pairs,
+			remote_pairs.into_iter().map(|(key, value)| (key.0, value.0)).collect::<Vec<_>>(),
+		);
+	}
+
 	#[test]
 	fn header_proof_is_generated_and_checked() {
 		let (local_checker, local_cht_root, remote_block_header, remote_header_proof) = prepare_for_header_proof_check(true);
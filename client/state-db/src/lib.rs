@@ -35,8 +35,8 @@ mod pruning;
 
 use std::fmt;
 use parking_lot::RwLock;
-use codec::Codec;
-use std::collections::{HashMap, hash_map::Entry};
+use codec::{Codec, Decode, Encode};
+use std::collections::{HashMap, HashSet, hash_map::Entry};
 use noncanonical::NonCanonicalOverlay;
 use pruning::RefWindow;
 use log::trace;
@@ -45,6 +45,8 @@ const PRUNING_MODE: &[u8] = b"mode";
 const PRUNING_MODE_ARCHIVE: &[u8] = b"archive";
 const PRUNING_MODE_ARCHIVE_CANON: &[u8] = b"archive_canonical";
 const PRUNING_MODE_CONSTRAINED: &[u8] = b"constrained";
+const PRUNING_MODE_HYBRID: &[u8] = b"hybrid";
+const CHECKPOINT_INTERVAL: &[u8] = b"checkpoint_interval";
 
 /// Database value type.
 pub type DBValue = Vec<u8>;
@@ -139,11 +141,22 @@ pub struct Constraints {
 	pub max_mem: Option<usize>,
 }
 
+/// Hybrid pruning constraints.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Checkpoints {
+	/// Number of canonical blocks to keep the state of.
+	pub max_blocks: u32,
+	/// The state of canonical blocks whose number is a multiple of this is never pruned.
+	pub interval: u32,
+}
+
 /// Pruning mode.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PruningMode {
 	/// Maintain a pruning window.
 	Constrained(Constraints),
+	/// Maintain a pruning window, keeping the state of the checkpoint blocks out of it.
+	Hybrid(Checkpoints),
 	/// No pruning. Canonicalization is a no-op.
 	ArchiveAll,
 	/// Canonicalization discards non-canonical nodes. All the canonical nodes are kept in the DB.
@@ -159,11 +172,27 @@ impl PruningMode {
 		})
 	}
 
+	/// Create a mode that keeps given number of blocks, and every `interval`-th block.
+	pub fn keep_blocks_and_checkpoints(n: u32, interval: u32) -> PruningMode {
+		PruningMode::Hybrid(Checkpoints {
+			max_blocks: n,
+			interval,
+		})
+	}
+
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
 	pub fn is_archive(&self) -> bool {
 		match *self {
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => true,
-			PruningMode::Constrained(_) => false
+			PruningMode::Constrained(_) | PruningMode::Hybrid(_) => false
+		}
+	}
+
+	/// Interval of the checkpoint blocks, in hybrid mode.
+	pub fn checkpoint_interval(&self) -> Option<u32> {
+		match *self {
+			PruningMode::Hybrid(ref checkpoints) => Some(checkpoints.interval),
+			_ => None,
 		}
 	}
 
@@ -173,6 +202,7 @@ impl PruningMode {
 			PruningMode::ArchiveAll => PRUNING_MODE_ARCHIVE,
 			PruningMode::ArchiveCanonical => PRUNING_MODE_ARCHIVE_CANON,
 			PruningMode::Constrained(_) => PRUNING_MODE_CONSTRAINED,
+			PruningMode::Hybrid(_) => PRUNING_MODE_HYBRID,
 		}
 	}
 }
@@ -194,6 +224,8 @@ struct StateDbSync<BlockHash: Hash, Key: Hash> {
 	non_canonical: NonCanonicalOverlay<BlockHash, Key>,
 	pruning: Option<RefWindow<BlockHash, Key>>,
 	pinned: HashMap<BlockHash, u32>,
+	/// Nodes inserted by the canonicalizations not yet committed to the db.
+	pending_canonical_inserts: HashSet<Key>,
 }
 
 impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
@@ -209,7 +241,8 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 				max_mem: Some(_),
 				..
 			}) => unimplemented!(),
-			PruningMode::Constrained(_) => Some(RefWindow::new(db)?),
+			PruningMode::Constrained(_) => Some(RefWindow::new(db, None)?),
+			PruningMode::Hybrid(Checkpoints { interval, .. }) => Some(RefWindow::new(db, Some(interval as u64))?),
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
@@ -218,6 +251,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			non_canonical,
 			pruning,
 			pinned: Default::default(),
+			pending_canonical_inserts: Default::default(),
 		})
 	}
 
@@ -228,10 +262,23 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			db_mode.as_ref().map(|v| std::str::from_utf8(&v))
 		);
 		match &db_mode {
-			Some(v) if v.as_slice() == mode.id() => Ok(()),
-			Some(v) => Err(Error::InvalidPruningMode(String::from_utf8_lossy(v).into())),
-			None => Ok(()),
+			Some(v) if v.as_slice() == mode.id() => (),
+			Some(v) => return Err(Error::InvalidPruningMode(String::from_utf8_lossy(v).into())),
+			None => return Ok(()),
 		}
+
+		if let Some(interval) = mode.checkpoint_interval() {
+			let db_interval = db.get_meta(&to_meta_key(CHECKPOINT_INTERVAL, &())).map_err(Error::Db)?;
+			match db_interval {
+				Some(v) if v == interval.encode() => (),
+				Some(v) => return Err(Error::InvalidPruningMode(format!(
+					"hybrid with checkpoints every {} blocks",
+					u32::decode(&mut v.as_slice())?,
+				))),
+				None => (),
+			}
+		}
+		Ok(())
 	}
 
 	pub fn insert_block<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64, parent_hash: &BlockHash, mut changeset: ChangeSet<Key>) -> Result<CommitSet<Key>, Error<E>> {
@@ -239,6 +286,9 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 		if number == 0 {
 			// Save pruning mode when writing first block.
 			meta.inserted.push((to_meta_key(PRUNING_MODE, &()), self.mode.id().into()));
+			if let Some(interval) = self.mode.checkpoint_interval() {
+				meta.inserted.push((to_meta_key(CHECKPOINT_INTERVAL, &()), interval.encode()));
+			}
 		}
 
 		match self.mode {
@@ -250,7 +300,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 					meta: meta,
 				})
 			},
-			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => {
+			PruningMode::Constrained(_) | PruningMode::Hybrid(_) | PruningMode::ArchiveCanonical => {
 				let commit = self.non_canonical.insert(hash, number, parent_hash, changeset);
 				commit.map(|mut c| {
 					c.meta.inserted.extend(meta.inserted);
@@ -260,7 +310,9 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 		}
 	}
 
-	pub fn canonicalize_block<E: fmt::Debug>(&mut self, hash: &BlockHash) -> Result<CommitSet<Key>, Error<E>> {
+	pub fn canonicalize_block<D: NodeDb>(&mut self, hash: &BlockHash, db: &D) -> Result<CommitSet<Key>, Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
 		let mut commit = CommitSet::default();
 		if self.mode == PruningMode::ArchiveAll {
 			return Ok(commit)
@@ -273,13 +325,36 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			}
 			Err(e) => return Err(e),
 		};
+		// Nodes that are not new to the db may be part of a checkpoint. This includes the ones
+		// inserted by the canonicalizations that are pending in the same transaction.
+		let mut born = Vec::new();
+		if let PruningMode::Hybrid(_) = self.mode {
+			for (key, _) in commit.data.inserted.iter() {
+				if !self.pending_canonical_inserts.contains(key) && db.get(key.as_ref()).map_err(Error::Db)?.is_none() {
+					born.push(key.clone());
+				}
+			}
+			self.pending_canonical_inserts.extend(commit.data.inserted.iter().map(|(k, _)| k.clone()));
+		}
 		if let Some(ref mut pruning) = self.pruning {
-			pruning.note_canonical(&hash, &mut commit);
+			pruning.note_canonical(&hash, born, &mut commit);
 		}
 		self.prune(&mut commit);
 		Ok(commit)
 	}
 
+	pub fn rebase<E: fmt::Debug>(&mut self, hash: &BlockHash, number: u64) -> Result<CommitSet<Key>, Error<E>> {
+		let mut commit = CommitSet::default();
+		if self.mode == PruningMode::ArchiveAll {
+			return Ok(commit)
+		}
+		self.non_canonical.rebase(hash, number, &mut commit)?;
+		if let Some(ref mut pruning) = self.pruning {
+			pruning.rebase(number + 1, &mut commit);
+		}
+		Ok(commit)
+	}
+
 	pub fn best_canonical(&self) -> Option<u64> {
 		return self.non_canonical.last_canonicalized_block_number()
 	}
@@ -287,24 +362,33 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	pub fn is_pruned(&self, hash: &BlockHash, number: u64) -> bool {
 		match self.mode {
 			PruningMode::ArchiveAll => false,
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) | PruningMode::Hybrid(_) => {
 				if self.best_canonical().map(|c| number > c).unwrap_or(true) {
 					!self.non_canonical.have_block(hash)
 				} else {
-					self.pruning.as_ref().map_or(false, |pruning| number < pruning.pending() || !pruning.have_block(hash))
+					self.pruning.as_ref().map_or(false, |pruning| if number < pruning.pending() {
+						!pruning.is_checkpoint(number)
+					} else {
+						!pruning.have_block(hash)
+					})
 				}
 			}
 		}
 	}
 
 	fn prune(&mut self, commit: &mut CommitSet<Key>) {
-		if let (&mut Some(ref mut pruning), &PruningMode::Constrained(ref constraints)) = (&mut self.pruning, &self.mode) {
+		let (max_blocks, max_mem) = match self.mode {
+			PruningMode::Constrained(ref constraints) => (constraints.max_blocks.unwrap_or(0), constraints.max_mem),
+			PruningMode::Hybrid(ref checkpoints) => (checkpoints.max_blocks, None),
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => return,
+		};
+		if let Some(ref mut pruning) = self.pruning {
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if pruning.window_size() <= max_blocks as u64 {
 					break;
 				}
 
-				if constraints.max_mem.map_or(false, |m| pruning.mem_used() > m) {
+				if max_mem.map_or(false, |m| pruning.mem_used() > m) {
 					break;
 				}
 
@@ -325,7 +409,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 			PruningMode::ArchiveAll => {
 				Some(CommitSet::default())
 			},
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) | PruningMode::Hybrid(_) => {
 				self.non_canonical.revert_one()
 			},
 		}
@@ -334,7 +418,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	pub fn pin(&mut self, hash: &BlockHash) -> Result<(), PinError> {
 		match self.mode {
 			PruningMode::ArchiveAll => Ok(()),
-			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
+			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) | PruningMode::Hybrid(_) => {
 				if self.non_canonical.have_block(hash) ||
 					self.pruning.as_ref().map_or(false, |pruning| pruning.have_block(hash))
 				{
@@ -378,6 +462,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	}
 
 	pub fn apply_pending(&mut self) {
+		self.pending_canonical_inserts.clear();
 		self.non_canonical.apply_pending();
 		if let Some(pruning) = &mut self.pruning {
 			pruning.apply_pending();
@@ -392,6 +477,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	}
 
 	pub fn revert_pending(&mut self) {
+		self.pending_canonical_inserts.clear();
 		if let Some(pruning) = &mut self.pruning {
 			pruning.revert_pending();
 		}
@@ -419,8 +505,19 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 	}
 
 	/// Finalize a previously inserted block.
-	pub fn canonicalize_block<E: fmt::Debug>(&self, hash: &BlockHash) -> Result<CommitSet<Key>, Error<E>> {
-		self.db.write().canonicalize_block(hash)
+	///
+	/// In hybrid mode, `db` tells which of its nodes are new.
+	pub fn canonicalize_block<D: NodeDb>(&self, hash: &BlockHash, db: &D) -> Result<CommitSet<Key>, Error<D::Error>>
+		where Key: AsRef<D::Key>
+	{
+		self.db.write().canonicalize_block(hash, db)
+	}
+
+	/// Continue from a block whose ancestors are unknown, as if it was the last finalized one.
+	///
+	/// Fails if there are non-canonical blocks.
+	pub fn rebase<E: fmt::Debug>(&self, hash: &BlockHash, number: u64) -> Result<CommitSet<Key>, Error<E>> {
+		self.db.write().rebase(hash, number)
 	}
 
 	/// Prevents pruning of specified block and its descendants.
@@ -457,6 +554,26 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 		return self.db.read().is_pruned(hash, number)
 	}
 
+	/// Returns the number of the first block of the pruning window, if there is one. The state of
+	/// the canonical blocks before it is pruned, checkpoints excepted.
+	pub fn pruning_window_start(&self) -> Option<u64> {
+		self.db.read().pruning.as_ref().map(|pruning| pruning.pending())
+	}
+
+	/// Returns the interval of the blocks whose canonical state is never pruned, in hybrid mode.
+	pub fn checkpoint_interval(&self) -> Option<u32> {
+		self.db.read().mode.checkpoint_interval()
+	}
+
+	/// Re-read the journals from `db`, checking that they decode. Returns the last canonicalized
+	/// block they record.
+	pub fn check_journals<D: MetaDb>(&self, db: &D) -> Result<Option<(BlockHash, u64)>, Error<D::Error>> {
+		let mode = self.db.read().mode.clone();
+		let journaled = StateDbSync::<BlockHash, Key>::new(mode, db)?;
+		Ok(journaled.non_canonical.last_canonicalized_hash()
+			.and_then(|hash| journaled.best_canonical().map(|number| (hash, number))))
+	}
+
 	/// Apply all pending changes
 	pub fn apply_pending(&self) {
 		self.db.write().apply_pending();
@@ -472,7 +589,7 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 mod tests {
 	use std::io;
 	use sp_core::H256;
-	use crate::{StateDb, PruningMode, Constraints};
+	use crate::{StateDb, PruningMode, Constraints, Checkpoints};
 	use crate::test::{make_db, make_changeset, TestDb};
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256>) {
@@ -520,7 +637,7 @@ mod tests {
 				.unwrap(),
 		);
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(1)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(1), &db).unwrap());
 		state_db.apply_pending();
 		db.commit(
 			&state_db
@@ -533,9 +650,9 @@ mod tests {
 				.unwrap(),
 		);
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(21)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(21), &db).unwrap());
 		state_db.apply_pending();
-		db.commit(&state_db.canonicalize_block::<io::Error>(&H256::from_low_u64_be(3)).unwrap());
+		db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(3), &db).unwrap());
 		state_db.apply_pending();
 
 		(db, state_db)
@@ -589,6 +706,62 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	fn make_hybrid_test_db(checkpoints: Checkpoints) -> (TestDb, StateDb<H256, H256>) {
+		let mut db = make_db(&[]);
+		let state_db = StateDb::new(PruningMode::Hybrid(checkpoints), &db).unwrap();
+		for n in 0..5 {
+			let deleted: &[u64] = if n == 0 { &[] } else { &[9 + n] };
+			db.commit(
+				&state_db
+					.insert_block::<io::Error>(
+						&H256::from_low_u64_be(n),
+						n,
+						&H256::from_low_u64_be(n.saturating_sub(1)),
+						make_changeset(&[10 + n], deleted),
+					)
+					.unwrap(),
+			);
+			state_db.apply_pending();
+			db.commit(&state_db.canonicalize_block(&H256::from_low_u64_be(n), &db).unwrap());
+			state_db.apply_pending();
+		}
+		(db, state_db)
+	}
+
+	#[test]
+	fn hybrid_keeps_checkpoints() {
+		let (db, sdb) = make_hybrid_test_db(Checkpoints { max_blocks: 1, interval: 2 });
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(0), 0));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(1), 1));
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(2), 2));
+		assert!(sdb.is_pruned(&H256::from_low_u64_be(3), 3));
+		assert!(!sdb.is_pruned(&H256::from_low_u64_be(4), 4));
+		assert_eq!(sdb.pruning_window_start(), Some(4));
+		assert_eq!(sdb.checkpoint_interval(), Some(2));
+		assert!(db.data_eq(&make_db(&[10, 12, 13, 14])));
+	}
+
+	#[test]
+	fn detects_incompatible_checkpoint_interval() {
+		let (db, _) = make_hybrid_test_db(Checkpoints { max_blocks: 1, interval: 2 });
+		let new_mode = PruningMode::keep_blocks_and_checkpoints(1, 3);
+		let state_db: Result<StateDb<H256, H256>, _> = StateDb::new(new_mode, &db);
+		assert!(state_db.is_err());
+		let same_mode = PruningMode::keep_blocks_and_checkpoints(4, 2);
+		let state_db: Result<StateDb<H256, H256>, _> = StateDb::new(same_mode, &db);
+		assert!(state_db.is_ok());
+	}
+
+	#[test]
+	fn checks_journals() {
+		let (mut db, sdb) = make_test_db(PruningMode::keep_blocks(2));
+		assert_eq!(sdb.check_journals(&db).unwrap(), Some((H256::from_low_u64_be(3), 3)));
+
+		let journal_key = db.meta.keys().find(|k| k.ends_with(b"noncanonical_journal")).unwrap().clone();
+		db.meta.insert(journal_key, vec![1, 2, 3]);
+		assert!(sdb.check_journals(&db).is_err());
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -240,6 +240,26 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		Ok(commit)
 	}
 
+	/// Continue from `(hash, number)` as if it was the last canonicalized block.
+	///
+	/// Used for a state imported without the blocks leading to it. Fails if there are
+	/// non-canonical blocks.
+	pub fn rebase<E: fmt::Debug>(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+		commit: &mut CommitSet<Key>,
+	) -> Result<(), Error<E>> {
+		if !self.levels.is_empty() || !self.pending_canonicalizations.is_empty() {
+			return Err(Error::InvalidBlock);
+		}
+		let last_canonicalized = (hash.clone(), number);
+		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), last_canonicalized.encode()));
+		trace!(target: "state-db", "Rebased on {:?} ({})", hash, number);
+		self.last_canonicalized = Some(last_canonicalized);
+		Ok(())
+	}
+
 	fn discard_journals(
 		&self,
 		level_index: usize,
@@ -508,6 +528,32 @@ mod tests {
 		overlay.canonicalize::<io::Error>(&h2, &mut commit).unwrap();
 	}
 
+	#[test]
+	fn insert_after_rebase() {
+		let h1 = H256::random();
+		let h2 = H256::random();
+		let h3 = H256::random();
+		let mut db = make_db(&[1, 2]);
+		let mut overlay = NonCanonicalOverlay::<H256, H256>::new(&db).unwrap();
+		let insertion = overlay.insert::<io::Error>(&h1, 1, &H256::default(), ChangeSet::default()).unwrap();
+		db.commit(&insertion);
+		let mut commit = CommitSet::default();
+		assert!(overlay.rebase::<io::Error>(&h2, 9, &mut commit).is_err());
+		overlay.canonicalize::<io::Error>(&h1, &mut commit).unwrap();
+		db.commit(&commit);
+		overlay.apply_pending();
+
+		let mut commit = CommitSet::default();
+		overlay.rebase::<io::Error>(&h2, 9, &mut commit).unwrap();
+		db.commit(&commit);
+		assert!(overlay.insert::<io::Error>(&h3, 2, &h1, ChangeSet::default()).is_err());
+		let insertion = overlay.insert::<io::Error>(&h3, 10, &h2, make_changeset(&[3], &[])).unwrap();
+		db.commit(&insertion);
+		let overlay2 = NonCanonicalOverlay::<H256, H256>::new(&db).unwrap();
+		assert_eq!(overlay2.last_canonicalized, Some((h2, 9)));
+		assert_eq!(overlay2.levels.len(), 1);
+	}
+
 	#[test]
 	fn insert_canonicalize_one() {
 		let h1 = H256::random();
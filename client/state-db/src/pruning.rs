@@ -21,6 +21,11 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! With checkpoints, the state of every block whose number is a multiple of the
+//! checkpoint interval is never pruned. A node in the death list of a pruned block
+//! is then only deleted if it was new to the DB after the last checkpoint, since
+//! it cannot be part of the state of an earlier one.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use codec::{Encode, Decode};
@@ -29,6 +34,8 @@ use log::{trace, warn};
 
 const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const LAST_CHECKPOINT: &[u8] = b"last_checkpoint";
+const BORN_JOURNAL: &[u8] = b"born_journal";
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash> {
@@ -44,6 +51,12 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash> {
 	/// Number of calls of `prune_one` after
 	/// last call `apply_pending` or `revert_pending`
 	pending_prunings: usize,
+	/// Blocks whose number is a multiple of this are never pruned.
+	checkpoint_interval: Option<u64>,
+	/// Last pruned checkpoint.
+	last_checkpoint: Option<u64>,
+	/// Keys that were new to the DB in blocks pruned since the last checkpoint.
+	born: HashSet<Key>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -51,6 +64,7 @@ struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	journal_key: Vec<u8>,
 	deleted: HashSet<Key>,
+	born: HashSet<Key>,
 }
 
 #[derive(Encode, Decode)]
@@ -64,14 +78,29 @@ fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+fn to_born_journal_key(block: u64) -> Vec<u8> {
+	to_meta_key(BORN_JOURNAL, &block)
+}
+
+fn read_born_journal<D: MetaDb, Key: Hash>(db: &D, block: u64) -> Result<Vec<Key>, Error<D::Error>> {
+	match db.get_meta(&to_born_journal_key(block)).map_err(|e| Error::Db(e))? {
+		Some(record) => Ok(Decode::decode(&mut record.as_slice())?),
+		None => Ok(Vec::new()),
+	}
+}
+
 impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
-	pub fn new<D: MetaDb>(db: &D) -> Result<RefWindow<BlockHash, Key>, Error<D::Error>> {
+	pub fn new<D: MetaDb>(db: &D, checkpoint_interval: Option<u64>) -> Result<RefWindow<BlockHash, Key>, Error<D::Error>> {
 		let last_pruned = db.get_meta(&to_meta_key(LAST_PRUNED, &()))
 			.map_err(|e| Error::Db(e))?;
 		let pending_number: u64 = match last_pruned {
 			Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
 			None => 0,
 		};
+		let last_checkpoint = match db.get_meta(&to_meta_key(LAST_CHECKPOINT, &())).map_err(|e| Error::Db(e))? {
+			Some(buffer) => Some(u64::decode(&mut buffer.as_slice())?),
+			None => None,
+		};
 		let mut block = pending_number;
 		let mut pruning = RefWindow {
 			death_rows: Default::default(),
@@ -79,7 +108,15 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			pending_number: pending_number,
 			pending_canonicalizations: 0,
 			pending_prunings: 0,
+			checkpoint_interval,
+			last_checkpoint,
+			born: Default::default(),
 		};
+		if let (Some(_), Some(last_checkpoint)) = (checkpoint_interval, last_checkpoint) {
+			for pruned in last_checkpoint + 1 .. pending_number {
+				pruning.born.extend(read_born_journal(db, pruned)?);
+			}
+		}
 		// read the journal
 		trace!(target: "state-db", "Reading pruning journal. Pending #{}", pending_number);
 		loop {
@@ -88,7 +125,11 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 				Some(record) => {
 					let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
 					trace!(target: "state-db", "Pruning journal entry {} ({} inserted, {} deleted)", block, record.inserted.len(), record.deleted.len());
-					pruning.import(&record.hash, journal_key, record.inserted.into_iter(), record.deleted);
+					let born = match checkpoint_interval {
+						Some(_) => read_born_journal(db, block)?,
+						None => Vec::new(),
+					};
+					pruning.import(&record.hash, journal_key, record.inserted.into_iter(), record.deleted, born);
 				},
 				None => break,
 			}
@@ -97,7 +138,14 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		Ok(pruning)
 	}
 
-	fn import<I: IntoIterator<Item=Key>>(&mut self, hash: &BlockHash, journal_key: Vec<u8>, inserted: I, deleted: Vec<Key>) {
+	fn import<I: IntoIterator<Item=Key>>(
+		&mut self,
+		hash: &BlockHash,
+		journal_key: Vec<u8>,
+		inserted: I,
+		deleted: Vec<Key>,
+		born: Vec<Key>,
+	) {
 		// remove all re-inserted keys from death rows
 		for k in inserted {
 			if let Some(block) = self.death_index.remove(&k) {
@@ -115,6 +163,7 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 				hash: hash.clone(),
 				deleted: deleted.into_iter().collect(),
 				journal_key: journal_key,
+				born: born.into_iter().collect(),
 			}
 		);
 	}
@@ -139,12 +188,56 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		self.death_rows.iter().skip(self.pending_prunings).any(|r| r.hash == *hash)
 	}
 
+	/// Is the state of `block` kept when it gets out of the window?
+	pub fn is_checkpoint(&self, block: u64) -> bool {
+		self.checkpoint_interval.map_or(false, |interval| block % interval == 0)
+	}
+
+	/// Last checkpoint before the next block to prune, and the index of the first row of the pending
+	/// prunings that follows it (0 if it's not among them).
+	fn pending_checkpoint(&self) -> (Option<u64>, usize) {
+		let mut checkpoint = (self.last_checkpoint, 0);
+		for row in 0 .. self.pending_prunings {
+			let block = self.pending_number + row as u64;
+			if self.is_checkpoint(block) {
+				checkpoint = (Some(block), row + 1);
+			}
+		}
+		checkpoint
+	}
+
 	/// Prune next block. Expects at least one block in the window. Adds changes to `commit`.
 	pub fn prune_one(&mut self, commit: &mut CommitSet<Key>) {
 		if let Some(pruned) = self.death_rows.get(self.pending_prunings) {
 			trace!(target: "state-db", "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.pending_number + self.pending_prunings as u64;
-			commit.data.deleted.extend(pruned.deleted.iter().cloned());
+			match self.checkpoint_interval {
+				None => commit.data.deleted.extend(pruned.deleted.iter().cloned()),
+				Some(_) => {
+					let (last_checkpoint, first_row) = self.pending_checkpoint();
+					let born_since_checkpoint = |key: &Key| {
+						(first_row == 0 && self.born.contains(key)) ||
+							self.death_rows.iter()
+								.skip(first_row)
+								.take(self.pending_prunings - first_row)
+								.any(|row| row.born.contains(key))
+					};
+					let deleted = pruned.deleted.iter()
+						.filter(|key| last_checkpoint.is_none() || born_since_checkpoint(*key))
+						.cloned()
+						.collect::<Vec<_>>();
+					trace!(target: "state-db", "Keeping {} nodes of checkpoint {:?}", pruned.deleted.len() - deleted.len(), last_checkpoint);
+					commit.data.deleted.extend(deleted);
+
+					if self.is_checkpoint(index) {
+						commit.meta.inserted.push((to_meta_key(LAST_CHECKPOINT, &()), index.encode()));
+						let first_born = last_checkpoint.map_or(index, |checkpoint| checkpoint + 1);
+						commit.meta.deleted.extend((first_born ..= index).map(to_born_journal_key));
+					} else if last_checkpoint.is_none() {
+						commit.meta.deleted.push(to_born_journal_key(index));
+					}
+				},
+			}
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
 			commit.meta.deleted.push(pruned.journal_key.clone());
 			self.pending_prunings += 1;
@@ -153,8 +246,23 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		}
 	}
 
-	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
-	pub fn note_canonical(&mut self, hash: &BlockHash, commit: &mut CommitSet<Key>) {
+	/// Prune all the blocks in the window and continue it from `block`. The changes are applied
+	/// right away.
+	pub fn rebase(&mut self, block: u64, commit: &mut CommitSet<Key>) {
+		while self.window_size() > 0 {
+			self.prune_one(commit);
+		}
+		self.apply_pending();
+		if block > self.pending_number {
+			self.pending_number = block;
+			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), (block - 1).encode()));
+		}
+	}
+
+	/// Add a change set to the window. Creates a journal record and pushes it to `commit`.
+	///
+	/// `born` are the inserted keys that are new to the DB, only used with checkpoints.
+	pub fn note_canonical(&mut self, hash: &BlockHash, born: Vec<Key>, commit: &mut CommitSet<Key>) {
 		trace!(target: "state-db", "Adding to pruning window: {:?} ({} inserted, {} deleted)", hash, commit.data.inserted.len(), commit.data.deleted.len());
 		let inserted = commit.data.inserted.iter().map(|(k, _)| k.clone()).collect();
 		let deleted = ::std::mem::replace(&mut commit.data.deleted, Vec::new());
@@ -166,7 +274,10 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		let block = self.pending_number + self.death_rows.len() as u64;
 		let journal_key = to_journal_key(block);
 		commit.meta.inserted.push((journal_key.clone(), journal_record.encode()));
-		self.import(&journal_record.hash, journal_key, journal_record.inserted.into_iter(), journal_record.deleted);
+		if self.checkpoint_interval.is_some() && !born.is_empty() {
+			commit.meta.inserted.push((to_born_journal_key(block), born.encode()));
+		}
+		self.import(&journal_record.hash, journal_key, journal_record.inserted.into_iter(), journal_record.deleted, born);
 		self.pending_canonicalizations += 1;
 	}
 
@@ -179,6 +290,15 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 			for k in pruned.deleted.iter() {
 				self.death_index.remove(&k);
 			}
+			if self.is_checkpoint(self.pending_number) {
+				self.last_checkpoint = Some(self.pending_number);
+				self.born.clear();
+			} else if self.last_checkpoint.is_some() {
+				for k in pruned.deleted.iter() {
+					self.born.remove(k);
+				}
+				self.born.extend(pruned.born);
+			}
 			self.pending_number += 1;
 		}
 		self.pending_prunings = 0;
@@ -206,16 +326,22 @@ mod tests {
 	use crate::test::{make_db, make_commit, TestDb};
 
 	fn check_journal(pruning: &RefWindow<H256, H256>, db: &TestDb) {
-		let restored: RefWindow<H256, H256> = RefWindow::new(db).unwrap();
+		let restored: RefWindow<H256, H256> = RefWindow::new(db, pruning.checkpoint_interval).unwrap();
 		assert_eq!(pruning.pending_number, restored.pending_number);
 		assert_eq!(pruning.death_rows, restored.death_rows);
 		assert_eq!(pruning.death_index, restored.death_index);
+		assert_eq!(pruning.last_checkpoint, restored.last_checkpoint);
+		assert_eq!(pruning.born, restored.born);
+	}
+
+	fn born(keys: &[u64]) -> Vec<H256> {
+		keys.iter().map(|k| H256::from_low_u64_be(*k)).collect()
 	}
 
 	#[test]
 	fn created_from_empty_db() {
 		let db = make_db(&[]);
-		let pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		assert_eq!(pruning.pending_number, 0);
 		assert!(pruning.death_rows.is_empty());
 		assert!(pruning.death_index.is_empty());
@@ -224,7 +350,7 @@ mod tests {
 	#[test]
 	fn prune_empty() {
 		let db = make_db(&[]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = CommitSet::default();
 		pruning.prune_one(&mut commit);
 		assert_eq!(pruning.pending_number, 0);
@@ -237,10 +363,10 @@ mod tests {
 	#[test]
 	fn prune_one() {
 		let mut db = make_db(&[1, 2, 3]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let h = H256::random();
-		pruning.note_canonical(&h, &mut commit);
+		pruning.note_canonical(&h, Vec::new(), &mut commit);
 		db.commit(&commit);
 		assert!(pruning.have_block(&h));
 		pruning.apply_pending();
@@ -266,12 +392,12 @@ mod tests {
 	#[test]
 	fn prune_two() {
 		let mut db = make_db(&[1, 2, 3]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		pruning.apply_pending();
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
@@ -294,12 +420,12 @@ mod tests {
 	#[test]
 	fn prune_two_pending() {
 		let mut db = make_db(&[1, 2, 3]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 		let mut commit = CommitSet::default();
@@ -317,15 +443,15 @@ mod tests {
 	#[test]
 	fn reinserted_survives() {
 		let mut db = make_db(&[1, 2, 3]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 		pruning.apply_pending();
@@ -350,15 +476,15 @@ mod tests {
 	#[test]
 	fn reinserted_survivew_pending() {
 		let mut db = make_db(&[1, 2, 3]);
-		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db).unwrap();
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, None).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), &mut commit);
+		pruning.note_canonical(&H256::random(), Vec::new(), &mut commit);
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -376,4 +502,68 @@ mod tests {
 		pruning.apply_pending();
 		assert_eq!(pruning.pending_number, 3);
 	}
+
+	#[test]
+	fn checkpoints_survive() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, Some(2)).unwrap();
+		let changes = [
+			(&[][..], &[][..], &[][..]),
+			(&[4], &[1], &[4]),
+			(&[5], &[4], &[5]),
+			(&[6], &[5, 2], &[6]),
+		];
+		for (inserted, deleted, new) in changes.iter() {
+			let mut commit = make_commit(inserted, deleted);
+			pruning.note_canonical(&H256::random(), born(new), &mut commit);
+			db.commit(&commit);
+		}
+		pruning.apply_pending();
+		check_journal(&pruning, &db);
+
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit);
+		pruning.prune_one(&mut commit);
+		db.commit(&commit);
+		pruning.apply_pending();
+		// 1 is part of checkpoint 0.
+		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5, 6])));
+		assert_eq!(pruning.last_checkpoint, Some(0));
+		check_journal(&pruning, &db);
+
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit);
+		pruning.prune_one(&mut commit);
+		db.commit(&commit);
+		pruning.apply_pending();
+		// 4 was new after checkpoint 0, 5 and 2 are part of checkpoint 2.
+		assert!(db.data_eq(&make_db(&[1, 2, 3, 5, 6])));
+		assert_eq!(pruning.last_checkpoint, Some(2));
+		assert!(pruning.is_checkpoint(2));
+		assert!(!pruning.is_checkpoint(3));
+		check_journal(&pruning, &db);
+	}
+
+	#[test]
+	fn reborn_node_of_checkpoint_survives() {
+		let mut db = make_db(&[1, 2]);
+		let mut pruning: RefWindow<H256, H256> = RefWindow::new(&db, Some(2)).unwrap();
+		let changes = [
+			(&[][..], &[][..], &[][..]),
+			(&[3], &[1], &[3]),
+			(&[4], &[3], &[4]),
+			// 1 is still in the DB, so it's not new.
+			(&[1], &[4], &[]),
+			(&[5], &[1], &[5]),
+		];
+		for (inserted, deleted, new) in changes.iter() {
+			let mut commit = make_commit(inserted, deleted);
+			pruning.note_canonical(&H256::random(), born(new), &mut commit);
+			pruning.prune_one(&mut commit);
+			db.commit(&commit);
+			pruning.apply_pending();
+			check_journal(&pruning, &db);
+		}
+		assert!(db.data_eq(&make_db(&[1, 2, 4, 5])));
+	}
 }
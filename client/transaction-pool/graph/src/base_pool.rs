@@ -19,7 +19,8 @@
 //! For a more full-featured pool, have a look at the `pool` module.
 
 use std::{
-	collections::HashSet,
+	cmp::Reverse,
+	collections::{HashMap, HashSet},
 	fmt,
 	hash,
 	sync::Arc,
@@ -28,13 +29,13 @@ use std::{
 use log::{trace, debug, warn};
 use serde::Serialize;
 use sp_core::hexdisplay::HexDisplay;
-use sp_runtime::traits::Member;
+use sp_runtime::{Percent, traits::Member};
 use sp_runtime::transaction_validity::{
 	TransactionTag as Tag,
 	TransactionLongevity as Longevity,
 	TransactionPriority as Priority,
 };
-use sp_transaction_pool::{error, PoolStatus, InPoolTransaction};
+use sp_transaction_pool::{error, PoolStatus, SenderStatus, InPoolTransaction};
 
 use crate::future::{FutureTransactions, WaitingTransaction};
 use crate::ready::ReadyTransactions;
@@ -220,6 +221,7 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	/// transactions to future in case they were just stuck in verification.
 	recently_pruned: [HashSet<Tag>; RECENTLY_PRUNED_TAGS],
 	recently_pruned_index: usize,
+	senders: Option<Senders>,
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
@@ -237,9 +239,31 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready: Default::default(),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
+			senders: None,
 		}
 	}
 
+	/// Require replacements to beat the priority of what they replace by `replace_bump`.
+	pub fn with_replace_bump(mut self, replace_bump: Percent) -> Self {
+		self.ready.set_replace_bump(replace_bump);
+		self
+	}
+
+	/// Tell senders apart with `sender_of` and keep each of them within `sender_limit`.
+	///
+	/// Senders are told apart only if both are given.
+	pub fn with_sender_limit(mut self, sender_limit: Option<Limit>, sender_of: Option<SenderOf>) -> Self {
+		self.senders = match (sender_limit, sender_of) {
+			(Some(limit), Some(sender_of)) => Some(Senders {
+				limit,
+				sender_of,
+				occupancy: Default::default(),
+			}),
+			_ => None,
+		};
+		self
+	}
+
 	/// Temporary enables future transactions, runs closure and then restores
 	/// `reject_future_transactions` flag back to previous value.
 	///
@@ -288,6 +312,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 
 			let hash = tx.transaction.hash.clone();
+			self.count(&tx.transaction, false, true);
 			self.future.import(tx);
 			return Ok(Imported::Future { hash });
 		}
@@ -315,12 +340,21 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			};
 
 			// find transactions in Future that it unlocks
-			to_import.append(&mut self.future.satisfy_tags(&tx.transaction.provides));
+			let mut unlocked = self.future.satisfy_tags(&tx.transaction.provides);
+			for tx in &unlocked {
+				self.count(&tx.transaction, false, false);
+			}
+			to_import.append(&mut unlocked);
 
 			// import this transaction
 			let current_hash = tx.transaction.hash.clone();
+			let transaction = tx.transaction.clone();
 			match self.ready.import(tx) {
 				Ok(mut replaced) => {
+					self.count(&transaction, true, true);
+					for tx in &replaced {
+						self.count(tx, true, false);
+					}
 					if !first {
 						promoted.push(current_hash);
 					}
@@ -346,7 +380,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		if removed.iter().any(|tx| tx.hash == hash) {
 			// We still need to remove all transactions that we promoted
 			// since they depend on each other and will never get to the best iterator.
-			self.ready.remove_subtree(&promoted);
+			for tx in self.ready.remove_subtree(&promoted) {
+				self.count(&tx, true, false);
+			}
 
 			debug!(target: "txpool", "[{:?}] Cycle detected, bailing.", hash);
 			return Err(error::Error::CycleDetected)
@@ -388,19 +424,70 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// Makes sure that the transactions in the queues stay within provided limits.
 	///
 	/// Removes and returns worst transactions from the queues and all transactions that depend on them.
-	/// Technically the worst transaction should be evaluated by computing the entire pending set.
-	/// We use a simplified approach to remove the transaction that occupies the pool for the longest time.
+	///
+	/// A sender over its quota first loses its newest transaction, future ones before ready ones:
+	/// it is usually the one that just went over the quota and has nothing depending on it.
+	///
+	/// Then, while a queue exceeds its limit, the worst transaction is removed. Technically it
+	/// should be evaluated by computing the entire pending set. We use a simplified approach:
+	/// within the lowest priority, the transaction of the sender with the most transactions in
+	/// the pool, and among those the one that occupies the pool for the longest time.
 	pub fn enforce_limits(&mut self, ready: &Limit, future: &Limit) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = vec![];
 
+		while let Some(senders) = self.senders.as_ref() {
+			let sender = match senders.over_quota() {
+				Some(sender) => sender.to_vec(),
+				None => break,
+			};
+			let sender_of = senders.sender_of;
+			let is_sender = |tx: &Transaction<Hash, Ex>| sender_of(&tx.provides) == Some(&sender[..]);
+
+			let newest_future = self.future
+				.fold(|newest, current| {
+					if !is_sender(&current.transaction) {
+						return newest;
+					}
+					match newest {
+						None => Some(current.clone()),
+						Some(ref tx) if tx.imported_at < current.imported_at => Some(current.clone()),
+						other => other,
+					}
+				})
+				.map(|tx| tx.transaction.hash.clone());
+			let newest = newest_future.or_else(|| self.ready
+				.fold(|newest, current| {
+					let transaction = &current.transaction;
+					if !is_sender(&transaction.transaction) {
+						return newest;
+					}
+					match newest {
+						None => Some(transaction.clone()),
+						Some(ref tx) if tx.insertion_id < transaction.insertion_id => Some(transaction.clone()),
+						other => other,
+					}
+				})
+				.map(|tx| tx.transaction.hash.clone())
+			);
+
+			if let Some(newest) = newest {
+				removed.append(&mut self.remove_subtree(&[newest]))
+			} else {
+				break;
+			}
+		}
+
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+			let load = self.load();
+			let rank = |tx: &Transaction<Hash, Ex>| (tx.priority, Reverse(load(tx)));
 			// find the worst transaction
 			let minimal = self.ready
 				.fold(|minimal, current| {
 					let transaction = &current.transaction;
 					match minimal {
 						None => Some(transaction.clone()),
-						Some(ref tx) if tx.insertion_id > transaction.insertion_id => {
+						Some(ref tx) if (rank(&tx.transaction), tx.insertion_id)
+							> (rank(&transaction.transaction), transaction.insertion_id) => {
 							Some(transaction.clone())
 						},
 						other => other,
@@ -415,12 +502,15 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		}
 
 		while future.is_exceeded(self.future.len(), self.future.bytes()) {
+			let load = self.load();
+			let rank = |tx: &Transaction<Hash, Ex>| (tx.priority, Reverse(load(tx)));
 			// find the worst transaction
 			let minimal = self.future
 				.fold(|minimal, current| {
 					match minimal {
 						None => Some(current.clone()),
-						Some(ref tx) if tx.imported_at > current.imported_at => {
+						Some(ref tx) if (rank(&tx.transaction), tx.imported_at)
+							> (rank(&current.transaction), current.imported_at) => {
 							Some(current.clone())
 						},
 						other => other,
@@ -437,6 +527,20 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Number of transactions the sender of a transaction has in the pool.
+	///
+	/// Always zero when senders are not told apart.
+	fn load(&self) -> impl Fn(&Transaction<Hash, Ex>) -> usize + '_ {
+		move |tx| self.senders.as_ref().map_or(0, |senders| senders.load(tx))
+	}
+
+	/// Account for `tx` entering (`added`) or leaving the ready (`is_ready`) or future queue.
+	fn count(&mut self, tx: &Transaction<Hash, Ex>, is_ready: bool, added: bool) {
+		if let Some(ref mut senders) = self.senders {
+			senders.count(tx, is_ready, added);
+		}
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -447,13 +551,24 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// and you don't want them to be stored in the pool use `prune_tags` method.
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = self.ready.remove_subtree(hashes);
-		removed.extend(self.future.remove(hashes));
+		for tx in &removed {
+			self.count(tx, true, false);
+		}
+		let future = self.future.remove(hashes);
+		for tx in &future {
+			self.count(tx, false, false);
+		}
+		removed.extend(future);
 		removed
 	}
 
 	/// Removes and returns all transactions from the future queue.
 	pub fn clear_future(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		self.future.clear()
+		let removed = self.future.clear();
+		for tx in &removed {
+			self.count(tx, false, false);
+		}
+		removed
 	}
 
 	/// Prunes transactions that provide given list of tags.
@@ -477,6 +592,12 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			// store the tags for next submission
 			recently_pruned.insert(tag);
 		}
+		for tx in &to_import {
+			self.count(&tx.transaction, false, false);
+		}
+		for tx in &pruned {
+			self.count(tx, true, false);
+		}
 
 		let mut promoted = vec![];
 		let mut failed = vec![];
@@ -505,8 +626,19 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready_bytes: self.ready.bytes(),
 			future: self.future.len(),
 			future_bytes: self.future.bytes(),
+			senders: self.senders(),
 		}
 	}
+
+	/// Occupancy of each sender, busiest first.
+	fn senders(&self) -> Vec<SenderStatus> {
+		let mut senders = match self.senders {
+			Some(ref senders) => senders.occupancy.values().cloned().collect(),
+			None => Vec::new(),
+		};
+		senders.sort_by(|a, b| (b.ready + b.future).cmp(&(a.ready + a.future)).then_with(|| a.sender.cmp(&b.sender)));
+		senders
+	}
 }
 
 /// Queue limits
@@ -525,6 +657,74 @@ impl Limit {
 	}
 }
 
+/// Returns the sender of a transaction given the tags it provides, if they tell.
+pub type SenderOf = fn(&[Tag]) -> Option<&[u8]>;
+
+/// Limit and occupancy of each sender, over both queues.
+///
+/// The occupancy is updated as transactions enter and leave the queues.
+struct Senders {
+	limit: Limit,
+	sender_of: SenderOf,
+	occupancy: HashMap<Vec<u8>, SenderStatus>,
+}
+
+impl Senders {
+	/// Returns a sender over its quota, if any.
+	fn over_quota(&self) -> Option<&[u8]> {
+		self.occupancy.values()
+			.find(|status| self.limit.is_exceeded(status.ready + status.future, status.bytes))
+			.map(|status| &status.sender[..])
+	}
+
+	/// Number of transactions the sender of `tx` has in the pool.
+	fn load<Hash, Ex>(&self, tx: &Transaction<Hash, Ex>) -> usize {
+		(self.sender_of)(&tx.provides)
+			.and_then(|sender| self.occupancy.get(sender))
+			.map_or(0, |status| status.ready + status.future)
+	}
+
+	fn count<Hash, Ex>(&mut self, tx: &Transaction<Hash, Ex>, is_ready: bool, added: bool) {
+		let sender = match (self.sender_of)(&tx.provides) {
+			Some(sender) => sender,
+			None => return,
+		};
+		if added {
+			let status = self.occupancy.entry(sender.to_vec()).or_insert_with(|| SenderStatus {
+				sender: sender.to_vec(),
+				ready: 0,
+				future: 0,
+				bytes: 0,
+			});
+			if is_ready {
+				status.ready += 1;
+			} else {
+				status.future += 1;
+			}
+			status.bytes += tx.bytes;
+		} else if let Some(status) = self.occupancy.get_mut(sender) {
+			if is_ready {
+				status.ready = status.ready.saturating_sub(1);
+			} else {
+				status.future = status.future.saturating_sub(1);
+			}
+			status.bytes = status.bytes.saturating_sub(tx.bytes);
+			if status.ready + status.future == 0 {
+				self.occupancy.remove(sender);
+			}
+		}
+	}
+}
+
+impl fmt::Debug for Senders {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Senders")
+			.field("limit", &self.limit)
+			.field("occupancy", &self.occupancy)
+			.finish()
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1119,4 +1319,97 @@ requires: [03,02], provides: [04], data: [4]}".to_owned()
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	/// Transaction of `sender` providing `(sender, nonce)`, like `frame_system::CheckNonce`.
+	fn sender_tx(sender: u8, nonce: u8, priority: u64, requires: Vec<Tag>) -> Transaction<Hash, Vec<u8>> {
+		Transaction {
+			data: vec![sender, nonce],
+			bytes: 1,
+			hash: (sender as u64) << 8 | nonce as u64,
+			priority,
+			valid_till: 64u64,
+			requires,
+			provides: vec![vec![sender, nonce]],
+			propagate: true,
+		}
+	}
+
+	fn sender_of(provides: &[Tag]) -> Option<&[u8]> {
+		provides.first().map(|tag| &tag[..1])
+	}
+
+	fn senders(count: usize) -> BasePool<Hash, Vec<u8>> {
+		pool().with_sender_limit(Some(Limit { count, total_bytes: 1024 }), Some(sender_of))
+	}
+
+	#[test]
+	fn should_drop_the_newest_transaction_of_a_sender_over_quota() {
+		// given
+		let mut pool = senders(2);
+		let limit = Limit { count: 100, total_bytes: 1024 };
+		pool.import(sender_tx(0xB, 0, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xA, 0, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xA, 3, 5, vec![vec![0xA, 2]])).unwrap();
+		pool.import(sender_tx(0xA, 1, 5, vec![vec![0xA, 0]])).unwrap();
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit);
+
+		// then
+		let removed = removed.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+		assert_eq!(removed, vec![0xA03]);
+		assert_eq!(pool.status().senders, vec![
+			SenderStatus { sender: vec![0xA], ready: 2, future: 0, bytes: 2 },
+			SenderStatus { sender: vec![0xB], ready: 1, future: 0, bytes: 1 },
+		]);
+	}
+
+	#[test]
+	fn should_keep_sender_occupancy_up_to_date() {
+		// given
+		let mut pool = senders(100);
+		pool.import(sender_tx(0xA, 1, 5, vec![vec![0xA, 0]])).unwrap();
+		assert_eq!(pool.status().senders, vec![
+			SenderStatus { sender: vec![0xA], ready: 0, future: 1, bytes: 1 },
+		]);
+
+		// when
+		pool.import(sender_tx(0xA, 0, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xB, 0, 5, vec![])).unwrap();
+
+		// then
+		assert_eq!(pool.status().senders, vec![
+			SenderStatus { sender: vec![0xA], ready: 2, future: 0, bytes: 2 },
+			SenderStatus { sender: vec![0xB], ready: 1, future: 0, bytes: 1 },
+		]);
+
+		// when
+		pool.prune_tags(vec![vec![0xA, 0]]);
+		pool.remove_subtree(&[0xB00]);
+
+		// then
+		assert_eq!(pool.status().senders, vec![
+			SenderStatus { sender: vec![0xA], ready: 1, future: 0, bytes: 1 },
+		]);
+	}
+
+	#[test]
+	fn should_evict_the_busiest_sender_within_the_lowest_priority() {
+		// given
+		let mut pool = senders(100);
+		pool.import(sender_tx(0xB, 0, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xA, 0, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xA, 1, 5, vec![])).unwrap();
+		pool.import(sender_tx(0xC, 0, 1, vec![])).unwrap();
+
+		// when
+		let removed = pool.enforce_limits(&Limit { count: 2, total_bytes: 1024 }, &Limit { count: 2, total_bytes: 1024 });
+
+		// then
+		let removed = removed.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+		assert_eq!(removed, vec![0xC00, 0xA00]);
+		let mut ready = pool.ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![0xA01, 0xB00]);
+	}
 }
@@ -71,6 +71,7 @@ impl<H: hash::Hash + traits::Member + Serialize, H2: Clone + fmt::Debug> Listene
 		trace!(target: "txpool", "[{:?}] Ready (replaced: {:?})", tx, old);
 		self.fire(tx, |watcher| watcher.ready());
 		if let Some(old) = old {
+			self.fire(tx, |watcher| watcher.replaced(old.clone(), tx.clone()));
 			self.fire(old, |watcher| watcher.usurped(tx.clone()));
 		}
 	}
@@ -87,7 +88,10 @@ impl<H: hash::Hash + traits::Member + Serialize, H2: Clone + fmt::Debug> Listene
 		self.fire(tx, |watcher| match by {
 			Some(t) => watcher.usurped(t.clone()),
 			None => watcher.dropped(),
-		})
+		});
+		if let Some(by) = by {
+			self.fire(by, |watcher| watcher.replaced(tx.clone(), by.clone()));
+		}
 	}
 
 	/// Transaction was removed as invalid.
@@ -17,6 +17,7 @@
 use std::{
 	hash,
 	collections::HashMap,
+	path::PathBuf,
 	sync::Arc,
 };
 
@@ -30,6 +31,7 @@ use futures::{
 	future::{Either, ready, join_all},
 };
 use sp_runtime::{
+	Percent,
 	generic::BlockId,
 	traits::{self, SaturatedConversion},
 	transaction_validity::{TransactionValidity, TransactionTag as Tag, TransactionValidityError},
@@ -84,6 +86,13 @@ pub trait ChainApi: Send + Sync {
 
 	/// Returns hash and encoding length of the extrinsic.
 	fn hash_and_length(&self, uxt: &ExtrinsicFor<Self>) -> (Self::Hash, usize);
+
+	/// Returns how to tell the sender of a transaction from the tags it provides.
+	///
+	/// `None` doesn't tell senders apart, whatever `Options::sender` says.
+	fn sender_of(&self) -> Option<base::SenderOf> {
+		None
+	}
 }
 
 /// Pool configuration options.
@@ -95,6 +104,20 @@ pub struct Options {
 	pub future: base::Limit,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
+	/// Minimal bump of priority a transaction needs to replace the ones providing the same tags.
+	pub replace_bump: Percent,
+	/// Limits of each sender, told apart by `ChainApi::sender_of`; `None` doesn't tell them apart.
+	pub sender: Option<base::Limit>,
+	/// File the pending transactions are journaled to, to survive restarts.
+	///
+	/// `None` keeps them in memory only.
+	pub journal: Option<PathBuf>,
+	/// Maximum number of blocks a transaction may wait in the future queue.
+	///
+	/// `None` keeps them until the limits push them out.
+	pub future_lifetime: Option<u64>,
+	/// Number of ready transactions revalidated after each block, in rotation.
+	pub revalidation_budget: usize,
 }
 
 impl Default for Options {
@@ -109,6 +132,11 @@ impl Default for Options {
 				total_bytes: 1 * 1024 * 1024,
 			},
 			reject_future_transactions: false,
+			replace_bump: Percent::zero(),
+			sender: None,
+			journal: None,
+			future_lifetime: None,
+			revalidation_budget: 16,
 		}
 	}
 }
@@ -153,6 +181,41 @@ impl<B: ChainApi> Pool<B> {
 			))
 	}
 
+	/// Imports one unverified extrinsic in place of the ready transaction `old`.
+	///
+	/// Unlike `submit_one`, fails unless `xt` provides one of the tags of `old`, so that it
+	/// can't end up in the pool next to the transaction it should replace.
+	pub fn replace_at(
+		&self,
+		at: &BlockId<B::Block>,
+		old: ExHash<B>,
+		xt: ExtrinsicFor<B>,
+	) -> impl Future<Output=Result<ExHash<B>, B::Error>> {
+		let replaced = match self.validated_pool.ready().find(|tx| tx.hash == old) {
+			Some(replaced) => replaced,
+			None => return Either::Left(ready(Err(error::Error::ReplacedNotReady.into()))),
+		};
+
+		let validated_pool = self.validated_pool.clone();
+		Either::Right(
+			self.verify(at, std::iter::once(xt), false)
+				.map(move |validated_transactions| validated_transactions.and_then(|validated_transactions| {
+					let (_, tx) = validated_transactions
+						.into_iter()
+						.next()
+						.expect("One extrinsic passed; one result returned; qed");
+					if let ValidatedTransaction::Valid(ref tx) = tx {
+						if !tx.provides.iter().any(|tag| replaced.provides.contains(tag)) {
+							return Err(error::Error::NotAReplacement.into());
+						}
+					}
+					validated_pool.submit(std::iter::once(tx))
+						.pop()
+						.expect("One extrinsic passed; one result returned; qed")
+				}))
+		)
+	}
+
 	/// Import a single extrinsic and starts to watch their progress in the pool.
 	pub fn submit_and_watch(
 		&self,
@@ -171,10 +234,11 @@ impl<B: ChainApi> Pool<B> {
 		)
 	}
 
-	/// Revalidate all ready transactions.
+	/// Revalidate ready transactions.
 	///
-	/// Returns future that performs validation of all ready transactions and
-	/// then resubmits all transactions back to the pool.
+	/// Returns future that performs validation of up to `max` ready transactions (all of them if
+	/// `None`) and then resubmits them back to the pool. Consecutive calls with a `max` rotate
+	/// through the ready queue, so that every transaction is eventually revalidated.
 	pub fn revalidate_ready(
 		&self,
 		at: &BlockId<B::Block>,
@@ -186,9 +250,10 @@ impl<B: ChainApi> Pool<B> {
 			max.map(|x| format!("{}", x)).unwrap_or_else(|| "all".into())
 		);
 		let validated_pool = self.validated_pool.clone();
-		let ready = self.validated_pool.ready()
-			.map(|tx| tx.data.clone())
-			.take(max.unwrap_or_else(usize::max_value));
+		let ready = match max {
+			Some(max) => self.validated_pool.next_to_revalidate(max),
+			None => self.validated_pool.ready().map(|tx| tx.data.clone()).collect(),
+		};
 
 		let now = Instant::now();
 		self.verify(at, ready, false)
@@ -365,6 +430,16 @@ impl<B: ChainApi> Pool<B> {
 		self.validated_pool.ready()
 	}
 
+	/// Get the extrinsics waiting in the future queue.
+	pub fn futures(&self) -> Vec<ExtrinsicFor<B>> {
+		self.validated_pool.futures()
+	}
+
+	/// Returns the pool configuration.
+	pub fn options(&self) -> &Options {
+		self.validated_pool.options()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.validated_pool.status()
@@ -702,6 +777,29 @@ mod tests {
 		assert!(pool.validated_pool.rotator().is_banned(&hash1));
 	}
 
+	#[test]
+	fn should_revalidate_ready_transactions_in_rotation() {
+		// given
+		let api = TestApi::default();
+		let pool = Pool::new(Default::default(), api.clone());
+		let hashes = (0..3).map(|nonce| block_on(pool.submit_one(&BlockId::Number(0), uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		}))).unwrap()).collect::<Vec<_>>();
+		assert_eq!(pool.status().ready, 3);
+		api.invalidate.lock().insert(hashes[2]);
+
+		// when
+		block_on(pool.revalidate_ready(&BlockId::Number(0), Some(2))).unwrap();
+		assert_eq!(pool.status().ready, 3);
+		block_on(pool.revalidate_ready(&BlockId::Number(0), Some(2))).unwrap();
+
+		// then
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), hashes[..2].to_vec());
+	}
+
 	#[test]
 	fn should_limit_futures() {
 		// given
@@ -763,6 +861,28 @@ mod tests {
 		assert_eq!(pool.status().future, 0);
 	}
 
+	#[test]
+	fn should_only_replace_a_ready_transaction_providing_the_same_tags() {
+		// given
+		let pool = pool();
+		let xt = |nonce| uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		});
+		let hash = block_on(pool.submit_one(&BlockId::Number(0), xt(0))).unwrap();
+
+		// when
+		let not_ready = block_on(pool.replace_at(&BlockId::Number(0), 42, xt(0)));
+		let other_slot = block_on(pool.replace_at(&BlockId::Number(0), hash, xt(1)));
+
+		// then
+		assert_matches!(not_ready, Err(error::Error::ReplacedNotReady));
+		assert_matches!(other_slot, Err(error::Error::NotAReplacement));
+		assert_eq!(pool.status().ready, 1);
+	}
+
 	#[test]
 	fn should_reject_transactions_with_no_provides() {
 		// given
@@ -951,6 +1071,34 @@ mod tests {
 			assert_eq!(stream.next(), Some(TransactionStatus::Dropped));
 		}
 
+		#[test]
+		fn should_trigger_dropped_when_future_lifetime_expires() {
+			// given
+			let pool = Pool::new(Options {
+				future_lifetime: Some(0),
+				..Default::default()
+			}, TestApi::default());
+			let watcher = block_on(pool.submit_and_watch(&BlockId::Number(0), uxt(Transfer {
+				from: AccountId::from_h256(H256::from_low_u64_be(1)),
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce: 1,
+			}))).unwrap();
+			pool.validated_pool.clear_stale(&BlockId::Number(1)).unwrap();
+			assert_eq!(pool.status().future, 1);
+
+			// when
+			pool.validated_pool.clear_stale(&BlockId::Number(2)).unwrap();
+			assert_eq!(pool.status().future, 0);
+
+			// then
+			assert!(pool.validated_pool.rotator().is_banned(watcher.hash()));
+			let mut stream = futures::executor::block_on_stream(watcher.into_stream());
+			assert_eq!(stream.next(), Some(TransactionStatus::Future));
+			assert_eq!(stream.next(), Some(TransactionStatus::Dropped));
+			assert_eq!(stream.next(), None);
+		}
+
 		#[test]
 		fn should_handle_pruning_in_the_middle_of_import() {
 			// given
@@ -24,7 +24,7 @@ use std::{
 use serde::Serialize;
 use log::trace;
 use parking_lot::RwLock;
-use sp_runtime::traits::Member;
+use sp_runtime::{Percent, traits::Member};
 use sp_runtime::transaction_validity::{
 	TransactionTag as Tag,
 };
@@ -114,6 +114,8 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	ready: Arc<RwLock<HashMap<Hash, ReadyTx<Hash, Ex>>>>,
 	/// Best transactions that are ready to be included to the block without any other previous transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimal bump of priority a replacement needs over the transactions it replaces.
+	replace_bump: Percent,
 }
 
 impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
@@ -123,11 +125,17 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			replace_bump: Percent::zero(),
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Sets the minimal bump of priority a replacement needs over the transactions it replaces.
+	pub fn set_replace_bump(&mut self, replace_bump: Percent) {
+		self.replace_bump = replace_bump;
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -428,8 +436,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
-				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
+			let required = old_priority
+				.saturating_add(self.replace_bump * old_priority)
+				.saturating_add(1);
+			if tx.priority < required {
+				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority, required })
 			}
 
 			// construct a list of unlocked transactions
@@ -590,6 +601,32 @@ mod tests {
 		assert_eq!(ready.get().count(), 1);
 	}
 
+	#[test]
+	fn should_require_the_replace_bump_over_the_replaced_priority() {
+		// given
+		let mut ready = ReadyTransactions::default();
+		ready.set_replace_bump(Percent::from_percent(50));
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.priority = 10;
+		import(&mut ready, tx1).unwrap();
+
+		// when
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+		tx2.priority = 15;
+		let err = import(&mut ready, tx2.clone()).unwrap_err();
+
+		// then
+		match err {
+			error::Error::TooLowPriority { old, new, required } => assert_eq!((old, new, required), (10, 15, 16)),
+			e => panic!("Unexpected error: {:?}", e),
+		}
+		tx2.priority = 16;
+		import(&mut ready, tx2).unwrap();
+		assert_eq!(ready.get().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+	}
+
 	#[test]
 	fn should_replace_multiple_transactions_correctly() {
 		// given
@@ -72,12 +72,18 @@ pub(crate) struct ValidatedPool<B: ChainApi> {
 	>>,
 	import_notification_sinks: Mutex<Vec<mpsc::UnboundedSender<()>>>,
 	rotator: PoolRotator<ExHash<B>>,
+	/// Block at which each transaction of the future queue was first seen there.
+	future_since: Mutex<HashMap<ExHash<B>, u64>>,
+	/// Ready transactions already revalidated in the current rotation.
+	revalidated: Mutex<HashSet<ExHash<B>>>,
 }
 
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, api: B) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(options.reject_future_transactions)
+			.with_sender_limit(options.sender.clone(), api.sender_of())
+			.with_replace_bump(options.replace_bump);
 		ValidatedPool {
 			api,
 			options,
@@ -85,9 +91,16 @@ impl<B: ChainApi> ValidatedPool<B> {
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: Default::default(),
+			future_since: Default::default(),
+			revalidated: Default::default(),
 		}
 	}
 
+	/// Returns the pool configuration.
+	pub fn options(&self) -> &Options {
+		&self.options
+	}
+
 	/// Bans given set of hashes.
 	pub fn ban(&self, now: &std::time::Instant, hashes: impl IntoIterator<Item=ExHash<B>>) {
 		self.rotator.ban(now, hashes)
@@ -151,8 +164,11 @@ impl<B: ChainApi> ValidatedPool<B> {
 
 		debug!(target: "txpool", "Pool Status: {:?}", status);
 
+		let sender_over_quota = self.options.sender.as_ref().map_or(false, |limit| status.senders.iter()
+			.any(|sender| limit.is_exceeded(sender.ready + sender.future, sender.bytes)));
 		if ready_limit.is_exceeded(status.ready, status.ready_bytes)
-			|| future_limit.is_exceeded(status.future, status.future_bytes) {
+			|| future_limit.is_exceeded(status.future, status.future_bytes)
+			|| sender_over_quota {
 			// clean up the pool
 			let removed = {
 				let mut pool = self.pool.write();
@@ -426,12 +442,77 @@ impl<B: ChainApi> ValidatedPool<B> {
 		// removing old transactions
 		self.remove_invalid(&to_remove);
 		self.remove_invalid(&futures_to_remove);
+		// and the ones that waited in the future queue for too long
+		self.expire_futures(block_number);
 		// clear banned transactions timeouts
 		self.rotator.clear_timeouts(&now);
 
 		Ok(())
 	}
 
+	/// Drops transactions that waited in the future queue for longer than `future_lifetime`.
+	///
+	/// A transaction's age is counted from the first block it was seen in the future queue at.
+	/// Expired transactions are banned and reported as dropped.
+	pub fn expire_futures(&self, block_number: u64) -> Vec<ExHash<B>> {
+		let lifetime = match self.options.future_lifetime {
+			Some(lifetime) => lifetime,
+			None => return Vec::new(),
+		};
+
+		let expired = {
+			let mut pool = self.pool.write();
+			let mut future_since = self.future_since.lock();
+			let futures = pool.futures().map(|tx| tx.hash.clone()).collect::<HashSet<_>>();
+			future_since.retain(|hash, _| futures.contains(hash));
+			let expired = futures.into_iter()
+				.filter(|hash| {
+					let since = *future_since.entry(hash.clone()).or_insert(block_number);
+					block_number.saturating_sub(since) > lifetime
+				})
+				.collect::<Vec<_>>();
+			for hash in &expired {
+				future_since.remove(hash);
+			}
+			pool.remove_subtree(&expired)
+		};
+		if expired.is_empty() {
+			return Vec::new()
+		}
+
+		debug!(target: "txpool", "Dropping {} expired future transactions", expired.len());
+		let hashes = expired.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		self.rotator.ban(&time::Instant::now(), hashes.iter().cloned());
+		let mut listener = self.listener.write();
+		for hash in &hashes {
+			listener.dropped(hash, None);
+		}
+
+		hashes
+	}
+
+	/// Picks the next `max` ready transactions to revalidate.
+	///
+	/// Transactions revalidated since the rotation started are skipped; once the whole ready
+	/// queue went through, a new rotation starts from the best transaction.
+	pub fn next_to_revalidate(&self, max: usize) -> Vec<ExtrinsicFor<B>> {
+		let pool = self.pool.read();
+		let mut revalidated = self.revalidated.lock();
+		let mut next = pool.ready()
+			.filter(|tx| !revalidated.contains(&tx.hash))
+			.take(max)
+			.collect::<Vec<_>>();
+		if next.len() < max {
+			revalidated.clear();
+			let picked = next.iter().map(|tx| tx.hash.clone()).collect::<HashSet<_>>();
+			let missing = max - next.len();
+			next.extend(pool.ready().filter(|tx| !picked.contains(&tx.hash)).take(missing));
+		}
+
+		revalidated.extend(next.iter().map(|tx| tx.hash.clone()));
+		next.into_iter().map(|tx| tx.data.clone()).collect()
+	}
+
 	/// Get rotator reference.
 	#[cfg(test)]
 	pub fn rotator(&self) -> &PoolRotator<ExHash<B>> {
@@ -489,6 +570,11 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.pool.read().ready()
 	}
 
+	/// Get the extrinsics waiting in the future queue.
+	pub fn futures(&self) -> Vec<ExtrinsicFor<B>> {
+		self.pool.read().futures().map(|tx| tx.data.clone()).collect()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.pool.read().status()
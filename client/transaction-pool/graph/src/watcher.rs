@@ -88,6 +88,11 @@ impl<H: Clone, H2: Clone> Sender<H, H2> {
 		self.finalized = true;
 	}
 
+	/// Extrinsic `new` replaced `old` in the pool.
+	pub fn replaced(&mut self, old: H, new: H) {
+		self.send(TransactionStatus::Replaced { old, new });
+	}
+
 	/// Extrinsic has been included in block with given hash.
 	pub fn in_block(&mut self, hash: H2) {
 		self.send(TransactionStatus::InBlock(hash));
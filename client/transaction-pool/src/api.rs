@@ -38,6 +38,7 @@ use crate::error::{self, Error};
 pub struct FullChainApi<Client, Block> {
 	client: Arc<Client>,
 	pool: ThreadPool,
+	sender_of: Option<sc_transaction_graph::base_pool::SenderOf>,
 	_marker: PhantomData<Block>,
 }
 
@@ -53,9 +54,19 @@ impl<Client, Block> FullChainApi<Client, Block> where
 				.name_prefix("txpool-verifier")
 				.create()
 				.expect("Failed to spawn verifier threads, that are critical for node operation."),
+			sender_of: None,
 			_marker: Default::default()
 		}
 	}
+
+	/// Tell the sender of a transaction from the tags it provides with `sender_of`.
+	///
+	/// The layout of the tags is up to the runtime, so the pool doesn't tell senders apart
+	/// unless told how.
+	pub fn with_sender_of(mut self, sender_of: sc_transaction_graph::base_pool::SenderOf) -> Self {
+		self.sender_of = Some(sender_of);
+		self
+	}
 }
 
 impl<Client, Block> sc_transaction_graph::ChainApi for FullChainApi<Client, Block> where
@@ -113,6 +124,10 @@ impl<Client, Block> sc_transaction_graph::ChainApi for FullChainApi<Client, Bloc
 			(Blake2Hasher::hash(x), x.len())
 		})
 	}
+
+	fn sender_of(&self) -> Option<sc_transaction_graph::base_pool::SenderOf> {
+		self.sender_of
+	}
 }
 
 /// The transaction pool logic for light client.
@@ -0,0 +1,149 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! On-disk journal of the pending transactions of a pool.
+//!
+//! The journal is a single file holding the SCALE-encoded list of encoded extrinsics. Each
+//! extrinsic is kept opaque, so that one which no longer decodes (e.g. after a runtime upgrade)
+//! is dropped on its own instead of invalidating the whole journal.
+
+use std::{fs, io, path::PathBuf, sync::Arc, time::{Duration, Instant}};
+use codec::{Decode, Encode};
+use futures::{
+	Future, FutureExt,
+	channel::oneshot,
+	executor::{ThreadPool, ThreadPoolBuilder},
+	future::{Either, ready},
+};
+use log::warn;
+use parking_lot::Mutex;
+use sc_transaction_graph::{ChainApi, ExtrinsicFor, Pool};
+
+/// Interval between two writes of a journal by a `BackgroundWriter`.
+const WRITE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Journal of the pending transactions, stored in a file.
+///
+/// Clones share the same file, and their writes are serialized.
+#[derive(Clone)]
+pub struct Journal {
+	path: PathBuf,
+	write_lock: Arc<Mutex<()>>,
+}
+
+impl Journal {
+	/// Create a journal stored at `path`.
+	pub fn new(path: PathBuf) -> Self {
+		Journal { path, write_lock: Default::default() }
+	}
+
+	/// Read the journaled extrinsics.
+	///
+	/// A missing journal is empty; extrinsics that no longer decode are skipped.
+	pub fn load<Ex: Decode>(&self) -> io::Result<Vec<Ex>> {
+		let bytes = match fs::read(&self.path) {
+			Ok(bytes) => bytes,
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+			Err(e) => return Err(e),
+		};
+		let encoded = Vec::<Vec<u8>>::decode(&mut &bytes[..])
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+
+		Ok(encoded.into_iter().filter_map(|xt| match Ex::decode(&mut &xt[..]) {
+			Ok(xt) => Some(xt),
+			Err(e) => {
+				warn!(target: "txpool", "Dropping undecodable journaled transaction: {:?}", e);
+				None
+			},
+		}).collect())
+	}
+
+	/// Replace the content of the journal with `xts`.
+	///
+	/// The journal is written to a temporary file first, so that a crash while writing leaves
+	/// the previous journal intact.
+	pub fn store<'a, Ex: Encode + 'a>(&self, xts: impl IntoIterator<Item=&'a Ex>) -> io::Result<()> {
+		if let Some(dir) = self.path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let encoded = xts.into_iter().map(Encode::encode).collect::<Vec<_>>();
+		let _write = self.write_lock.lock();
+		let tmp = self.path.with_extension("tmp");
+		fs::write(&tmp, encoded.encode())?;
+		fs::rename(&tmp, &self.path)
+	}
+
+	/// Replace the content of the journal with the ready and future transactions of `pool`.
+	pub fn persist<A: ChainApi>(&self, pool: &Pool<A>) -> io::Result<()> {
+		self.store(&pending(pool))
+	}
+}
+
+/// The ready and future transactions of `pool`.
+fn pending<A: ChainApi>(pool: &Pool<A>) -> Vec<ExtrinsicFor<A>> {
+	let ready = pool.ready().map(|tx| tx.data.clone());
+	ready.chain(pool.futures()).collect()
+}
+
+/// Writes a journal on a thread of its own, at most once per `WRITE_INTERVAL`, so that the
+/// futures persisting a full pool don't block on the file system.
+pub(crate) struct BackgroundWriter {
+	journal: Journal,
+	thread: ThreadPool,
+	last_write: Mutex<Option<Instant>>,
+}
+
+impl BackgroundWriter {
+	/// Create a writer of `journal`.
+	pub fn new(journal: Journal) -> Self {
+		BackgroundWriter {
+			journal,
+			thread: ThreadPoolBuilder::new()
+				.pool_size(1)
+				.name_prefix("txpool-journal")
+				.create()
+				.expect("Failed to spawn the transaction pool journal thread."),
+			last_write: Default::default(),
+		}
+	}
+
+	/// Replace the content of the journal with the ready and future transactions of `pool`,
+	/// unless it was written less than `WRITE_INTERVAL` ago.
+	///
+	/// Only taking the transactions out of `pool` happens on the caller's thread; the returned
+	/// future resolves once they are written.
+	pub fn persist<A: ChainApi>(&self, pool: &Pool<A>) -> impl Future<Output=io::Result<()>> + Unpin {
+		{
+			let mut last_write = self.last_write.lock();
+			if last_write.map_or(false, |at| at.elapsed() < WRITE_INTERVAL) {
+				return Either::Left(ready(Ok(())))
+			}
+			*last_write = Some(Instant::now());
+		}
+
+		let (tx, rx) = oneshot::channel();
+		let journal = self.journal.clone();
+		let xts = pending(pool);
+		self.thread.spawn_ok(async move {
+			let _ = tx.send(journal.store(&xts));
+		});
+
+		Either::Right(rx.map(|result| match result {
+			Ok(r) => r,
+			Err(_) => Err(io::Error::new(io::ErrorKind::Other, "Journal write was canceled")),
+		}))
+	}
+}
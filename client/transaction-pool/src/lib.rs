@@ -20,6 +20,7 @@
 #![warn(unused_extern_crates)]
 
 mod api;
+mod journal;
 mod maintainer;
 
 pub mod error;
@@ -28,10 +29,12 @@ mod tests;
 
 pub use sc_transaction_graph as txpool;
 pub use crate::api::{FullChainApi, LightChainApi};
+pub use crate::journal::Journal;
 pub use crate::maintainer::{FullBasicPoolMaintainer, LightBasicPoolMaintainer};
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, io, sync::Arc};
 use futures::{Future, FutureExt};
+use log::{debug, warn};
 
 use sp_runtime::{
 	generic::BlockId,
@@ -49,6 +52,7 @@ pub struct BasicPool<PoolApi, Block>
 		PoolApi: sc_transaction_graph::ChainApi<Block=Block, Hash=Block::Hash>,
 {
 	pool: Arc<sc_transaction_graph::Pool<PoolApi>>,
+	journal: Option<Journal>,
 }
 
 impl<PoolApi, Block> BasicPool<PoolApi, Block>
@@ -57,8 +61,13 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 		PoolApi: sc_transaction_graph::ChainApi<Block=Block, Hash=Block::Hash>,
 {
 	/// Create new basic transaction pool with provided api.
+	///
+	/// If `options.journal` is set, the pending transactions are written there when the pool
+	/// is dropped; call `restore_journal` to resubmit them. To also write them while the node
+	/// runs, hand `journal()` to the pool maintainer.
 	pub fn new(options: sc_transaction_graph::Options, pool_api: PoolApi) -> Self {
 		BasicPool {
+			journal: options.journal.clone().map(Journal::new),
 			pool: Arc::new(sc_transaction_graph::Pool::new(options, pool_api)),
 		}
 	}
@@ -67,6 +76,60 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 	pub fn pool(&self) -> &Arc<sc_transaction_graph::Pool<PoolApi>> {
 		&self.pool
 	}
+
+	/// Gets the journal of the pending transactions, if there is one.
+	pub fn journal(&self) -> Option<&Journal> {
+		self.journal.as_ref()
+	}
+
+	/// Resubmit the journaled transactions at `at`.
+	///
+	/// Each of them is validated again through `ChainApi::validate_transaction`; those that are
+	/// no longer valid are dropped. Resolves to the number of transactions imported.
+	pub fn restore_journal(
+		&self,
+		at: &BlockId<Block>,
+	) -> impl Future<Output=Result<usize, PoolApi::Error>> {
+		let xts = match self.journal {
+			Some(ref journal) => journal.load().unwrap_or_else(|e| {
+				warn!(target: "txpool", "Unable to read the transaction pool journal: {:?}", e);
+				Vec::new()
+			}),
+			None => Vec::new(),
+		};
+		let journaled = xts.len();
+
+		self.pool.submit_at(at, xts, false).map(move |result| result.map(|imported| {
+			let imported = imported.into_iter().filter(Result::is_ok).count();
+			debug!(
+				target: "txpool",
+				"Restored {} journaled transactions, dropped {} stale ones",
+				imported,
+				journaled - imported,
+			);
+			imported
+		}))
+	}
+
+	/// Write the ready and future transactions to the journal, if there is one.
+	pub fn persist_journal(&self) -> io::Result<()> {
+		match self.journal {
+			Some(ref journal) => journal.persist(&self.pool),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<PoolApi, Block> Drop for BasicPool<PoolApi, Block>
+	where
+		Block: BlockT,
+		PoolApi: sc_transaction_graph::ChainApi<Block=Block, Hash=Block::Hash>,
+{
+	fn drop(&mut self) {
+		if let Err(e) = self.persist_journal() {
+			warn!(target: "txpool", "Unable to write the transaction pool journal: {:?}", e);
+		}
+	}
 }
 
 impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
@@ -95,6 +158,15 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		Box::new(self.pool.submit_one(at, xt))
 	}
 
+	fn replace_at(
+		&self,
+		at: &BlockId<Self::Block>,
+		old: TxHash<Self>,
+		xt: TransactionFor<Self>,
+	) -> Box<dyn Future<Output=Result<TxHash<Self>, Self::Error>> + Send + Unpin> {
+		Box::new(self.pool.replace_at(at, old, xt))
+	}
+
 	fn submit_and_watch(
 		&self,
 		at: &BlockId<Self::Block>,
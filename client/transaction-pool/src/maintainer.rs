@@ -41,10 +41,13 @@ use sp_transaction_pool::runtime_api::TaggedTransactionQueue;
 
 use sc_transaction_graph::{self, ChainApi};
 
+use crate::journal::{BackgroundWriter, Journal};
+
 /// Basic transaction pool maintainer for full clients.
 pub struct FullBasicPoolMaintainer<Client, PoolApi: ChainApi> {
 	pool: Arc<sc_transaction_graph::Pool<PoolApi>>,
 	client: Arc<Client>,
+	journal: Option<Arc<BackgroundWriter>>,
 }
 
 impl<Client, PoolApi: ChainApi> FullBasicPoolMaintainer<Client, PoolApi> {
@@ -53,7 +56,14 @@ impl<Client, PoolApi: ChainApi> FullBasicPoolMaintainer<Client, PoolApi> {
 		pool: Arc<sc_transaction_graph::Pool<PoolApi>>,
 		client: Arc<Client>,
 	) -> Self {
-		FullBasicPoolMaintainer { pool, client }
+		FullBasicPoolMaintainer { pool, client, journal: None }
+	}
+
+	/// Write the pending transactions to `journal` at the end of maintainance, at most every few
+	/// seconds and off the maintainance thread.
+	pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+		self.journal = journal.map(|journal| Arc::new(BackgroundWriter::new(journal)));
+		self
 	}
 }
 
@@ -97,9 +107,19 @@ where
 				),
 			}));
 
+		// Keep the journal up to date, so that a crash loses only the latest transactions.
+		let pool = self.pool.clone();
+		let journal = self.journal.clone();
+		let persist = move || match journal {
+			Some(journal) => Either::Left(journal.persist(&pool).map(move |result| if let Err(e) = result {
+				warn!(target: "txpool", "[{:?}] Unable to write the transaction pool journal: {:?}", id, e);
+			})),
+			None => Either::Right(ready(())),
+		};
+
 		// Avoid calling into runtime if there is nothing to prune from the pool anyway.
 		if self.pool.status().is_empty() {
-			return Box::new(resubmit_future)
+			return Box::new(resubmit_future.then(move |_| persist()))
 		}
 
 		let block = (self.client.header(id), self.client.block_body(&id));
@@ -127,7 +147,7 @@ where
 		};
 
 		let revalidate_future = self.pool
-			.revalidate_ready(&id, Some(16))
+			.revalidate_ready(&id, Some(self.pool.options().revalidation_budget))
 			.then(move |result| ready(match result {
 				Ok(_) => debug!(target: "txpool",
 					"[{:?}] Revalidation done: {}", id, took()
@@ -137,7 +157,7 @@ where
 				),
 			}));
 
-		Box::new(prune_future.then(|_| revalidate_future))
+		Box::new(prune_future.then(|_| revalidate_future).then(move |_| persist()))
 	}
 }
 
@@ -355,7 +375,7 @@ mod tests {
 	use super::*;
 	use futures::executor::block_on;
 	use codec::Encode;
-	use substrate_test_runtime_client::{prelude::*, runtime::{Block, Transfer}, sp_consensus::{BlockOrigin, SelectChain}};
+	use substrate_test_runtime_client::{prelude::*, runtime::{Block, Extrinsic, Transfer}, sp_consensus::{BlockOrigin, SelectChain}};
 	use sp_transaction_pool::PoolStatus;
 	use crate::api::{FullChainApi, LightChainApi};
 
@@ -392,6 +412,42 @@ mod tests {
 		assert_eq!(pool.status().future, 0);
 	}
 
+	#[test]
+	fn should_write_the_journal_after_maintainance() {
+		let (client, longest_chain) = TestClientBuilder::new().build_with_longest_chain();
+		let client = Arc::new(client);
+		let pool = sc_transaction_graph::Pool::new(Default::default(), FullChainApi::new(client.clone()));
+		let pool = Arc::new(pool);
+		let transfer = |nonce| Transfer {
+			amount: 5,
+			nonce,
+			from: AccountKeyring::Alice.into(),
+			to: Default::default(),
+		}.into_signed_tx();
+		let xts = vec![transfer(0), transfer(1)];
+		let best = longest_chain.best_chain().unwrap();
+		block_on(pool.submit_at(&BlockId::hash(best.hash()), xts.clone(), false)).unwrap();
+
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push(xts[0].clone()).unwrap();
+		let block = builder.bake().unwrap();
+		let id = BlockId::hash(block.header().hash());
+		client.import(BlockOrigin::Own, block).unwrap();
+
+		// the pool is still alive, yet the journal holds what is left of it
+		let dir = tempfile::tempdir().unwrap();
+		let journal = Journal::new(dir.path().join("journal"));
+		let maintainer = FullBasicPoolMaintainer::new(pool.clone(), client).with_journal(Some(journal.clone()));
+		block_on(maintainer.maintain(&id, &[]));
+
+		assert_eq!(journal.load::<Extrinsic>().unwrap(), vec![xts[1].clone()]);
+
+		// a journal written a moment ago is not written again.
+		std::fs::remove_file(dir.path().join("journal")).unwrap();
+		block_on(maintainer.maintain(&id, &[]));
+		assert!(!dir.path().join("journal").exists());
+	}
+
 	#[test]
 	fn should_remove_transactions_from_the_light_pool() {
 		let transaction = Transfer {
@@ -24,17 +24,19 @@ use substrate_test_runtime_client::{runtime::{AccountId, Block, Hash, Index, Ext
 use sp_runtime::{
 	generic::{self, BlockId},
 	traits::{Hash as HashT, BlakeTwo256},
-	transaction_validity::{TransactionValidity, ValidTransaction},
+	transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction},
 };
 
 struct TestApi {
 	pub modifier: Box<dyn Fn(&mut ValidTransaction) + Send + Sync>,
+	pub reject_stale: bool,
 }
 
 impl TestApi {
 	fn default() -> Self {
 		TestApi {
 			modifier: Box::new(|_| {}),
+			reject_stale: false,
 		}
 	}
 }
@@ -51,6 +53,9 @@ impl sc_transaction_graph::ChainApi for TestApi {
 		uxt: sc_transaction_graph::ExtrinsicFor<Self>,
 	) -> Self::ValidationFuture {
 		let expected = index(at);
+		if self.reject_stale && uxt.transfer().nonce < expected {
+			return futures::future::ready(Ok(Err(InvalidTransaction::Stale.into())));
+		}
 		let requires = if expected == uxt.transfer().nonce {
 			vec![]
 		} else {
@@ -220,3 +225,29 @@ fn should_correctly_prune_transactions_providing_more_than_one_tag() {
 	assert_eq!(pool.status().ready, 0);
 	assert_eq!(pool.status().future, 2);
 }
+
+#[test]
+fn journal_should_be_restored_without_stale_transactions() {
+	let dir = tempfile::tempdir().unwrap();
+	let options = || sc_transaction_graph::Options {
+		journal: Some(dir.path().join("journal")),
+		..Default::default()
+	};
+	let pool = BasicPool::new(options(), TestApi::default());
+	let xts = vec![uxt(Alice, 209), uxt(Alice, 210), uxt(Alice, 212)];
+	block_on(pool.pool().submit_at(&BlockId::number(0), xts, false)).unwrap();
+	assert_eq!(pool.pool().status().ready, 2);
+	assert_eq!(pool.pool().status().future, 1);
+	drop(pool);
+
+	// 209 was included in block 1 while the node was down.
+	let mut api = TestApi::default();
+	api.reject_stale = true;
+	let pool = BasicPool::new(options(), api);
+	assert_eq!(block_on(pool.restore_journal(&BlockId::number(1))).unwrap(), 2);
+
+	let pending: Vec<_> = pool.pool().ready().map(|a| a.data.transfer().nonce).collect();
+	assert_eq!(pending, vec![210]);
+	let future: Vec<_> = pool.pool().futures().iter().map(|xt| xt.transfer().nonce).collect();
+	assert_eq!(future, vec![212]);
+}
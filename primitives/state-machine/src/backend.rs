@@ -154,6 +154,59 @@ pub trait Backend<H: Hasher>: std::fmt::Debug {
 		all
 	}
 
+	/// Get at most `count` keys with given prefix, in lexicographic order.
+	///
+	/// When `start_key` is given, only keys strictly after it are returned, so the last key
+	/// of a page can be used to request the next one.
+	fn keys_paged(
+		&self,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Result<Vec<Vec<u8>>, Self::Error> {
+		let mut keys = Vec::new();
+		if count == 0 {
+			return Ok(keys);
+		}
+		let mut cursor = match start_key {
+			Some(start_key) if start_key >= prefix => start_key.to_vec(),
+			_ => {
+				if self.exists_storage(prefix)? {
+					keys.push(prefix.to_vec());
+				}
+				prefix.to_vec()
+			},
+		};
+		while keys.len() < count {
+			match self.next_storage_key(&cursor)? {
+				Some(key) if key.starts_with(prefix) => {
+					keys.push(key.clone());
+					cursor = key;
+				},
+				_ => break,
+			}
+		}
+		Ok(keys)
+	}
+
+	/// Get at most `count` key/value pairs with given prefix, in lexicographic order.
+	///
+	/// See `keys_paged` for the meaning of `start_key`.
+	fn pairs_paged(
+		&self,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+		let mut pairs = Vec::new();
+		for key in self.keys_paged(prefix, start_key, count)? {
+			if let Some(value) = self.storage(&key)? {
+				pairs.push((key, value));
+			}
+		}
+		Ok(pairs)
+	}
+
 	/// Get all keys of child storage with given prefix
 	fn child_keys(
 		&self,
@@ -621,6 +621,43 @@ where
 	Ok(proving_backend.extract_proof())
 }
 
+/// Generate proof of a page of key/value pairs with given prefix, as read by
+/// `Backend::pairs_paged`.
+pub fn prove_read_keys<B, H>(
+	mut backend: B,
+	prefix: &[u8],
+	start_key: Option<&[u8]>,
+	count: usize,
+) -> Result<StorageProof, Box<dyn Error>>
+where
+	B: Backend<H>,
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let trie_backend = backend.as_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+	prove_read_keys_on_trie_backend(trie_backend, prefix, start_key, count)
+}
+
+/// Generate proof of a page of key/value pairs with given prefix on pre-created trie backend.
+pub fn prove_read_keys_on_trie_backend<S, H>(
+	trie_backend: &TrieBackend<S, H>,
+	prefix: &[u8],
+	start_key: Option<&[u8]>,
+	count: usize,
+) -> Result<StorageProof, Box<dyn Error>>
+where
+	S: trie_backend_essence::TrieBackendStorage<H>,
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let proving_backend = proving_backend::ProvingBackend::<_, H>::new(trie_backend);
+	proving_backend
+		.pairs_paged(prefix, start_key, count)
+		.map_err(|e| Box::new(e) as Box<dyn Error>)?;
+	Ok(proving_backend.extract_proof())
+}
+
 /// Check storage read proof, generated by `prove_read` call.
 pub fn read_proof_check<H, I>(
 	root: H::Out,
@@ -668,6 +705,27 @@ where
 	Ok(result)
 }
 
+/// Check proof of a page of key/value pairs, generated by `prove_read_keys` call.
+///
+/// Fails if the proof misses any node needed to enumerate the page, so a remote node
+/// can't omit keys from it.
+pub fn read_keys_proof_check<H>(
+	root: H::Out,
+	proof: StorageProof,
+	prefix: &[u8],
+	start_key: Option<&[u8]>,
+	count: usize,
+) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Box<dyn Error>>
+where
+	H: Hasher,
+	H::Out: Ord + Codec,
+{
+	let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+	proving_backend
+		.pairs_paged(prefix, start_key, count)
+		.map_err(|e| Box::new(e) as Box<dyn Error>)
+}
+
 /// Check storage read proof on pre-created proving backend.
 pub fn read_proof_check_on_proving_backend<H>(
 	proving_backend: &TrieBackend<MemoryDB<H>, H>,
@@ -1068,6 +1126,41 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_read_keys_and_proof_check_works() {
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let remote_proof = prove_read_keys(remote_backend, b"value", None, 1).unwrap();
+		let local_result = read_keys_proof_check::<Blake2Hasher>(
+			remote_root,
+			remote_proof.clone(),
+			b"value",
+			None,
+			1,
+		).unwrap();
+		assert_eq!(local_result, vec![(b"value1".to_vec(), vec![42])]);
+
+		// the proof doesn't cover the whole prefix
+		assert!(read_keys_proof_check::<Blake2Hasher>(
+			remote_root,
+			remote_proof,
+			b"",
+			None,
+			10,
+		).is_err());
+
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_proof = prove_read_keys(remote_backend, b"value", Some(b"value1"), 10).unwrap();
+		let local_result = read_keys_proof_check::<Blake2Hasher>(
+			remote_root,
+			remote_proof,
+			b"value",
+			Some(b"value1"),
+			10,
+		).unwrap();
+		assert_eq!(local_result, vec![(b"value2".to_vec(), vec![24])]);
+	}
+
 	#[test]
 	fn cannot_change_changes_trie_config() {
 		let backend = trie_backend::tests::test_trie();
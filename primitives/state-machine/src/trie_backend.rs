@@ -316,6 +316,28 @@ pub mod tests {
 		).pairs().is_empty());
 	}
 
+	#[test]
+	fn keys_paged_continues_from_start_key() {
+		let test_trie = test_trie();
+		assert_eq!(
+			test_trie.keys_paged(b"value", None, 1).unwrap(),
+			vec![b"value1".to_vec()],
+		);
+		assert_eq!(
+			test_trie.keys_paged(b"value", Some(b"value1"), 10).unwrap(),
+			vec![b"value2".to_vec()],
+		);
+		assert_eq!(
+			test_trie.keys_paged(b"key", None, 10).unwrap(),
+			vec![b"key".to_vec()],
+		);
+		assert!(test_trie.keys_paged(b"value", Some(b"value2"), 10).unwrap().is_empty());
+		assert_eq!(
+			test_trie.pairs_paged(b"", Some(b"value1"), 2).unwrap(),
+			vec![(b"value2".to_vec(), vec![24]), (vec![128], vec![128])],
+		);
+	}
+
 	#[test]
 	fn storage_root_is_non_default() {
 		assert!(test_trie().storage_root(::std::iter::empty()).0 != H256::repeat_byte(0));
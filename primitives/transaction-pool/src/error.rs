@@ -45,13 +45,21 @@ pub enum Error {
 	#[display(fmt="[{:?}] Already imported", _0)]
 	AlreadyImported(Box<dyn std::any::Any + Send>),
 	/// The transaction cannot be imported cause it's a replacement and has too low priority.
-	#[display(fmt="Too low priority ({} > {})", old, new)]
+	#[display(fmt="Too low priority ({} > {}, {} required)", old, new, required)]
 	TooLowPriority {
 		/// Transaction already in the pool.
 		old: Priority,
 		/// Transaction entering the pool.
-		new: Priority
+		new: Priority,
+		/// Lowest priority a replacement needs, given the pool's minimal bump.
+		required: Priority,
 	},
+	/// The transaction to replace is not in the ready queue.
+	#[display(fmt="The transaction to replace is not ready in the pool.")]
+	ReplacedNotReady,
+	/// The replacement doesn't provide any tag of the transaction it should replace.
+	#[display(fmt="The transaction doesn't provide any tag of the one it should replace.")]
+	NotAReplacement,
 	/// Deps cycle etected and we couldn't import transaction.
 	#[display(fmt="Cycle Detected")]
 	CycleDetected,
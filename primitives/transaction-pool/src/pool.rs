@@ -35,7 +35,8 @@ use sp_runtime::{
 };
 
 /// Transaction pool status.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PoolStatus {
 	/// Number of transactions in the ready queue.
 	pub ready: usize,
@@ -45,6 +46,25 @@ pub struct PoolStatus {
 	pub future: usize,
 	/// Sum of bytes of ready transaction encodings.
 	pub future_bytes: usize,
+	/// Occupancy of each sender, busiest first.
+	///
+	/// Empty unless the pool is configured to tell senders apart.
+	pub senders: Vec<SenderStatus>,
+}
+
+/// Transactions of one sender in the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderStatus {
+	/// The sender, as told from the `provides` tags of its transactions.
+	#[serde(with = "sp_core::bytes")]
+	pub sender: Vec<u8>,
+	/// Number of its transactions in the ready queue.
+	pub ready: usize,
+	/// Number of its transactions in the future queue.
+	pub future: usize,
+	/// Sum of bytes of its transaction encodings.
+	pub bytes: usize,
 }
 
 impl PoolStatus {
@@ -65,6 +85,7 @@ impl PoolStatus {
 ///		- `Ready`
 /// 2. Inside `Ready` queue:
 ///		- `Broadcast`
+///		- `Replaced`
 /// 3. Leaving the pool:
 ///		- `InBlock`
 ///		- `Invalid`
@@ -102,6 +123,15 @@ pub enum TransactionStatus<Hash, BlockHash> {
 	/// Transaction has been replaced in the pool, by another transaction
 	/// that provides the same tags. (e.g. same (sender, nonce)).
 	Usurped(Hash),
+	/// Transaction has replaced another one in the pool, which is `Usurped`.
+	///
+	/// Sent to the replacement, so that both hashes are known to its submitter.
+	Replaced {
+		/// The replaced transaction.
+		old: Hash,
+		/// The transaction that replaced it.
+		new: Hash,
+	},
 	/// Transaction has been dropped from the pool because of the limit.
 	Dropped,
 	/// Transaction is no longer valid in the current state.
@@ -183,6 +213,17 @@ pub trait TransactionPool: Send + Sync {
 		Self::Error
 	>> + Send + Unpin>;
 
+	/// Returns a future that imports `xt` in place of the ready transaction `old`.
+	///
+	/// Fails unless `xt` provides one of the tags of `old` and beats its priority by the
+	/// minimal bump of the pool.
+	fn replace_at(
+		&self,
+		at: &BlockId<Self::Block>,
+		old: TxHash<Self>,
+		xt: TransactionFor<Self>,
+	) -> Box<dyn Future<Output=Result<TxHash<Self>, Self::Error>> + Send + Unpin>;
+
 	/// Returns a future that import a single transaction and starts to watch their progress in the pool.
 	fn submit_and_watch(
 		&self,
@@ -277,6 +318,11 @@ impl<Pool, Maintainer> MaintainableTransactionPool<Pool, Maintainer> {
 	pub fn new(pool: Pool, maintainer: Maintainer) -> Self {
 		MaintainableTransactionPool { pool, maintainer }
 	}
+
+	/// Gets a reference to the underlying pool.
+	pub fn pool(&self) -> &Pool {
+		&self.pool
+	}
 }
 
 impl<Pool, Maintainer> TransactionPool for MaintainableTransactionPool<Pool, Maintainer>
@@ -305,6 +351,15 @@ impl<Pool, Maintainer> TransactionPool for MaintainableTransactionPool<Pool, Mai
 		self.pool.submit_one(at, xt)
 	}
 
+	fn replace_at(
+		&self,
+		at: &BlockId<Self::Block>,
+		old: TxHash<Self>,
+		xt: TransactionFor<Self>,
+	) -> Box<dyn Future<Output=Result<TxHash<Self>, Self::Error>> + Send + Unpin> {
+		self.pool.replace_at(at, old, xt)
+	}
+
 	fn submit_and_watch(
 		&self,
 		at: &BlockId<Self::Block>,
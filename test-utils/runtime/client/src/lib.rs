@@ -37,7 +37,7 @@ use sc_client::{
 	light::fetcher::{
 		Fetcher,
 		RemoteHeaderRequest, RemoteReadRequest, RemoteReadChildRequest,
-		RemoteCallRequest, RemoteChangesRequest, RemoteBodyRequest,
+		RemoteReadKeysRequest, RemoteCallRequest, RemoteChangesRequest, RemoteBodyRequest,
 	},
 };
 
@@ -300,6 +300,7 @@ impl LightFetcher {
 impl Fetcher<substrate_test_runtime::Block> for LightFetcher {
 	type RemoteHeaderResult = FetcherFutureResult<substrate_test_runtime::Header>;
 	type RemoteReadResult = FetcherFutureResult<HashMap<Vec<u8>, Option<Vec<u8>>>>;
+	type RemoteReadKeysResult = FetcherFutureResult<Vec<(Vec<u8>, Vec<u8>)>>;
 	type RemoteCallResult = FetcherFutureResult<Vec<u8>>;
 	type RemoteChangesResult = FetcherFutureResult<Vec<(NumberFor<substrate_test_runtime::Block>, u32)>>;
 	type RemoteBodyResult = FetcherFutureResult<Vec<substrate_test_runtime::Extrinsic>>;
@@ -316,6 +317,10 @@ impl Fetcher<substrate_test_runtime::Block> for LightFetcher {
 		unimplemented!()
 	}
 
+	fn remote_read_keys(&self, _: RemoteReadKeysRequest<substrate_test_runtime::Header>) -> Self::RemoteReadKeysResult {
+		unimplemented!()
+	}
+
 	fn remote_call(&self, req: RemoteCallRequest<substrate_test_runtime::Header>) -> Self::RemoteCallResult {
 		match self.call {
 			Some(ref call) => futures::future::ready(call(req)),
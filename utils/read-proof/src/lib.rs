@@ -0,0 +1,143 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline verification of storage read proofs.
+//!
+//! Proofs returned by the `state_getReadProof` and `state_getChildReadProof` RPC methods are
+//! checked against the state root of a header the caller trusts, so the values they prove
+//! don't depend on trusting the node that served them.
+
+#![warn(missing_docs)]
+
+use std::collections::HashMap;
+use sp_runtime::traits::{Header as HeaderT, Hash as HashT};
+use sp_state_machine::{read_proof_check, read_child_proof_check};
+
+pub use sp_state_machine::StorageProof;
+
+/// Read proof verification error.
+#[derive(Debug, derive_more::Display)]
+pub enum Error {
+	/// The proof is not valid for the state root of the header.
+	#[display(fmt = "Invalid read proof: {}", _0)]
+	InvalidProof(String),
+}
+
+impl std::error::Error for Error {}
+
+/// Values proven by a read proof, `None` for the keys proven to be absent.
+pub type ProvenValues = HashMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// Builds a proof from the trie nodes returned by the RPC.
+pub fn proof_from_nodes<I>(nodes: I) -> StorageProof where
+	I: IntoIterator,
+	I::Item: Into<Vec<u8>>,
+{
+	StorageProof::new(nodes.into_iter().map(Into::into).collect())
+}
+
+/// Checks a read proof of `keys` against the state root of `header`.
+///
+/// Fails if the proof misses any of the keys.
+pub fn verify_read_proof<Header, I>(
+	header: &Header,
+	proof: StorageProof,
+	keys: I,
+) -> Result<ProvenValues, Error> where
+	Header: HeaderT,
+	Header::Hash: Ord,
+	I: IntoIterator,
+	I::Item: AsRef<[u8]>,
+{
+	read_proof_check::<<Header::Hashing as HashT>::Hasher, _>(*header.state_root(), proof, keys)
+		.map_err(|e| Error::InvalidProof(e.to_string()))
+}
+
+/// Checks a read proof of `keys` in the child trie at `child_storage_key` against the state
+/// root of `header`.
+///
+/// Fails if the proof misses any of the keys.
+pub fn verify_child_read_proof<Header, I>(
+	header: &Header,
+	proof: StorageProof,
+	child_storage_key: &[u8],
+	keys: I,
+) -> Result<ProvenValues, Error> where
+	Header: HeaderT,
+	Header::Hash: Ord,
+	I: IntoIterator,
+	I::Item: AsRef<[u8]>,
+{
+	read_child_proof_check::<<Header::Hashing as HashT>::Hasher, _>(
+		*header.state_root(),
+		proof,
+		child_storage_key,
+		keys,
+	).map_err(|e| Error::InvalidProof(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::storage::ChildInfo;
+	use sp_runtime::generic::BlockId;
+	use sp_state_machine::Backend as _;
+	use substrate_test_runtime_client::{prelude::*, runtime::Header};
+
+	const KEY: &[u8] = b":mock";
+	const VALUE: &[u8] = b"hello world";
+	const CHILD_STORAGE_KEY: &[u8] = b":child_storage:default:child";
+	const CHILD_INFO: ChildInfo<'static> = ChildInfo::new_default(b"unique_id");
+
+	fn genesis_header(client: &TestClient) -> Header {
+		let at = BlockId::Number(0);
+		let mut header = client.header(&at).unwrap().unwrap();
+		// the test genesis header doesn't commit to the extra storage
+		header.state_root = client.state_at(&at).unwrap().storage_root(std::iter::empty()).0;
+		header
+	}
+
+	#[test]
+	fn verifies_read_proof_against_header() {
+		let client = TestClientBuilder::new()
+			.add_extra_storage(KEY.to_vec(), VALUE.to_vec())
+			.build();
+		let at = BlockId::Number(0);
+		let mut header = genesis_header(&client);
+		let proof = client.read_proof(&at, &[KEY]).unwrap();
+		let nodes: Vec<Vec<u8>> = proof.clone().iter_nodes().collect();
+
+		let values = verify_read_proof(&header, proof_from_nodes(nodes), &[KEY]).unwrap();
+		assert_eq!(values.get(KEY), Some(&Some(VALUE.to_vec())));
+
+		// the proof doesn't hold for another state root
+		header.state_root = Default::default();
+		assert!(verify_read_proof(&header, proof, &[KEY]).is_err());
+	}
+
+	#[test]
+	fn verifies_child_read_proof_against_header() {
+		let client = TestClientBuilder::new()
+			.add_extra_child_storage(CHILD_STORAGE_KEY.to_vec(), CHILD_INFO, KEY.to_vec(), VALUE.to_vec())
+			.build();
+		let at = BlockId::Number(0);
+		let header = genesis_header(&client);
+		let proof = client.read_child_proof(&at, CHILD_STORAGE_KEY, CHILD_INFO, &[KEY]).unwrap();
+
+		let values = verify_child_read_proof(&header, proof, CHILD_STORAGE_KEY, &[KEY]).unwrap();
+		assert_eq!(values.get(KEY), Some(&Some(VALUE.to_vec())));
+	}
+}